@@ -4,4 +4,9 @@ use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 /// for renderers like wgpu without depending on winit.
 pub trait SurfaceProvider: HasWindowHandle + HasDisplayHandle {
     fn size(&self) -> (u32, u32);
+
+    /// Physical pixels per logical pixel (2.0 on most HiDPI displays).
+    fn scale_factor(&self) -> f64 {
+        1.0
+    }
 }
@@ -14,6 +14,9 @@ pub struct WindowConfig {
     pub continuous: Option<bool>,
     pub target_fps: Option<u32>,
     pub vsync: Option<bool>,
+    /// Samples per pixel for multisample anti-aliasing (1, 2, 4, 8 or 16); `1` turns
+    /// it off. Renderers lower it to the highest count the GPU supports.
+    pub msaa_samples: Option<u32>,
 
     /// Whether to grab/lock the cursor inside the window (FPS mouse look).
     pub cursor_grab: Option<bool>,
@@ -40,6 +43,7 @@ impl Default for WindowConfig {
             continuous: Some(false),
             target_fps: None,
             vsync: Some(false),
+            msaa_samples: Some(1),
 
             cursor_grab: Some(false),
             cursor_visible: Some(true),
@@ -50,7 +54,7 @@ impl Default for WindowConfig {
 }
 
 impl WindowConfig {
-    /// Validate width/height and target_fps if provided.
+    /// Validate width/height, target_fps and msaa_samples if provided.
     pub fn validate(&self) -> Result<(), String> {
         if let (Some(w), Some(h)) = (self.width, self.height)
             && (w == 0 || h == 0)
@@ -64,6 +68,12 @@ impl WindowConfig {
             return Err("target_fps must be > 0".into());
         }
 
+        if let Some(samples) = self.msaa_samples
+            && !matches!(samples, 1 | 2 | 4 | 8 | 16)
+        {
+            return Err("msaa_samples must be 1, 2, 4, 8 or 16".into());
+        }
+
         Ok(())
     }
 
@@ -127,6 +137,10 @@ impl WindowConfigBuilder {
         self.config.vsync = Some(v);
         self
     }
+    pub fn msaa_samples(mut self, samples: u32) -> Self {
+        self.config.msaa_samples = Some(samples);
+        self
+    }
 
     pub fn cursor_grab(mut self, v: bool) -> Self {
         self.config.cursor_grab = Some(v);
@@ -810,4 +810,8 @@ impl SurfaceProvider for Window {
         let size = self.inner_size();
         (size.width, size.height)
     }
+
+    fn scale_factor(&self) -> f64 {
+        Window::scale_factor(self)
+    }
 }
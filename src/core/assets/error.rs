@@ -1,3 +1,4 @@
+use super::image::ImageId;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -31,6 +32,12 @@ pub enum AssetError {
     #[error("Invalid font size: {font_size}")]
     InvalidFontSize { font_size: f32 },
 
+    #[error("Image {0:?} is not loaded")]
+    ImageNotLoaded(ImageId),
+
+    #[error("Invalid image data: {0}")]
+    InvalidImageData(String),
+
     #[error("Out of memory")]
     OutOfMemory,
 
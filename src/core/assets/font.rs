@@ -57,4 +57,7 @@ pub struct FontAsset {
     pub font_size: f32,
     /// Line height (baseline → baseline)
     pub line_height: f32,
+    /// Atlas texels per glyph pixel. Glyph sizes, bearings and UVs are the same at
+    /// every raster scale.
+    pub raster_scale: f32,
 }
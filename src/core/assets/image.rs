@@ -13,4 +13,82 @@ pub struct ImageAsset {
     pub width: u32,
     pub height: u32,
     pub data: Vec<u8>,
+    /// How the renderer samples the image once uploaded.
+    pub sampler: SamplerOptions,
+}
+
+/// Check that `data` holds exactly `width` x `height` RGBA8 pixels. The error message
+/// is wrapped in the caller's error type.
+pub(crate) fn check_rgba8(width: u32, height: u32, data: &[u8]) -> Result<(), String> {
+    let expected = width as usize * height as usize * 4;
+    if width == 0 || height == 0 || data.len() != expected {
+        return Err(format!(
+            "expected {} bytes for a {}x{} RGBA8 image, got {}",
+            expected,
+            width,
+            height,
+            data.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Texture filtering used when an image is scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FilterMode {
+    /// Blocky, exact texels (pixel art).
+    Nearest,
+    /// Smooth bilinear interpolation.
+    #[default]
+    Linear,
+}
+
+/// What happens when UVs fall outside `0..1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AddressMode {
+    /// Repeat the edge texels.
+    #[default]
+    ClampToEdge,
+    /// Tile the image.
+    Repeat,
+    /// Tile the image, mirroring every other repetition.
+    MirrorRepeat,
+}
+
+/// Per-image sampling options, set at load time or later through `AssetManager`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SamplerOptions {
+    pub filter: FilterMode,
+    pub address_mode: AddressMode,
+    /// Generate a mipmap chain on upload, for images drawn much smaller than their
+    /// size. Ignored for canvases.
+    pub mipmaps: bool,
+}
+
+impl SamplerOptions {
+    /// Nearest filtering with clamped edges: crisp pixel art.
+    pub fn pixel_art() -> Self {
+        Self {
+            filter: FilterMode::Nearest,
+            ..Self::default()
+        }
+    }
+
+    /// Builder: set the filter mode.
+    pub fn with_filter(mut self, filter: FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Builder: set the address mode (e.g. `Repeat` for tiled backgrounds).
+    pub fn with_address_mode(mut self, address_mode: AddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
+
+    /// Builder: enable or disable mipmap generation.
+    pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
 }
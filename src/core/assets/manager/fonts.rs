@@ -1,10 +1,18 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 use super::super::cache::FontKey;
 use super::super::error::{AssetError, AssetResult};
 use super::super::font::{FontAsset, FontCharset, FontId, Glyph};
-use super::super::image::ImageAsset;
+use super::super::image::{ImageAsset, ImageId, SamplerOptions};
 use super::AssetManager;
+use crate::math::Vec2;
+
+/// Largest glyph atlas side, in atlas units (texels at a raster scale of 1). Atlases
+/// are cropped to the glyphs packed into them.
+const ATLAS_SIZE: u32 = 1024;
+/// Largest raster scale; keeps atlases within common texture size limits.
+const MAX_RASTER_SCALE: f32 = 4.0;
 
 impl AssetManager {
     pub fn load_font<P: AsRef<Path>>(&mut self, path: P, font_size: f32) -> AssetResult<FontId> {
@@ -40,9 +48,7 @@ impl AssetManager {
         font_size: f32,
         charset: FontCharset,
     ) -> AssetResult<FontId> {
-        use crate::math::Vec2;
         use fontdue::Font;
-        use std::collections::HashMap;
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
@@ -87,15 +93,6 @@ impl AssetManager {
         let font = Font::from_bytes(data.clone(), fontdue::FontSettings::default())
             .map_err(|_| AssetError::InvalidFont)?;
 
-        // Prepare to rasterize glyphs into an atlas
-        const ATLAS_SIZE: u32 = 1024;
-        let mut atlas_pixels = vec![0u8; (ATLAS_SIZE * ATLAS_SIZE) as usize];
-        let mut glyphs = HashMap::new();
-
-        let mut pen_x = 0u32;
-        let mut pen_y = 0u32;
-        let mut row_height = 0u32;
-
         let mut chars: Vec<char> = match charset {
             FontCharset::Ascii => (0x20u32..=0x7Eu32).filter_map(char::from_u32).collect(),
             FontCharset::Latin1 => (0x20u32..=0xFFu32).filter_map(char::from_u32).collect(),
@@ -114,90 +111,8 @@ impl AssetManager {
         chars.sort_unstable();
         chars.dedup();
 
-        for ch in chars {
-            let (metrics, bitmap) = font.rasterize(ch, font_size);
-
-            if metrics.width == 0 || metrics.height == 0 {
-                glyphs.insert(
-                    ch,
-                    Glyph {
-                        uv_min: Vec2::ZERO,
-                        uv_max: Vec2::ZERO,
-                        size: Vec2::ZERO,
-                        // fontdue metrics:
-                        // - xmin: offset of the left-most bitmap edge from the origin.
-                        // - ymin: offset of the bottom-most bitmap edge from the baseline (Y-up).
-                        bearing: Vec2::new(metrics.xmin as f32, metrics.ymin as f32),
-                        advance: metrics.advance_width,
-                    },
-                );
-
-                pen_x += metrics.advance_width.ceil() as u32;
-                continue;
-            }
-
-            if pen_x + metrics.width as u32 >= ATLAS_SIZE {
-                pen_x = 0;
-                pen_y += row_height + 1;
-                row_height = 0;
-            }
-
-            if pen_y + metrics.height as u32 >= ATLAS_SIZE {
-                return Err(AssetError::OutOfMemory);
-            }
-
-            // Copy bitmap into atlas
-            for y in 0..metrics.height {
-                for x in 0..metrics.width {
-                    let src = x + y * metrics.width;
-                    let dst = (pen_x + x as u32) + (pen_y + y as u32) * ATLAS_SIZE;
-
-                    atlas_pixels[dst as usize] = bitmap[src];
-                }
-            }
-
-            let uv_min = Vec2::new(
-                pen_x as f32 / ATLAS_SIZE as f32,
-                pen_y as f32 / ATLAS_SIZE as f32,
-            );
-
-            let uv_max = Vec2::new(
-                (pen_x + metrics.width as u32) as f32 / ATLAS_SIZE as f32,
-                (pen_y + metrics.height as u32) as f32 / ATLAS_SIZE as f32,
-            );
-
-            glyphs.insert(
-                ch,
-                Glyph {
-                    uv_min,
-                    uv_max,
-                    size: Vec2::new(metrics.width as f32, metrics.height as f32),
-                    // fontdue metrics:
-                    // - xmin: offset of the left-most bitmap edge from the origin.
-                    // - ymin: offset of the bottom-most bitmap edge from the baseline (Y-up).
-                    bearing: Vec2::new(metrics.xmin as f32, metrics.ymin as f32),
-                    advance: metrics.advance_width,
-                },
-            );
-
-            pen_x += metrics.width as u32 + 1;
-            row_height = row_height.max(metrics.height as u32);
-        }
-
-        // Convert grayscale atlas to RGBA
-        let mut atlas_rgba = Vec::with_capacity((ATLAS_SIZE * ATLAS_SIZE * 4) as usize);
-        for &gray in &atlas_pixels {
-            atlas_rgba.push(255); // R
-            atlas_rgba.push(255); // G
-            atlas_rgba.push(255); // B
-            atlas_rgba.push(gray); // A (alpha = grayscale value)
-        }
-
-        let atlas_asset = ImageAsset {
-            width: ATLAS_SIZE,
-            height: ATLAS_SIZE,
-            data: atlas_rgba,
-        };
+        let (atlas_asset, glyphs) =
+            rasterize_atlas(&font, font_size, &chars, self.font_raster_scale)?;
         let atlas_image = self.load_image_from_asset(atlas_asset)?;
 
         // Create FontAsset
@@ -210,6 +125,7 @@ impl AssetManager {
                 .map(|m| m.new_line_size)
                 .unwrap_or(font_size),
             font_size,
+            raster_scale: self.font_raster_scale,
         };
 
         let id = FontId::new();
@@ -243,6 +159,8 @@ impl AssetManager {
             self.current_memory_bytes = self
                 .current_memory_bytes
                 .saturating_sub(entry.asset.data.len());
+            // The glyph atlas is only reachable through the font.
+            self.unload_image(entry.asset.atlas);
             log::debug!(
                 "Unloaded font {:?}, memory now: {}",
                 id,
@@ -255,6 +173,10 @@ impl AssetManager {
     }
 
     pub fn unload_all_fonts(&mut self) {
+        let atlases: Vec<ImageId> = self.fonts.by_id.values().map(|e| e.asset.atlas).collect();
+        for atlas in atlases {
+            self.unload_image(atlas);
+        }
         let freed: usize = self
             .fonts
             .by_id
@@ -268,4 +190,239 @@ impl AssetManager {
             self.current_memory_bytes
         );
     }
+
+    /// Atlas texels per glyph pixel used when rasterizing fonts.
+    pub fn font_raster_scale(&self) -> f32 {
+        self.font_raster_scale
+    }
+
+    /// Rasterize font atlases at `scale` texels per glyph pixel (the screen's physical
+    /// pixels per game unit) so text stays crisp on HiDPI displays. Loaded fonts are
+    /// rasterized again; their glyph metrics and UVs do not change, so text laid out
+    /// before stays valid.
+    ///
+    /// `scale` is rounded up to a quarter and clamped to 1.0..=4.0, so small window
+    /// resizes do not rasterize fonts again. The engine calls this when the scale
+    /// factor or scale policy changes.
+    pub fn set_font_raster_scale(&mut self, scale: f32) {
+        let scale = if scale.is_finite() {
+            ((scale * 4.0).ceil() / 4.0).clamp(1.0, MAX_RASTER_SCALE)
+        } else {
+            1.0
+        };
+        if scale == self.font_raster_scale {
+            return;
+        }
+        self.font_raster_scale = scale;
+
+        let ids: Vec<FontId> = self.fonts.by_id.keys().copied().collect();
+        for id in ids {
+            if let Err(e) = self.rasterize_font_again(id) {
+                log::error!("Failed to rasterize font {:?} at {}x: {}", id, scale, e);
+            }
+        }
+    }
+
+    /// Replace the atlas of a loaded font with one rasterized at the current scale.
+    fn rasterize_font_again(&mut self, id: FontId) -> AssetResult<()> {
+        let Some(font) = self.get_font(id) else {
+            return Ok(());
+        };
+        if font.raster_scale == self.font_raster_scale {
+            return Ok(());
+        }
+        let (atlas, font_size) = (font.atlas, font.font_size);
+        let mut chars: Vec<char> = font.glyphs.keys().copied().collect();
+        chars.sort_unstable();
+
+        let parsed =
+            fontdue::Font::from_bytes(font.data.as_slice(), fontdue::FontSettings::default())
+                .map_err(|_| AssetError::InvalidFont)?;
+        let (atlas_asset, _) = rasterize_atlas(&parsed, font_size, &chars, self.font_raster_scale)?;
+        self.replace_image(atlas, atlas_asset)?;
+        if let Some(entry) = self.fonts.by_id.get_mut(&id) {
+            entry.asset.raster_scale = self.font_raster_scale;
+        }
+        Ok(())
+    }
+}
+
+/// Pack `chars` into a glyph atlas and rasterize them at `font_size * raster_scale`.
+///
+/// Glyphs are packed from their metrics at `font_size`, so the layout, UVs and glyph
+/// metrics are the same at every raster scale; only the atlas resolution changes. The
+/// atlas is only as large as the packed glyphs.
+fn rasterize_atlas(
+    font: &fontdue::Font,
+    font_size: f32,
+    chars: &[char],
+    raster_scale: f32,
+) -> AssetResult<(ImageAsset, HashMap<char, Glyph>)> {
+    // Pack every glyph first: the atlas extent depends on all of them.
+    let mut slots = Vec::with_capacity(chars.len());
+    let mut extent = (1u32, 1u32);
+    let mut pen_x = 0u32;
+    let mut pen_y = 0u32;
+    let mut row_height = 0u32;
+
+    for &ch in chars {
+        let metrics = font.metrics(ch, font_size);
+
+        if metrics.width == 0 || metrics.height == 0 {
+            slots.push((ch, metrics, None));
+            pen_x += metrics.advance_width.ceil() as u32;
+            continue;
+        }
+
+        if pen_x + metrics.width as u32 >= ATLAS_SIZE {
+            pen_x = 0;
+            pen_y += row_height + 1;
+            row_height = 0;
+        }
+
+        if pen_y + metrics.height as u32 >= ATLAS_SIZE {
+            return Err(AssetError::OutOfMemory);
+        }
+
+        slots.push((ch, metrics, Some((pen_x, pen_y))));
+        extent.0 = extent.0.max(pen_x + metrics.width as u32);
+        extent.1 = extent.1.max(pen_y + metrics.height as u32);
+        pen_x += metrics.width as u32 + 1;
+        row_height = row_height.max(metrics.height as u32);
+    }
+
+    // Raster scales are multiples of a quarter: with the extent a multiple of four the
+    // atlas holds a whole number of texels per unit, so UVs land on the scaled slots.
+    let extent = (extent.0.next_multiple_of(4), extent.1.next_multiple_of(4));
+    let width = (extent.0 as f32 * raster_scale).round() as u32;
+    let height = (extent.1 as f32 * raster_scale).round() as u32;
+    let mut atlas_pixels = vec![0u8; (width * height) as usize];
+    let mut glyphs = HashMap::new();
+
+    for (ch, metrics, slot) in slots {
+        // fontdue metrics:
+        // - xmin: offset of the left-most bitmap edge from the origin.
+        // - ymin: offset of the bottom-most bitmap edge from the baseline (Y-up).
+        let bearing = Vec2::new(metrics.xmin as f32, metrics.ymin as f32);
+        let Some((pen_x, pen_y)) = slot else {
+            glyphs.insert(
+                ch,
+                Glyph {
+                    uv_min: Vec2::ZERO,
+                    uv_max: Vec2::ZERO,
+                    size: Vec2::ZERO,
+                    bearing,
+                    advance: metrics.advance_width,
+                },
+            );
+            continue;
+        };
+
+        // Copy the bitmap into the glyph's slot, scaled to atlas texels. At other
+        // scales the bitmap bounds can differ slightly from the slot: align them on the
+        // glyph outline and clip to the slot.
+        let (scaled, bitmap) = font.rasterize(ch, font_size * raster_scale);
+        let top = |ymin: i32, height: usize| -(ymin as f32 + height as f32);
+        let dx = (scaled.xmin as f32 - metrics.xmin as f32 * raster_scale).round() as i64;
+        let dy = (top(scaled.ymin, scaled.height)
+            - top(metrics.ymin, metrics.height) * raster_scale)
+            .round() as i64;
+        let slot_x0 = (pen_x as f32 * raster_scale).round() as i64;
+        let slot_y0 = (pen_y as f32 * raster_scale).round() as i64;
+        let slot_x1 = ((pen_x + metrics.width as u32) as f32 * raster_scale).round() as i64;
+        let slot_y1 = ((pen_y + metrics.height as u32) as f32 * raster_scale).round() as i64;
+        for y in 0..scaled.height {
+            let dst_y = slot_y0 + dy + y as i64;
+            if dst_y < slot_y0 || dst_y >= slot_y1.min(height as i64) {
+                continue;
+            }
+            for x in 0..scaled.width {
+                let dst_x = slot_x0 + dx + x as i64;
+                if dst_x < slot_x0 || dst_x >= slot_x1.min(width as i64) {
+                    continue;
+                }
+                let dst = dst_x as usize + dst_y as usize * width as usize;
+                atlas_pixels[dst] = bitmap[x + y * scaled.width];
+            }
+        }
+
+        let uv_min = Vec2::new(
+            pen_x as f32 / extent.0 as f32,
+            pen_y as f32 / extent.1 as f32,
+        );
+
+        let uv_max = Vec2::new(
+            (pen_x + metrics.width as u32) as f32 / extent.0 as f32,
+            (pen_y + metrics.height as u32) as f32 / extent.1 as f32,
+        );
+
+        glyphs.insert(
+            ch,
+            Glyph {
+                uv_min,
+                uv_max,
+                size: Vec2::new(metrics.width as f32, metrics.height as f32),
+                bearing,
+                advance: metrics.advance_width,
+            },
+        );
+    }
+
+    // Convert grayscale atlas to RGBA
+    let mut atlas_rgba = Vec::with_capacity(atlas_pixels.len() * 4);
+    for &gray in &atlas_pixels {
+        atlas_rgba.push(255); // R
+        atlas_rgba.push(255); // G
+        atlas_rgba.push(255); // B
+        atlas_rgba.push(gray); // A (alpha = grayscale value)
+    }
+
+    let atlas = ImageAsset {
+        width,
+        height,
+        data: atlas_rgba,
+        sampler: SamplerOptions::default(),
+    };
+    Ok((atlas, glyphs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::AssetManager;
+
+    #[test]
+    fn raster_scale_keeps_glyph_layout() {
+        let mut assets = AssetManager::new();
+        let font = assets
+            .load_font("src/game/assets/Minecraft.ttf", 16.0)
+            .unwrap();
+        let before = assets.get_font(font).unwrap().clone();
+        let atlas_width = |assets: &AssetManager| assets.get_image(before.atlas).unwrap().width;
+        // Cropped to the packed glyphs: a few rows of ASCII.
+        let width = atlas_width(&assets);
+        assert!(assets.get_image(before.atlas).unwrap().height < 128);
+        assets.take_image_changes();
+
+        // Rounded up to a quarter.
+        assets.set_font_raster_scale(1.9);
+        assert_eq!(assets.font_raster_scale(), 2.0);
+        let after = assets.get_font(font).unwrap();
+        assert_eq!(after.raster_scale, 2.0);
+        assert_eq!(after.atlas, before.atlas);
+        let a = before.glyphs[&'A'];
+        let b = after.glyphs[&'A'];
+        assert_eq!((a.uv_min, a.uv_max, a.size), (b.uv_min, b.uv_max, b.size));
+        assert_eq!(atlas_width(&assets), width * 2);
+        assert_eq!(assets.take_image_changes().len(), 1);
+
+        // Fonts loaded afterwards use the current scale.
+        let big = assets
+            .load_font("src/game/assets/Minecraft.ttf", 24.0)
+            .unwrap();
+        assert_eq!(assets.get_font(big).unwrap().raster_scale, 2.0);
+
+        // Fractional scales keep a whole number of texels per atlas unit.
+        assets.set_font_raster_scale(1.25);
+        assert_eq!(atlas_width(&assets) * 4, width * 5);
+    }
 }
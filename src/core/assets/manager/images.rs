@@ -3,9 +3,35 @@ use std::path::Path;
 use super::super::cache::AssetPathInfo;
 use super::super::cache::ImageKey;
 use super::super::error::{AssetError, AssetResult};
-use super::super::image::{ImageAsset, ImageId};
+use super::super::image::{ImageAsset, ImageId, SamplerOptions, check_rgba8};
 use super::AssetManager;
 
+/// An image edit the renderer has not seen yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ImageChange {
+    /// Upload the whole image again (new image, pixels, size or sampler).
+    Replace(ImageId),
+    /// Upload the `width` x `height` rect at (`x`, `y`) again.
+    Region {
+        id: ImageId,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Free the image's texture.
+    Unload(ImageId),
+}
+
+impl ImageChange {
+    fn id(&self) -> ImageId {
+        match *self {
+            ImageChange::Replace(id) | ImageChange::Unload(id) => id,
+            ImageChange::Region { id, .. } => id,
+        }
+    }
+}
+
 impl AssetManager {
     /// Load an image from disk and cache it under a newly generated identifier.
     /// Returns the ImageId that can be used to retrieve the image later.
@@ -15,6 +41,19 @@ impl AssetManager {
         self.load_image_from_path_info(&info)
     }
 
+    /// Load an image from disk with the given sampling options.
+    ///
+    /// If the image is already cached, its sampling options are replaced.
+    pub fn load_image_with_sampler<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        sampler: SamplerOptions,
+    ) -> AssetResult<ImageId> {
+        let id = self.load_image(path)?;
+        self.set_image_sampler(id, sampler);
+        Ok(id)
+    }
+
     pub(crate) fn load_image_from_path_info(
         &mut self,
         info: &AssetPathInfo,
@@ -43,15 +82,17 @@ impl AssetManager {
             width,
             height,
             data,
+            sampler: SamplerOptions::default(),
         };
 
         let id = ImageId::new();
         self.images.insert_keyed(id, key, image);
         self.current_memory_bytes += image_size;
+        self.queue_image_change(ImageChange::Replace(id));
         Ok(id)
     }
 
-    /// Load an image from an existing ImageAsset.
+    /// Load an image from an existing ImageAsset (sampled with `asset.sampler`).
     pub fn load_image_from_asset(&mut self, asset: ImageAsset) -> AssetResult<ImageId> {
         let image_size = asset.data.len();
         self.ensure_capacity_for(image_size)?;
@@ -59,6 +100,7 @@ impl AssetManager {
         let id = ImageId::new();
         self.images.insert_unkeyed(id, asset);
         self.current_memory_bytes += image_size;
+        self.queue_image_change(ImageChange::Replace(id));
         Ok(id)
     }
 
@@ -77,6 +119,130 @@ impl AssetManager {
         self.images.by_id.get(&id).map(|entry| &entry.asset)
     }
 
+    /// Sampling options of an image.
+    pub fn image_sampler(&self, id: ImageId) -> Option<SamplerOptions> {
+        self.get_image(id).map(|image| image.sampler)
+    }
+
+    /// Change how an image is sampled (filtering, tiling, mipmaps). The renderer picks
+    /// the change up before the next frame.
+    /// Returns false if the image is not loaded.
+    pub fn set_image_sampler(&mut self, id: ImageId, sampler: SamplerOptions) -> bool {
+        let Some(entry) = self.images.by_id.get_mut(&id) else {
+            return false;
+        };
+        if entry.asset.sampler != sampler {
+            entry.asset.sampler = sampler;
+            self.queue_image_change(ImageChange::Replace(id));
+        }
+        true
+    }
+
+    /// Replace the pixels of a loaded image, possibly with a new size. The image keeps
+    /// its id and sampler unless `asset.sampler` differs; its texture is updated before
+    /// the next frame.
+    pub fn replace_image(&mut self, id: ImageId, asset: ImageAsset) -> AssetResult<()> {
+        check_rgba8(asset.width, asset.height, &asset.data)
+            .map_err(AssetError::InvalidImageData)?;
+        let old_size = match self.images.by_id.get(&id) {
+            Some(entry) => entry.asset.data.len(),
+            None => return Err(AssetError::ImageNotLoaded(id)),
+        };
+        let new_size = asset.data.len();
+        if new_size > old_size {
+            self.ensure_capacity_for(new_size - old_size)?;
+        }
+
+        if let Some(entry) = self.images.by_id.get_mut(&id) {
+            entry.asset = asset;
+        }
+        self.current_memory_bytes = self.current_memory_bytes - old_size + new_size;
+        self.queue_image_change(ImageChange::Replace(id));
+        Ok(())
+    }
+
+    /// Overwrite the `width` x `height` rect at (`x`, `y`) of a loaded image with RGBA8
+    /// `data`. Only that rect is uploaded again, which keeps per-frame edits (painting,
+    /// minimaps) cheap.
+    pub fn update_image_region(
+        &mut self,
+        id: ImageId,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> AssetResult<()> {
+        check_rgba8(width, height, data).map_err(AssetError::InvalidImageData)?;
+        let entry = self
+            .images
+            .by_id
+            .get_mut(&id)
+            .ok_or(AssetError::ImageNotLoaded(id))?;
+        let image = &mut entry.asset;
+        if width > image.width
+            || height > image.height
+            || x > image.width - width
+            || y > image.height - height
+        {
+            return Err(AssetError::InvalidImageData(format!(
+                "region {}x{} at ({}, {}) is outside the {}x{} image",
+                width, height, x, y, image.width, image.height
+            )));
+        }
+
+        let row_len = width as usize * 4;
+        for (row, src) in data.chunks_exact(row_len).enumerate() {
+            let start = ((y as usize + row) * image.width as usize + x as usize) * 4;
+            image.data[start..start + row_len].copy_from_slice(src);
+        }
+        self.queue_image_change(ImageChange::Region {
+            id,
+            x,
+            y,
+            width,
+            height,
+        });
+        Ok(())
+    }
+
+    /// Upload every loaded image again, after the renderer lost its textures.
+    pub(crate) fn requeue_all_images(&mut self) {
+        let ids: Vec<ImageId> = self.images.by_id.keys().copied().collect();
+        for id in ids {
+            self.queue_image_change(ImageChange::Replace(id));
+        }
+    }
+
+    /// Image edits since the last call, in order, for the renderer to apply.
+    pub(crate) fn take_image_changes(&mut self) -> Vec<ImageChange> {
+        std::mem::take(&mut self.image_changes)
+    }
+
+    /// Queue `change`, dropping pending changes it makes redundant. Replacements read
+    /// the image when applied, so they cover earlier (and later) region updates.
+    fn queue_image_change(&mut self, change: ImageChange) {
+        let id = change.id();
+        let pending = &mut self.image_changes;
+        match change {
+            ImageChange::Replace(_) => {
+                if pending.contains(&change) {
+                    return;
+                }
+                pending.retain(
+                    |c| !matches!(c, ImageChange::Region { id: other, .. } if *other == id),
+                );
+            }
+            ImageChange::Region { .. } => {
+                if pending.contains(&ImageChange::Replace(id)) {
+                    return;
+                }
+            }
+            ImageChange::Unload(_) => pending.retain(|c| c.id() != id),
+        }
+        pending.push(change);
+    }
+
     /// Unload and remove an image from memory.
     /// Returns true if the image was found and unloaded, false otherwise.
     pub fn unload_image(&mut self, id: ImageId) -> bool {
@@ -84,6 +250,7 @@ impl AssetManager {
             self.current_memory_bytes = self
                 .current_memory_bytes
                 .saturating_sub(entry.asset.data.len());
+            self.queue_image_change(ImageChange::Unload(id));
             log::debug!(
                 "Unloaded image {:?}, memory now: {}",
                 id,
@@ -96,6 +263,10 @@ impl AssetManager {
     }
 
     pub fn unload_all_images(&mut self) {
+        let ids: Vec<ImageId> = self.images.by_id.keys().copied().collect();
+        for id in ids {
+            self.queue_image_change(ImageChange::Unload(id));
+        }
         let freed: usize = self
             .images
             .by_id
@@ -110,3 +281,45 @@ impl AssetManager {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ImageChange;
+    use crate::core::assets::{AssetManager, ImageAsset, SamplerOptions};
+
+    #[test]
+    fn image_edits_queue_minimal_changes() {
+        let mut assets = AssetManager::new();
+        let id = assets
+            .load_image_from_asset(ImageAsset {
+                width: 2,
+                height: 2,
+                data: vec![0; 16],
+                sampler: SamplerOptions::default(),
+            })
+            .unwrap();
+        // The pending upload of the new image already covers the edit.
+        assets
+            .update_image_region(id, 1, 1, 1, 1, &[255; 4])
+            .unwrap();
+        assert_eq!(assets.take_image_changes(), [ImageChange::Replace(id)]);
+        assert_eq!(&assets.get_image(id).unwrap().data[12..], [255; 4]);
+
+        assert!(assets.update_image_region(id, 1, 1, 2, 1, &[0; 8]).is_err());
+        assets.update_image_region(id, 0, 0, 2, 1, &[7; 8]).unwrap();
+        assert_eq!(
+            assets.take_image_changes(),
+            [ImageChange::Region {
+                id,
+                x: 0,
+                y: 0,
+                width: 2,
+                height: 1
+            }]
+        );
+
+        assets.set_image_sampler(id, SamplerOptions::pixel_art());
+        assets.unload_image(id);
+        assert_eq!(assets.take_image_changes(), [ImageChange::Unload(id)]);
+    }
+}
@@ -14,6 +14,8 @@ mod metrics;
 mod sounds;
 mod spritesheet;
 
+pub(crate) use images::ImageChange;
+
 /// Simple asset manager capable of loading and caching images, fonts, and sounds.
 /// Tracks memory usage and supports unloading.
 pub struct AssetManager {
@@ -24,6 +26,10 @@ pub struct AssetManager {
     pub(crate) path_policy: AssetPathPolicy,
     pub(crate) max_memory_bytes: usize,
     pub(crate) current_memory_bytes: usize,
+    /// Image edits not yet applied to the renderer (see `take_image_changes`).
+    pub(crate) image_changes: Vec<ImageChange>,
+    /// Atlas texels per glyph pixel for font atlases (see `set_font_raster_scale`).
+    pub(crate) font_raster_scale: f32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -125,6 +131,8 @@ impl AssetManager {
             path_policy: AssetPathPolicy::AllowAndWarn,
             max_memory_bytes: max_bytes,
             current_memory_bytes: 0,
+            image_changes: Vec::new(),
+            font_raster_scale: 1.0,
         }
     }
 
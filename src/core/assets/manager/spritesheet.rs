@@ -149,7 +149,7 @@ impl SpritesheetAtlas {
     ///
     /// This is CPU/memory heavier than using UVs, but can be handy for simpler workflows.
     pub fn as_image_vec(&self, assets: &mut AssetManager) -> AssetResult<Vec<ImageId>> {
-        let (sheet_width, sheet_height, sheet_data) = {
+        let (sheet_width, sheet_height, sheet_data, sampler) = {
             let sheet =
                 assets
                     .get_image(self.image)
@@ -157,7 +157,7 @@ impl SpritesheetAtlas {
                         path: assets.asset_root.clone(),
                         reason: "Spritesheet atlas source image is not loaded".to_string(),
                     })?;
-            (sheet.width, sheet.height, sheet.data.clone())
+            (sheet.width, sheet.height, sheet.data.clone(), sheet.sampler)
         };
 
         let mut out = Vec::with_capacity(self.regions.len());
@@ -182,6 +182,7 @@ impl SpritesheetAtlas {
                 width: region.width,
                 height: region.height,
                 data: sprite_data,
+                sampler,
             };
 
             let id = assets.load_image_from_asset(sprite)?;
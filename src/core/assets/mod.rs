@@ -7,7 +7,9 @@ pub mod manager;
 pub mod sound_tracking;
 pub mod spritesheet;
 
-pub use image::{ImageAsset, ImageId};
+pub(crate) use image::check_rgba8;
+#[allow(unused_imports)]
+pub use image::{AddressMode, FilterMode, ImageAsset, ImageId, SamplerOptions};
 #[allow(unused_imports)]
 pub use manager::{AssetManager, AssetPathPolicy};
 pub use spritesheet::{SpriteOrder, SpritesheetConfig};
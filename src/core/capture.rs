@@ -0,0 +1,162 @@
+use crate::core::assets::ImageAsset;
+use std::path::{Path, PathBuf};
+
+/// Screenshot requests and frame-sequence recording, driven by the engine around
+/// `Renderer::present`.
+///
+/// Frames are read back from the GPU and encoded on the render thread, so captured
+/// frames take noticeably longer than regular ones.
+#[derive(Debug, Default)]
+pub struct FrameCapture {
+    screenshots: Vec<PathBuf>,
+    recording: Option<Recording>,
+    /// Whether the frame being captured belongs to the recording.
+    record_pending: bool,
+}
+
+#[derive(Debug)]
+struct Recording {
+    dir: PathBuf,
+    every: u32,
+    /// Frames presented since recording started.
+    frame: u64,
+    /// Number of the next PNG written.
+    next_index: u32,
+}
+
+impl FrameCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the next presented frame to `path` as PNG.
+    pub fn request_screenshot(&mut self, path: impl Into<PathBuf>) {
+        self.screenshots.push(path.into());
+    }
+
+    /// Write every `every`-th presented frame (starting with the next one) to `dir` as
+    /// `frame_00000.png`, `frame_00001.png`, ... Replaces a running recording.
+    pub fn start_recording(&mut self, dir: impl Into<PathBuf>, every: u32) -> std::io::Result<()> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        self.recording = Some(Recording {
+            dir,
+            every: every.max(1),
+            frame: 0,
+            next_index: 0,
+        });
+        Ok(())
+    }
+
+    /// Stop recording, returning how many frames were written.
+    pub fn stop_recording(&mut self) -> Option<u32> {
+        self.recording.take().map(|recording| recording.next_index)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Whether the frame about to be presented must be captured. Call once per frame.
+    pub(crate) fn wants_frame(&mut self) -> bool {
+        self.record_pending = self.recording.as_mut().is_some_and(|recording| {
+            let due = recording.frame % recording.every as u64 == 0;
+            recording.frame += 1;
+            due
+        });
+        self.record_pending || !self.screenshots.is_empty()
+    }
+
+    /// Write a captured frame to the pending screenshot paths and the recording.
+    /// Failures are logged; the request is dropped either way.
+    pub(crate) fn save(&mut self, frame: &ImageAsset) {
+        for path in std::mem::take(&mut self.screenshots) {
+            write_png(&path, frame);
+        }
+        if std::mem::take(&mut self.record_pending)
+            && let Some(recording) = self.recording.as_mut()
+        {
+            let path = recording
+                .dir
+                .join(format!("frame_{:05}.png", recording.next_index));
+            recording.next_index += 1;
+            write_png(&path, frame);
+        }
+    }
+
+    /// The renderer returned no frame for the captured one, e.g. because it was
+    /// skipped or the surface does not allow reading frames back. Pending screenshots
+    /// fail; the recording only misses this frame.
+    pub(crate) fn fail(&mut self) {
+        for path in std::mem::take(&mut self.screenshots) {
+            log::error!("Failed to capture frame for {}", path.display());
+        }
+        self.record_pending = false;
+    }
+}
+
+fn write_png(path: &Path, frame: &ImageAsset) {
+    let result = image::save_buffer_with_format(
+        path,
+        &frame.data,
+        frame.width,
+        frame.height,
+        image::ExtendedColorType::Rgba8,
+        image::ImageFormat::Png,
+    );
+    match result {
+        Ok(()) => log::info!("Saved frame to {}", path.display()),
+        Err(e) => log::error!("Failed to save frame to {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameCapture;
+    use crate::math::Color;
+    use crate::render::{Renderer, SoftwareRenderer};
+
+    #[test]
+    fn screenshots_and_every_nth_frame_are_written() {
+        let dir = std::env::temp_dir().join(format!("rusty_capture_{}", std::process::id()));
+        let mut renderer = SoftwareRenderer::new(4, 2);
+        renderer.set_clear_color(Color::GREEN.to_linear_rgba());
+        let mut capture = FrameCapture::new();
+        capture.start_recording(dir.join("seq"), 2).unwrap();
+
+        let mut captured = Vec::new();
+        for frame in 0..5 {
+            if frame == 1 {
+                capture.request_screenshot(dir.join("shot.png"));
+            }
+            if capture.wants_frame() {
+                renderer.capture_next_frame();
+                captured.push(frame);
+            }
+            renderer.present().unwrap();
+            if let Some(image) = renderer.take_captured_frame() {
+                capture.save(&image);
+            }
+        }
+        assert_eq!(captured, [0, 1, 2, 4]);
+        assert_eq!(capture.stop_recording(), Some(3));
+
+        let shot = image::open(dir.join("shot.png")).unwrap().to_rgba8();
+        assert_eq!(shot.dimensions(), (4, 2));
+        assert_eq!(shot.get_pixel(0, 0).0, [0, 255, 0, 255]);
+        assert!(dir.join("seq/frame_00002.png").exists());
+        assert!(!dir.join("seq/frame_00003.png").exists());
+
+        // A frame without readback fails the screenshot once; the recording goes on
+        // with the next frame.
+        capture.start_recording(dir.join("seq"), 1).unwrap();
+        capture.request_screenshot(dir.join("lost.png"));
+        assert!(capture.wants_frame());
+        capture.fail();
+        assert!(capture.wants_frame());
+        capture.save(&renderer.read_image());
+        assert!(!dir.join("lost.png").exists());
+        assert_eq!(capture.stop_recording(), Some(1));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,27 +3,38 @@ use crate::backend::surface_provider::SurfaceProvider;
 use crate::backend::window::WindowConfig;
 use crate::backend::window_backend::{BackendError, BackendResult, WindowBackend};
 use crate::core::assets::AssetManager;
+use crate::core::assets::manager::ImageChange;
+use crate::core::capture::FrameCapture;
 use crate::core::engine_state::EngineState;
 use crate::core::events::EventHandler;
 use crate::core::events::EventHandlerApi;
 use crate::core::events::{
     AxisMotionEvent, GestureEvent, ImeEvent, KeyEvent, Modifiers, MouseButtonEvent,
-    MouseMotionEvent, MouseWheelDelta, PanEvent, Position, Size, Theme, Touch,
+    MouseMotionEvent, MouseWheelDelta, PanEvent, Position, RenderLostEvent, Size, Theme, Touch,
     TouchpadPressureEvent,
 };
+use crate::math::Vec2;
 use crate::render::Renderer;
+use crate::render::canvas::render_canvas_passes;
 use crate::render::context::RenderContext;
-use std::path::Path;
+use crate::render::material::Materials;
+use crate::render::post::PostStack;
+use crate::render::renderer::RenderError;
+use crate::render::{ScalePolicy, ScreenScale};
+use std::path::{Path, PathBuf};
 
 pub struct Engine {
     pub events: EventHandler,
     pub state: EngineState,
     pub audio: AudioSystem,
     pub assets: AssetManager,
+    pub materials: Materials,
+    /// Post-processing effects applied to every frame.
+    pub post: PostStack,
+    capture: FrameCapture,
     backend: Box<dyn WindowBackend>,
     renderer: Box<dyn Renderer>,
 
-    window_size: (u32, u32),
     window_config: Option<WindowConfig>,
 }
 
@@ -39,9 +50,11 @@ impl Engine {
             state: EngineState::new(),
             audio,
             assets: AssetManager::new(),
+            materials: Materials::new(),
+            post: PostStack::new(),
+            capture: FrameCapture::new(),
             backend,
             renderer,
-            window_size: (1, 1),
             window_config: None,
         })
     }
@@ -66,6 +79,39 @@ impl Engine {
         self.window_config.take()
     }
 
+    /// Write the next presented frame to `path` as PNG.
+    pub fn request_screenshot(&mut self, path: impl Into<PathBuf>) {
+        self.capture.request_screenshot(path);
+    }
+
+    /// Write every `every`-th presented frame to `dir` as a numbered PNG sequence
+    /// (`frame_00000.png`, ...). Creates `dir` if needed.
+    pub fn start_recording(&mut self, dir: impl Into<PathBuf>, every: u32) -> std::io::Result<()> {
+        self.capture.start_recording(dir, every)
+    }
+
+    /// Stop recording, returning how many frames were written.
+    pub fn stop_recording(&mut self) -> Option<u32> {
+        self.capture.stop_recording()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.capture.is_recording()
+    }
+
+    /// Choose the units game code draws and receives input in (logical pixels by
+    /// default). `state.screen` describes the resulting mapping.
+    pub fn set_scale_policy(&mut self, policy: ScalePolicy) {
+        let screen = &mut self.state.screen;
+        *screen = ScreenScale::new(policy, screen.physical_size, screen.scale_factor);
+        self.assets.set_font_raster_scale(self.state.screen.scale);
+    }
+
+    /// Time render passes on the GPU; timings show up in `state.render_stats`.
+    pub fn set_gpu_timing(&mut self, enabled: bool) {
+        self.renderer.set_gpu_timing(enabled);
+    }
+
     /// Run the backend event loop. Returns an error if the backend fails.
     pub fn run(&mut self) -> BackendResult<()> {
         // Forward backend events and hook renderer calls in the engine layer
@@ -74,21 +120,86 @@ impl Engine {
             renderer: &'a mut dyn Renderer,
             initialized: bool,
             state: &'a mut EngineState,
-            window_size: &'a mut (u32, u32),
             window_config: Option<&'a WindowConfig>,
-            assets: &'a AssetManager,
+            assets: &'a mut AssetManager,
+            materials: &'a mut Materials,
+            post: &'a mut PostStack,
+            capture: &'a mut FrameCapture,
+        }
+
+        impl Forwarder<'_> {
+            /// Convert a window position from the backend (physical pixels) to game units.
+            fn to_game_units(&self, pos: &Position) -> Position {
+                let p = self.state.screen.to_logical(Vec2::new(pos.x, pos.y));
+                Position { x: p.x, y: p.y }
+            }
+
+            /// Reconfigure an unusable surface, or rebuild the renderer after a lost
+            /// device and queue everything it dropped for upload again, then notify game
+            /// code.
+            fn handle_present_error(&mut self, error: RenderError) {
+                let device_lost = match error {
+                    RenderError::DeviceLost => true,
+                    RenderError::SurfaceError(_) => false,
+                    e => {
+                        log::error!("Failed to present frame: {}", e);
+                        return;
+                    }
+                };
+                log::warn!("Renderer lost, recovering: {}", error);
+                if !device_lost {
+                    // GPU resources survive; the surface only needs configuring again.
+                    self.renderer.resize(self.state.screen.physical_size);
+                    EventHandlerApi::on_render_lost(
+                        self.events,
+                        &RenderLostEvent {
+                            device_lost,
+                            recovered: true,
+                        },
+                    );
+                    return;
+                }
+                let recovered = self.renderer.recover();
+                // Everything is gone either way: upload it again now, or from
+                // `on_surface_ready` once the renderer is initialized again.
+                self.assets.requeue_all_images();
+                self.materials.requeue_all();
+                self.post.mark_changed();
+                let recovered = match recovered {
+                    Ok(()) => {
+                        sync_images(self.renderer, self.assets);
+                        true
+                    }
+                    Err(e) => {
+                        // Stop rendering until the backend reports a usable surface again.
+                        log::error!("Failed to recover renderer: {}", e);
+                        self.initialized = false;
+                        false
+                    }
+                };
+                EventHandlerApi::on_render_lost(
+                    self.events,
+                    &RenderLostEvent {
+                        device_lost,
+                        recovered,
+                    },
+                );
+            }
         }
 
         impl<'a> EventHandlerApi for Forwarder<'a> {
             fn on_surface_ready(&mut self, surface: &dyn SurfaceProvider) {
                 if !self.initialized {
-                    let _ = self.renderer.init(surface, self.window_config);
-                    // Upload any images that were loaded before the surface was ready.
-                    for (id, image) in self.assets.iter_images() {
-                        let _ =
-                            self.renderer
-                                .upload_image(id, image.width, image.height, &image.data);
+                    if let Err(e) = self.renderer.init(surface, self.window_config) {
+                        log::error!("Failed to initialize renderer: {}", e);
+                        return;
                     }
+                    let screen = &mut self.state.screen;
+                    *screen =
+                        ScreenScale::new(screen.policy, surface.size(), surface.scale_factor());
+                    self.assets.set_font_raster_scale(self.state.screen.scale);
+                    // Upload any images that were loaded before the surface was ready.
+                    sync_images(self.renderer, self.assets);
                     self.initialized = true;
                 }
             }
@@ -97,7 +208,8 @@ impl Engine {
                 if self.initialized {
                     self.renderer.resize((size.width, size.height));
                 }
-                *self.window_size = (size.width, size.height);
+                self.state.screen = self.state.screen.resized((size.width, size.height));
+                self.assets.set_font_raster_scale(self.state.screen.scale);
                 EventHandlerApi::on_resize(self.events, size);
             }
 
@@ -118,6 +230,8 @@ impl Engine {
             }
 
             fn on_scale_factor_changed(&mut self, scale: &f64) {
+                self.state.screen = self.state.screen.with_scale_factor(*scale);
+                self.assets.set_font_raster_scale(self.state.screen.scale);
                 EventHandlerApi::on_scale_factor_changed(self.events, scale);
             }
 
@@ -146,15 +260,23 @@ impl Engine {
             }
 
             fn on_mouse_button_pressed(&mut self, ev: &MouseButtonEvent) {
-                EventHandlerApi::on_mouse_button_pressed(self.events, ev);
+                let ev = MouseButtonEvent {
+                    position: self.to_game_units(&ev.position),
+                    ..*ev
+                };
+                EventHandlerApi::on_mouse_button_pressed(self.events, &ev);
             }
 
             fn on_mouse_button_released(&mut self, ev: &MouseButtonEvent) {
-                EventHandlerApi::on_mouse_button_released(self.events, ev);
+                let ev = MouseButtonEvent {
+                    position: self.to_game_units(&ev.position),
+                    ..*ev
+                };
+                EventHandlerApi::on_mouse_button_released(self.events, &ev);
             }
 
             fn on_mouse_move(&mut self, pos: &Position) {
-                EventHandlerApi::on_mouse_move(self.events, pos);
+                EventHandlerApi::on_mouse_move(self.events, &self.to_game_units(pos));
             }
 
             fn on_mouse_motion(&mut self, ev: &MouseMotionEvent) {
@@ -174,7 +296,11 @@ impl Engine {
             }
 
             fn on_touch(&mut self, touch: &Touch) {
-                EventHandlerApi::on_touch(self.events, touch);
+                let touch = Touch {
+                    position: self.to_game_units(&touch.position),
+                    ..*touch
+                };
+                EventHandlerApi::on_touch(self.events, &touch);
             }
 
             fn on_pinch(&mut self, gesture: &GestureEvent) {
@@ -224,23 +350,65 @@ impl Engine {
             }
 
             fn on_redraw(&mut self) {
+                // Compile materials registered since the last frame and apply
+                // post-processing changes.
+                if self.initialized {
+                    for id in self.materials.take_pending() {
+                        let Some(descriptor) = self.materials.get(id) else {
+                            continue;
+                        };
+                        if let Err(e) = self.renderer.create_material(id, descriptor) {
+                            log::error!("Failed to create material '{}': {}", descriptor.label, e);
+                        }
+                    }
+                    if self.post.take_changed()
+                        && let Err(e) = self.renderer.set_post_effects(&self.post.enabled_effects())
+                    {
+                        log::error!("Failed to set post effects: {}", e);
+                    }
+                }
+
                 // Let user redraw callbacks run, then render
                 EventHandlerApi::on_redraw(self.events);
                 // RenderContext callbacks (immediate-mode drawing)
-                let mut ctx = RenderContext::new(*self.window_size);
+                let mut ctx = RenderContext::with_screen_scale(self.state.screen);
                 self.events.on_render.invoke(&mut ctx);
+                // Upload images loaded, edited or unloaded so far, including by the
+                // callbacks above, before anything samples them.
+                if self.initialized {
+                    sync_images(self.renderer, self.assets);
+                }
                 if let Some(color) = ctx.clear_color {
                     let [r, g, b, a] = color.to_linear_rgba();
                     self.renderer.set_clear_color([r, g, b, a]);
                 }
-                if !ctx.vertices.is_empty() {
-                    self.renderer.submit(&ctx.vertices);
+                for (id, params) in ctx.material_params() {
+                    self.renderer.set_material_params(*id, params);
+                }
+                if self.initialized
+                    && let Err(e) = render_canvas_passes(self.renderer, &ctx)
+                {
+                    log::error!("Failed to render canvas: {}", e);
                 }
-                if !ctx.sprites.is_empty() {
-                    self.renderer.draw_sprites(&ctx.sprites, *self.window_size);
+                let draw_list = ctx.draw_list();
+                if !draw_list.is_empty() {
+                    self.renderer
+                        .submit_draw_list(&draw_list, self.state.screen.physical_size);
                 }
                 if self.initialized {
-                    let _ = self.renderer.present();
+                    let capturing = self.capture.wants_frame();
+                    if capturing {
+                        self.renderer.capture_next_frame();
+                    }
+                    if let Err(e) = self.renderer.present() {
+                        self.handle_present_error(e);
+                    }
+                    self.state.render_stats = self.renderer.render_stats();
+                    match self.renderer.take_captured_frame() {
+                        Some(frame) => self.capture.save(&frame),
+                        None if capturing => self.capture.fail(),
+                        None => {}
+                    }
                 }
 
                 // Frame end: clear one-frame input states after user update/render have run.
@@ -253,11 +421,62 @@ impl Engine {
             renderer: self.renderer.as_mut(),
             initialized: false,
             state: &mut self.state,
-            window_size: &mut self.window_size,
             window_config: self.window_config.as_ref(),
-            assets: &self.assets,
+            assets: &mut self.assets,
+            materials: &mut self.materials,
+            post: &mut self.post,
+            capture: &mut self.capture,
         };
 
         self.backend.run(&mut forwarder)
     }
 }
+
+/// Apply image loads, edits and removals made through `assets` since the last call.
+fn sync_images(renderer: &mut dyn Renderer, assets: &mut AssetManager) {
+    for change in assets.take_image_changes() {
+        apply_image_change(renderer, assets, change);
+    }
+}
+
+/// Bring the renderer's copy of an image up to date with `assets`.
+fn apply_image_change(renderer: &mut dyn Renderer, assets: &AssetManager, change: ImageChange) {
+    let (id, region) = match change {
+        ImageChange::Unload(id) => {
+            renderer.unload_image(id);
+            return;
+        }
+        ImageChange::Replace(id) => (id, None),
+        ImageChange::Region {
+            id,
+            x,
+            y,
+            width,
+            height,
+        } => (id, Some((x, y, width, height))),
+    };
+    let Some(image) = assets.get_image(id) else {
+        return;
+    };
+
+    if let Some((x, y, width, height)) = region {
+        let row_len = width as usize * 4;
+        let mut data = Vec::with_capacity(row_len * height as usize);
+        for row in y..y + height {
+            let start = (row as usize * image.width as usize + x as usize) * 4;
+            data.extend_from_slice(&image.data[start..start + row_len]);
+        }
+        // Renderers without partial updates (or mipmapped textures) get the whole image.
+        if renderer
+            .update_image_region(id, x, y, width, height, &data)
+            .is_ok()
+        {
+            return;
+        }
+    }
+    if let Err(e) =
+        renderer.replace_image(id, image.width, image.height, &image.data, &image.sampler)
+    {
+        log::error!("Failed to update image {:?}: {}", id, e);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::render::{RenderStats, ScalePolicy, ScreenScale};
 use std::time::{Duration, Instant};
 
 pub struct EngineState {
@@ -5,6 +6,10 @@ pub struct EngineState {
     pub total_time: Duration,
     pub frame_count: u64,
     pub fps: f64,
+    /// Renderer counters of the last presented frame.
+    pub render_stats: RenderStats,
+    /// Window size, scale factor and how game units map to physical pixels.
+    pub screen: ScreenScale,
 
     // Internal
     last_frame: Instant,
@@ -19,6 +24,8 @@ impl EngineState {
             total_time: Duration::ZERO,
             frame_count: 0,
             fps: 0.0,
+            render_stats: RenderStats::default(),
+            screen: ScreenScale::new(ScalePolicy::default(), (1, 1), 1.0),
             last_frame: Instant::now(),
             fps_update_timer: Duration::ZERO,
             fps_frame_count: 0,
@@ -53,3 +60,14 @@ impl EngineState {
         self.last_frame
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::EngineState;
+    use crate::render::ScalePolicy;
+
+    #[test]
+    fn new_state_uses_logical_pixels() {
+        assert_eq!(EngineState::new().screen.policy, ScalePolicy::Logical);
+    }
+}
@@ -2,7 +2,7 @@ use super::callbacks::{Callbacks, Mut, Ref2};
 use super::input::Input;
 use super::input_events::{
     AxisMotionEvent, GestureEvent, ImeEvent, Key, KeyEvent, Modifiers, MouseButtonEvent,
-    MouseMotionEvent, MouseWheelDelta, PanEvent, Position, Size, Theme, Touch,
+    MouseMotionEvent, MouseWheelDelta, PanEvent, Position, RenderLostEvent, Size, Theme, Touch,
     TouchpadPressureEvent,
 };
 use crate::backend::surface_provider::SurfaceProvider;
@@ -56,6 +56,9 @@ pub trait EventHandlerApi {
 
     fn on_redraw(&mut self) {}
     fn on_update(&mut self, _state: &EngineState) {}
+
+    /// Called by the engine after the renderer lost its device or surface.
+    fn on_render_lost(&mut self, _ev: &RenderLostEvent) {}
 }
 
 /// Orchestrates user callbacks and input state.
@@ -124,6 +127,7 @@ pub struct EventHandler {
 
     // === RENDER CONTEXT CALLBACKS ===
     pub on_render: Callbacks<RenderContext, Mut>,
+    on_render_lost: Callbacks<RenderLostEvent>,
 
     // === INPUT SNAPSHOT ===
     pub on_keys_state_changed: Callbacks<Vec<Key>>,
@@ -169,6 +173,7 @@ impl EventHandler {
             on_redraw: Callbacks::new(),
             on_update: Callbacks::new(),
             on_render: Callbacks::new(),
+            on_render_lost: Callbacks::new(),
             on_keys_state_changed: Callbacks::new(),
             current_modifiers: Modifiers::default(),
             input: Input::new(),
@@ -230,6 +235,11 @@ impl EventHandler {
     pub fn on_render<F: FnMut(&mut RenderContext) + 'static>(&mut self, f: F) -> usize {
         self.on_render.add(f)
     }
+    /// Called after the GPU device or window surface was lost and rendering was
+    /// restarted. Canvas contents must be drawn again.
+    pub fn on_render_lost<F: FnMut(&RenderLostEvent) + 'static>(&mut self, f: F) -> usize {
+        self.on_render_lost.add(f)
+    }
     /// Primary gameplay hook: update logic (poll input here).
     pub fn on_update<F: FnMut(&EngineState) + 'static>(&mut self, mut f: F) -> usize {
         self.on_update.add(move |state, _input| f(state))
@@ -466,4 +476,8 @@ impl EventHandlerApi for EventHandler {
         self.on_update.invoke(state, &self.input);
         log::trace!("update: end");
     }
+
+    fn on_render_lost(&mut self, ev: &RenderLostEvent) {
+        self.on_render_lost.invoke(ev);
+    }
 }
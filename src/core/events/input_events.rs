@@ -320,3 +320,16 @@ pub struct PanEvent {
     pub phase: TouchPhase,
     pub delta: Position,
 }
+
+/// Sent after the renderer lost its GPU device or window surface.
+///
+/// Textures, materials and post effects are restored by the engine; canvas contents
+/// are not and must be drawn again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderLostEvent {
+    /// `true` if the device itself was lost, `false` for an unusable surface.
+    pub device_lost: bool,
+    /// Whether rendering was restored. If not, it resumes once the window surface is
+    /// ready again.
+    pub recovered: bool,
+}
@@ -1,4 +1,5 @@
 pub mod assets;
+pub mod capture;
 pub mod engine;
 pub mod engine_state;
 pub mod events;
@@ -4,7 +4,7 @@ use crate::graphics::animation::Animation;
 use crate::math::Transform;
 use crate::math::color::Color;
 use crate::math::vec2::Vec2;
-use crate::render::{Drawable, RenderContext, SpriteDrawData, Transform2d};
+use crate::render::{BlendMode, Drawable, RenderContext, SpriteDrawData, Transform2d};
 use std::collections::VecDeque;
 use std::time::Duration;
 
@@ -22,6 +22,10 @@ pub struct AnimatedSprite {
     pub animation: Animation,
     pub size: Vec2,
     pub tint: Color,
+    /// Mirror the frames horizontally, e.g. to face left with right-facing art.
+    pub flip_x: bool,
+    /// Mirror the frames vertically.
+    pub flip_y: bool,
 
     current_frame: usize,
     elapsed: Duration,
@@ -57,6 +61,8 @@ impl AnimatedSprite {
             animation,
             size: Vec2::new(width as f32, height as f32),
             tint: Color::WHITE,
+            flip_x: false,
+            flip_y: false,
             current_frame: 0,
             elapsed: Duration::ZERO,
             playback_state: PlaybackState::Playing,
@@ -76,6 +82,8 @@ impl AnimatedSprite {
             animation,
             size: Vec2::new(width as f32, height as f32),
             tint: Color::WHITE,
+            flip_x: false,
+            flip_y: false,
             current_frame: 0,
             elapsed: Duration::ZERO,
             playback_state: PlaybackState::Playing,
@@ -255,6 +263,10 @@ impl AnimatedSprite {
             tint: self.tint,
             uv_min: Vec2::new(0.0, 0.0),
             uv_max: Vec2::new(1.0, 1.0),
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            material: None,
+            blend: BlendMode::Alpha,
         }
     }
 }
@@ -1,9 +1,12 @@
 pub mod animated_sprite;
 pub mod animation;
+pub mod nine_slice;
 pub mod sprite;
 pub mod text;
 
 pub use animated_sprite::AnimatedSprite;
 pub use animation::Animation;
+#[allow(unused_imports)]
+pub use nine_slice::{Insets, NineSlice, SliceMode};
 pub use sprite::Sprite;
 pub use text::Text;
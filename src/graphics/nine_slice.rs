@@ -0,0 +1,363 @@
+use crate::core::assets::spritesheet::{SpriteRegion, SpritesheetAtlas};
+use crate::core::assets::{ImageAsset, ImageId};
+use crate::math::Transform;
+use crate::math::color::Color;
+use crate::math::vec2::Vec2;
+use crate::render::{Drawable, RenderContext, TexturedVertex, Transform2d};
+
+/// How the edges or the center of a `NineSlice` span the space between its corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SliceMode {
+    /// One copy of the source slice, stretched.
+    #[default]
+    Stretch,
+    /// Copies of the source slice at their source size, the last one cut short.
+    Tile,
+}
+
+/// Widths of the borders of a `NineSlice`, in source pixels. Corners are drawn at
+/// this size.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Insets {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl Insets {
+    pub fn new(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub fn uniform(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+}
+
+/// Image split into a 3x3 grid by `insets`, drawn at any `size` without distorting
+/// its corners: edges and center stretch or tile to fill the rest. Meant for UI
+/// panels, buttons and frames.
+///
+/// When `size` is smaller than two opposite borders, both shrink proportionally.
+#[derive(Clone, Debug)]
+pub struct NineSlice {
+    pub transform: Transform,
+    pub image_id: ImageId,
+    /// Size of the source region, in pixels.
+    pub source_size: Vec2,
+    /// UV region of the source in the image, e.g. a `SpriteRegion` of an atlas.
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    pub insets: Insets,
+    /// Drawn size, before the transform's scale.
+    pub size: Vec2,
+    pub tint: Color,
+    pub edge_mode: SliceMode,
+    pub center_mode: SliceMode,
+}
+
+/// One drawn piece: local rectangle and its UV rectangle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SliceQuad {
+    min: Vec2,
+    max: Vec2,
+    uv_min: Vec2,
+    uv_max: Vec2,
+}
+
+/// Piece of one axis: drawn span and source span, in pixels.
+type Span = (f32, f32, f32, f32);
+
+impl NineSlice {
+    /// Nine-slice of a whole loaded image, drawn at the image's size.
+    pub fn from_image(id: ImageId, image: &ImageAsset, insets: Insets) -> Self {
+        Self::new(id, image.width, image.height, insets)
+    }
+
+    /// Nine-slice of a whole image of `width` x `height` pixels.
+    pub fn new(id: ImageId, width: u32, height: u32, insets: Insets) -> Self {
+        let size = Vec2::new(width as f32, height as f32);
+        Self {
+            transform: Transform::new(),
+            image_id: id,
+            source_size: size,
+            uv_min: Vec2::new(0.0, 0.0),
+            uv_max: Vec2::new(1.0, 1.0),
+            insets,
+            size,
+            tint: Color::WHITE,
+            edge_mode: SliceMode::Stretch,
+            center_mode: SliceMode::Stretch,
+        }
+    }
+
+    /// Nine-slice of a `region` of `image`, drawn at the region's size.
+    pub fn from_region(image: ImageId, region: &SpriteRegion, insets: Insets) -> Self {
+        let mut slice = Self::new(image, region.width, region.height, insets);
+        slice.uv_min = region.uv_min;
+        slice.uv_max = region.uv_max;
+        slice
+    }
+
+    /// Nine-slice of the atlas region at `index`; `None` if out of range.
+    pub fn from_atlas(atlas: &SpritesheetAtlas, index: usize, insets: Insets) -> Option<Self> {
+        atlas
+            .regions
+            .get(index)
+            .map(|region| Self::from_region(atlas.image, region, insets))
+    }
+
+    /// Builder: Drawn size
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Builder: Mode of both the edges and the center
+    pub fn with_mode(mut self, mode: SliceMode) -> Self {
+        self.edge_mode = mode;
+        self.center_mode = mode;
+        self
+    }
+
+    /// Builder: Mode of the edges
+    pub fn with_edge_mode(mut self, mode: SliceMode) -> Self {
+        self.edge_mode = mode;
+        self
+    }
+
+    /// Builder: Mode of the center
+    pub fn with_center_mode(mut self, mode: SliceMode) -> Self {
+        self.center_mode = mode;
+        self
+    }
+
+    pub fn set_origin_keep_position(&mut self, origin: Vec2) {
+        self.transform.set_origin_keep_position(origin, self.size);
+    }
+
+    pub fn set_origin_center_keep_position(&mut self) {
+        self.transform.set_origin_center_keep_position(self.size);
+    }
+
+    fn local_quads(&self) -> Vec<SliceQuad> {
+        let insets = self.insets;
+        let columns = |tile| {
+            axis_spans(
+                self.size.x,
+                self.source_size.x,
+                insets.left,
+                insets.right,
+                tile,
+            )
+        };
+        let rows = |tile| {
+            axis_spans(
+                self.size.y,
+                self.source_size.y,
+                insets.top,
+                insets.bottom,
+                tile,
+            )
+        };
+        let (columns, tiled_columns) = (columns(false), columns(true));
+        let (rows, tiled_rows) = (rows(false), rows(true));
+
+        let uv_extent = self.uv_max - self.uv_min;
+        let uv = |x: f32, y: f32| {
+            self.uv_min
+                + Vec2::new(
+                    uv_extent.x * x / self.source_size.x.max(1.0),
+                    uv_extent.y * y / self.source_size.y.max(1.0),
+                )
+        };
+
+        let mut quads = Vec::new();
+        for row in 0..3 {
+            for column in 0..3 {
+                let mode = if row == 1 && column == 1 {
+                    self.center_mode
+                } else {
+                    self.edge_mode
+                };
+                let tile = mode == SliceMode::Tile;
+                let xs = if tile && column == 1 {
+                    &tiled_columns[column]
+                } else {
+                    &columns[column]
+                };
+                let ys = if tile && row == 1 {
+                    &tiled_rows[row]
+                } else {
+                    &rows[row]
+                };
+                for &(y0, y1, v0, v1) in ys {
+                    for &(x0, x1, u0, u1) in xs {
+                        quads.push(SliceQuad {
+                            min: Vec2::new(x0, y0),
+                            max: Vec2::new(x1, y1),
+                            uv_min: uv(u0, v0),
+                            uv_max: uv(u1, v1),
+                        });
+                    }
+                }
+            }
+        }
+        quads
+    }
+}
+
+/// Spans of the three bands along one axis: the start border, the middle and the end
+/// border. Empty bands have no spans.
+fn axis_spans(size: f32, source: f32, start: f32, end: f32, tile: bool) -> [Vec<Span>; 3] {
+    let size = size.max(0.0);
+    let start = start.clamp(0.0, source);
+    let end = end.clamp(0.0, source - start);
+    // Borders keep their source size unless they do not fit.
+    let fit = if start + end > size {
+        size / (start + end)
+    } else {
+        1.0
+    };
+    let (drawn_start, drawn_end) = (start * fit, end * fit);
+    let (middle_from, middle_to) = (drawn_start, size - drawn_end);
+    let source_middle = (start, source - end);
+
+    let span = |from: f32, to: f32, source_from: f32, source_to: f32| {
+        if to - from > 1e-4 {
+            vec![(from, to, source_from, source_to)]
+        } else {
+            Vec::new()
+        }
+    };
+
+    let tile_length = (source_middle.1 - source_middle.0) * fit;
+    let middle = if tile && tile_length > 1e-4 {
+        let mut spans = Vec::new();
+        let mut from = middle_from;
+        while middle_to - from > 1e-4 {
+            let to = (from + tile_length).min(middle_to);
+            let covered = (to - from) / tile_length;
+            spans.push((
+                from,
+                to,
+                source_middle.0,
+                source_middle.0 + (source_middle.1 - source_middle.0) * covered,
+            ));
+            from = to;
+        }
+        spans
+    } else {
+        span(middle_from, middle_to, source_middle.0, source_middle.1)
+    };
+
+    [
+        span(0.0, drawn_start, 0.0, start),
+        middle,
+        span(middle_to, size, source - end, source),
+    ]
+}
+
+impl Transform2d for NineSlice {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+impl Drawable for NineSlice {
+    fn draw(&self, ctx: &mut RenderContext) {
+        let color = self.tint.to_linear_rgba();
+        let quads = self.local_quads();
+        let mut vertices = Vec::with_capacity(quads.len() * 4);
+        let mut indices = Vec::with_capacity(quads.len() * 6);
+        for quad in &quads {
+            let start = vertices.len() as u32;
+            let corners = [
+                (Vec2::new(quad.min.x, quad.min.y), quad.uv_min),
+                (
+                    Vec2::new(quad.max.x, quad.min.y),
+                    Vec2::new(quad.uv_max.x, quad.uv_min.y),
+                ),
+                (Vec2::new(quad.max.x, quad.max.y), quad.uv_max),
+                (
+                    Vec2::new(quad.min.x, quad.max.y),
+                    Vec2::new(quad.uv_min.x, quad.uv_max.y),
+                ),
+            ];
+            vertices.extend(corners.map(|(local, uv)| {
+                TexturedVertex {
+                    pos: ctx
+                        .to_ndc(self.transform.transform_point(local, self.size))
+                        .to_array(),
+                    uv: uv.to_array(),
+                    color,
+                }
+            }));
+            indices.extend([0, 1, 2, 0, 2, 3].map(|i| start + i));
+        }
+        ctx.draw_mesh(&vertices, &indices, Some(self.image_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Insets, NineSlice, SliceMode};
+    use crate::core::assets::ImageId;
+    use crate::core::assets::spritesheet::SpriteRegion;
+    use crate::math::Vec2;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        (a.x - b.x).abs() + (a.y - b.y).abs() < 1e-4
+    }
+
+    #[test]
+    fn nine_slices_keep_corners_and_tile_inside_atlas_regions() {
+        // 30x30 region in the top-right quarter of a 60x60 atlas.
+        let region = SpriteRegion {
+            x: 30,
+            y: 0,
+            width: 30,
+            height: 30,
+            uv_min: Vec2::new(0.5, 0.0),
+            uv_max: Vec2::new(1.0, 0.5),
+        };
+        let slice = NineSlice::from_region(ImageId::new(), &region, Insets::uniform(10.0))
+            .with_size(Vec2::new(95.0, 50.0));
+
+        let quads = slice.local_quads();
+        assert_eq!(quads.len(), 9);
+        assert_eq!(quads[0].max, Vec2::new(10.0, 10.0));
+        assert!(close(
+            quads[0].uv_max,
+            Vec2::new(0.5 + 0.5 / 3.0, 0.5 / 3.0)
+        ));
+        assert_eq!(quads[4].min, Vec2::new(10.0, 10.0));
+        assert_eq!(quads[4].max, Vec2::new(85.0, 40.0));
+        assert_eq!(quads[8].min, Vec2::new(85.0, 40.0));
+        assert!(close(quads[8].uv_max, Vec2::new(1.0, 0.5)));
+
+        // 75 pixels of edge: seven whole tiles and a half one showing half the slice.
+        let tiled = slice.clone().with_mode(SliceMode::Tile).local_quads();
+        let top: Vec<_> = tiled.iter().filter(|q| q.min.y == 0.0).collect();
+        assert_eq!(top.len(), 2 + 8);
+        let last = top[top.len() - 2];
+        assert_eq!((last.min.x, last.max.x), (80.0, 85.0));
+        assert!(close(last.uv_max, Vec2::new(0.75, 0.5 / 3.0)));
+        assert_eq!(tiled.len(), 4 + 2 * 8 + 2 * 3 + 8 * 3);
+
+        // Too small for both borders: corners shrink instead of overlapping.
+        let small = slice.with_size(Vec2::new(10.0, 10.0)).local_quads();
+        assert_eq!(small.len(), 4);
+        assert_eq!(small[3].min, Vec2::new(5.0, 5.0));
+    }
+}
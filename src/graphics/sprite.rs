@@ -1,9 +1,10 @@
-use crate::core::assets::ImageAsset;
 use crate::core::assets::ImageId;
+use crate::core::assets::spritesheet::SpritesheetAtlas;
+use crate::core::assets::{ImageAsset, SamplerOptions};
 use crate::math::Transform;
 use crate::math::color::Color;
 use crate::math::vec2::Vec2;
-use crate::render::{Drawable, RenderContext, SpriteDrawData, Transform2d};
+use crate::render::{BlendMode, Drawable, RenderContext, SpriteDrawData, Transform2d};
 
 /// Simple 2D sprite similar to pygame's Sprite.
 /// Holds a reference id to a texture and basic transform properties.
@@ -13,6 +14,13 @@ pub struct Sprite {
     pub image_id: ImageId,
     pub size: Vec2,
     pub tint: Color,
+    /// Region of the image shown, in 0..1 texture coordinates.
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    /// Mirror the image horizontally, e.g. to face left with right-facing art.
+    pub flip_x: bool,
+    /// Mirror the image vertically.
+    pub flip_y: bool,
 }
 
 impl Sprite {
@@ -24,6 +32,10 @@ impl Sprite {
             image_id: id,
             size,
             tint: Color::WHITE,
+            uv_min: Vec2::new(0.0, 0.0),
+            uv_max: Vec2::new(1.0, 1.0),
+            flip_x: false,
+            flip_y: false,
         }
     }
 
@@ -33,10 +45,33 @@ impl Sprite {
             width,
             height,
             data: Vec::new(),
+            sampler: SamplerOptions::default(),
         };
         Self::from_image(id, &image)
     }
 
+    /// Sprite showing the atlas region at `index`, at the region's size. Sprites from
+    /// one atlas share its texture. `None` if `index` is out of range.
+    pub fn from_region(atlas: &SpritesheetAtlas, index: usize) -> Option<Self> {
+        let region = atlas.regions.get(index)?;
+        let mut sprite = Self::new(atlas.image, region.width, region.height);
+        sprite.uv_min = region.uv_min;
+        sprite.uv_max = region.uv_max;
+        Some(sprite)
+    }
+
+    /// Builder: Mirror the image horizontally
+    pub fn with_flip_x(mut self, flip: bool) -> Self {
+        self.flip_x = flip;
+        self
+    }
+
+    /// Builder: Mirror the image vertically
+    pub fn with_flip_y(mut self, flip: bool) -> Self {
+        self.flip_y = flip;
+        self
+    }
+
     /// Compute world-space corners of the sprite quad in pixel coordinates.
     /// Order: top-left, top-right, bottom-right, bottom-left.
     pub fn world_corners(&self) -> [Vec2; 4] {
@@ -60,8 +95,12 @@ impl Sprite {
             scale: self.transform.scale,
             origin: self.transform.origin,
             tint: self.tint,
-            uv_min: Vec2::new(0.0, 0.0),
-            uv_max: Vec2::new(1.0, 1.0),
+            uv_min: self.uv_min,
+            uv_max: self.uv_max,
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            material: None,
+            blend: BlendMode::Alpha,
         }
     }
 }
@@ -90,16 +129,52 @@ impl From<&Sprite> for SpriteDrawData {
 
 impl From<Sprite> for SpriteDrawData {
     fn from(sprite: Sprite) -> Self {
-        SpriteDrawData {
-            image_id: sprite.image_id,
-            size: sprite.size,
-            position: sprite.transform.position,
-            rotation: sprite.transform.rotation,
-            scale: sprite.transform.scale,
-            origin: sprite.transform.origin,
-            tint: sprite.tint,
-            uv_min: Vec2::new(0.0, 0.0),
-            uv_max: Vec2::new(1.0, 1.0),
-        }
+        sprite.to_draw_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sprite;
+    use crate::core::assets::ImageId;
+    use crate::core::assets::spritesheet::{SpriteRegion, SpritesheetAtlas};
+    use crate::math::Vec2;
+
+    #[test]
+    fn region_sprites_share_the_atlas_and_flip_their_corners() {
+        let region = |x: u32| SpriteRegion {
+            x,
+            y: 0,
+            width: 16,
+            height: 8,
+            uv_min: Vec2::new(x as f32 / 32.0, 0.0),
+            uv_max: Vec2::new((x + 16) as f32 / 32.0, 1.0),
+        };
+        let atlas = SpritesheetAtlas {
+            image: ImageId::new(),
+            regions: vec![region(0), region(16)],
+        };
+        assert!(Sprite::from_region(&atlas, 2).is_none());
+
+        let mut sprite = Sprite::from_region(&atlas, 1).unwrap().with_flip_x(true);
+        sprite.transform.origin = Vec2::ZERO;
+        let data = sprite.to_draw_data();
+        assert_eq!(data.image_id, atlas.image);
+        assert_eq!(data.size, Vec2::new(16.0, 8.0));
+        assert_eq!(
+            (data.uv_min, data.uv_max),
+            (Vec2::new(0.5, 0.0), Vec2::new(1.0, 1.0))
+        );
+
+        // The left edge of the region is drawn on the right of the same quad.
+        let [tl, tr, br, bl] = data.textured_corners();
+        assert_eq!([tl, tr, br, bl], {
+            let [tl, tr, br, bl] = data.world_corners();
+            [tr, tl, bl, br]
+        });
+        assert_eq!(tl, Vec2::new(16.0, 0.0));
+
+        let data = sprite.with_flip_x(false).with_flip_y(true).to_draw_data();
+        assert_eq!(data.textured_corners()[0], Vec2::new(0.0, 8.0));
     }
 }
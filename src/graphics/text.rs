@@ -1,7 +1,7 @@
 use crate::{
     core::assets::{font::FontId, manager::AssetManager},
     math::{Color, Transform, Vec2},
-    render::{Drawable, RenderContext, SpriteDrawData, Transform2d},
+    render::{BlendMode, Drawable, RenderContext, SpriteDrawData, Transform2d},
 };
 
 use crate::core::assets::font::FontAsset;
@@ -359,6 +359,10 @@ impl Text {
                 tint: self.color,
                 uv_min: glyph.uv_min,
                 uv_max: glyph.uv_max,
+                flip_x: false,
+                flip_y: false,
+                material: None,
+                blend: BlendMode::Alpha,
             });
 
             pen_x += (glyph.advance + letter_spacing) * scale;
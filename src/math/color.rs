@@ -378,13 +378,21 @@ impl Color {
         ]
     }
 
-    fn srgb_to_linear(component: f32) -> f32 {
+    pub(crate) fn srgb_to_linear(component: f32) -> f32 {
         if component <= 0.04045 {
             component / 12.92
         } else {
             ((component + 0.055) / 1.055).powf(2.4)
         }
     }
+
+    pub(crate) fn linear_to_srgb(component: f32) -> f32 {
+        if component <= 0.0031308 {
+            component * 12.92
+        } else {
+            1.055 * component.powf(1.0 / 2.4) - 0.055
+        }
+    }
 }
 
 impl From<String> for Color {
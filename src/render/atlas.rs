@@ -0,0 +1,220 @@
+/// Settings for packing small images into shared atlas pages.
+///
+/// Sprites whose images share a page can be drawn in a single batch. Only images with a
+/// clamp-to-edge sampler and no mipmaps are packed; one page set is kept per filter mode.
+/// Sprites keep using `uv_min` / `uv_max` in `0..1` image space, the renderer remaps them
+/// to the image's sub-region of the page (custom sprite materials see page UVs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasConfig {
+    /// Width and height of each page in pixels.
+    pub page_size: u32,
+    /// Images larger than this in either dimension get their own texture.
+    pub max_image_size: u32,
+    /// Pixels around each packed image filled with its edge texels, so linear filtering
+    /// never picks up neighbours.
+    pub padding: u32,
+}
+
+impl Default for AtlasConfig {
+    fn default() -> Self {
+        Self {
+            page_size: 2048,
+            max_image_size: 256,
+            padding: 1,
+        }
+    }
+}
+
+impl AtlasConfig {
+    /// Whether an image of this size is small enough to be packed.
+    pub fn accepts(&self, width: u32, height: u32) -> bool {
+        width <= self.max_image_size
+            && height <= self.max_image_size
+            && width + 2 * self.padding <= self.page_size
+            && height + 2 * self.padding <= self.page_size
+    }
+}
+
+/// Occupancy of one atlas page, as reported by `Renderer::atlas_stats`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasPageStats {
+    pub width: u32,
+    pub height: u32,
+    /// Images currently packed into the page.
+    pub images: usize,
+    /// Pixels covered by those images, padding excluded.
+    pub used_pixels: u64,
+}
+
+impl AtlasPageStats {
+    /// Fraction of the page covered by images, in `0..=1`.
+    pub fn occupancy(&self) -> f32 {
+        self.used_pixels as f32 / (self.width as f32 * self.height as f32)
+    }
+}
+
+/// Shelf rectangle packer for one atlas page.
+///
+/// Rectangles go into the shortest open shelf they fit in; a new shelf is opened below
+/// the last one when none fits. Removed rectangles are reused first, split into the
+/// part taken and the rest.
+#[derive(Debug, Clone)]
+pub(crate) struct ShelfPacker {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+    /// Removed rectangles as `(x, y, width, height)`.
+    free: Vec<(u32, u32, u32, u32)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Next free x position.
+    x: u32,
+}
+
+impl ShelfPacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            shelves: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Give back a rectangle returned by `insert`.
+    pub fn remove(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.free.push((x, y, width, height));
+    }
+
+    /// Reserve a `width` x `height` rectangle, returning its top-left corner, or `None`
+    /// when the page is full.
+    pub fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width > self.width || height > self.height {
+            return None;
+        }
+
+        // Smallest removed rectangle that fits; what is left of it stays free.
+        if let Some(index) = (0..self.free.len())
+            .filter(|&i| self.free[i].2 >= width && self.free[i].3 >= height)
+            .min_by_key(|&i| self.free[i].2 as u64 * self.free[i].3 as u64)
+        {
+            let (x, y, free_w, free_h) = self.free.swap_remove(index);
+            if free_w > width {
+                self.free.push((x + width, y, free_w - width, height));
+            }
+            if free_h > height {
+                self.free.push((x, y + height, free_w, free_h - height));
+            }
+            return Some((x, y));
+        }
+
+        let fits = |shelf: &&mut Shelf| shelf.height >= height && self.width - shelf.x >= width;
+        // Tall shelves would waste space on short images: only reuse shelves up to 1.5x.
+        if let Some(shelf) = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height <= height + height / 2)
+            .filter(fits)
+            .min_by_key(|shelf| shelf.height)
+        {
+            let x = shelf.x;
+            shelf.x += width;
+            return Some((x, shelf.y));
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map_or(0, |shelf| shelf.y + shelf.height);
+        if self.height - y < height {
+            // No room for a new shelf; fall back to any shelf that fits.
+            let shelf = self
+                .shelves
+                .iter_mut()
+                .filter(fits)
+                .min_by_key(|s| s.height)?;
+            let x = shelf.x;
+            shelf.x += width;
+            return Some((x, shelf.y));
+        }
+        self.shelves.push(Shelf {
+            y,
+            height,
+            x: width,
+        });
+        Some((0, y))
+    }
+}
+
+/// Copy of an RGBA8 image with `padding` pixels on every side repeating its edge texels.
+pub(crate) fn extrude(width: u32, height: u32, data: &[u8], padding: u32) -> Vec<u8> {
+    extrude_region((width, height), (0, 0, width, height), data, padding).2
+}
+
+/// Like `extrude`, for `data` covering only the `(x, y, width, height)` rect of an image
+/// of `image_size`: the rect grows by `padding` on the sides touching the image edges.
+///
+/// Returns the top-left corner of the grown rect in padded image coordinates, its size
+/// and its pixels.
+pub(crate) fn extrude_region(
+    image_size: (u32, u32),
+    rect: (u32, u32, u32, u32),
+    data: &[u8],
+    padding: u32,
+) -> ((u32, u32), (u32, u32), Vec<u8>) {
+    let (x, y, width, height) = rect;
+    let grow = |touches: bool| if touches { padding } else { 0 };
+    let (left, top) = (grow(x == 0), grow(y == 0));
+    let right = grow(x + width == image_size.0);
+    let bottom = grow(y + height == image_size.1);
+    let (out_w, out_h) = (width + left + right, height + top + bottom);
+
+    let mut out = Vec::with_capacity((out_w * out_h * 4) as usize);
+    for oy in 0..out_h {
+        let src_y = oy.saturating_sub(top).min(height - 1);
+        for ox in 0..out_w {
+            let src_x = ox.saturating_sub(left).min(width - 1);
+            let index = ((src_y * width + src_x) * 4) as usize;
+            out.extend_from_slice(&data[index..index + 4]);
+        }
+    }
+    ((x + padding - left, y + padding - top), (out_w, out_h), out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShelfPacker, extrude, extrude_region};
+
+    #[test]
+    fn packer_fills_shelves_without_overlap() {
+        let mut packer = ShelfPacker::new(64, 64);
+        let sizes = [(32, 16), (32, 16), (16, 16), (20, 30), (64, 40)];
+        let placed: Vec<_> = sizes.iter().map(|&(w, h)| packer.insert(w, h)).collect();
+        assert_eq!(
+            placed,
+            vec![
+                Some((0, 0)),
+                Some((32, 0)),
+                Some((0, 16)),
+                Some((0, 32)),
+                None
+            ]
+        );
+        assert_eq!(packer.insert(16, 8), Some((16, 16)));
+
+        // Removed space is reused, and what is left of it too.
+        packer.remove(0, 0, 32, 16);
+        assert_eq!(packer.insert(16, 16), Some((0, 0)));
+        assert_eq!(packer.insert(16, 16), Some((16, 0)));
+
+        let padded = extrude(1, 1, &[1, 2, 3, 4], 1);
+        assert_eq!(padded, [1, 2, 3, 4].repeat(9));
+        // Right column of a 2x1 image: only the top, right and bottom sides grow.
+        let (origin, size, _) = extrude_region((2, 1), (1, 0, 1, 1), &[0; 4], 1);
+        assert_eq!((origin, size), ((2, 0), (2, 3)));
+    }
+}
@@ -0,0 +1,79 @@
+/// How a draw's color is combined with what is already in the target.
+///
+/// Blending happens in linear space. `Alpha` is the default for shapes and sprites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// Standard "source over": `src * a + dst * (1 - a)`.
+    #[default]
+    Alpha,
+    /// Adds light: `src * a + dst`. Glows, fire, particles.
+    Additive,
+    /// Darkens: `src * dst + dst * (1 - a)`. Shadows and lighting overlays; expects
+    /// transparent texels to be black.
+    Multiply,
+    /// Lightens: `src + dst * (1 - src)`.
+    Screen,
+    /// "Source over" for colors already multiplied by alpha: `src + dst * (1 - a)`.
+    PremultipliedAlpha,
+    /// Replaces the target, alpha included.
+    Opaque,
+}
+
+impl BlendMode {
+    /// Every mode, in `index` order.
+    pub const ALL: [BlendMode; 6] = [
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::PremultipliedAlpha,
+        BlendMode::Opaque,
+    ];
+
+    /// Position in `ALL`, used by renderers to keep one pipeline per mode.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Blend `src` over `dst` on the CPU, with the same factors the GPU pipelines use.
+    /// Alpha always accumulates as in `Alpha` (except for `Opaque`).
+    pub(crate) fn apply(self, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+        let a = src[3];
+        let color = |c: usize| match self {
+            BlendMode::Alpha => src[c] * a + dst[c] * (1.0 - a),
+            BlendMode::Additive => src[c] * a + dst[c],
+            BlendMode::Multiply => src[c] * dst[c] + dst[c] * (1.0 - a),
+            BlendMode::Screen => src[c] + dst[c] * (1.0 - src[c]),
+            BlendMode::PremultipliedAlpha => src[c] + dst[c] * (1.0 - a),
+            BlendMode::Opaque => src[c],
+        };
+        let alpha = match self {
+            BlendMode::Opaque => a,
+            _ => a + dst[3] * (1.0 - a),
+        };
+        [color(0), color(1), color(2), alpha]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlendMode;
+
+    #[test]
+    fn modes_combine_half_transparent_source() {
+        let src = [1.0, 0.5, 0.0, 0.5];
+        let dst = [0.5, 0.5, 0.5, 1.0];
+        assert_eq!(BlendMode::Alpha.apply(src, dst), [0.75, 0.5, 0.25, 1.0]);
+        assert_eq!(BlendMode::Additive.apply(src, dst), [1.0, 0.75, 0.5, 1.0]);
+        assert_eq!(BlendMode::Multiply.apply(src, dst), [0.75, 0.5, 0.25, 1.0]);
+        assert_eq!(BlendMode::Screen.apply(src, dst), [1.0, 0.75, 0.5, 1.0]);
+        assert_eq!(
+            BlendMode::PremultipliedAlpha.apply(src, dst),
+            [1.25, 0.75, 0.25, 1.0]
+        );
+        assert_eq!(BlendMode::Opaque.apply(src, dst), src);
+        for (i, mode) in BlendMode::ALL.iter().enumerate() {
+            assert_eq!(mode.index(), i);
+        }
+    }
+}
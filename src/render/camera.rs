@@ -0,0 +1,160 @@
+use crate::math::vec2::Vec2;
+
+/// Screen-space rectangle (in screen units) a camera renders into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Viewport covering a whole render target of the given size.
+    pub fn full(size: (u32, u32)) -> Self {
+        Self::new(0.0, 0.0, size.0 as f32, size.1 as f32)
+    }
+
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(self.x + self.width * 0.5, self.y + self.height * 0.5)
+    }
+}
+
+/// 2D camera mapping world coordinates to screen pixels.
+///
+/// `position` is the world point shown at the center of the viewport. `zoom` scales
+/// the world (2.0 shows everything twice as big) and `rotation` (radians) rotates the
+/// view, so the world appears rotated by `-rotation` on screen.
+///
+/// Set it on a `RenderContext` with `set_camera` to draw in world space; use
+/// `screen_to_world` to convert `Input::mouse_position` into world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    pub position: Vec2,
+    pub zoom: f32,
+    pub rotation: f32,
+    /// Screen rectangle to render into. `None` uses the whole render target.
+    pub viewport: Option<Viewport>,
+}
+
+impl Camera2D {
+    /// Camera looking at `position`, with no zoom or rotation.
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport: None,
+        }
+    }
+
+    /// Builder: set the zoom factor.
+    pub fn with_zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// Builder: set the view rotation in radians.
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Builder: render into a sub-rectangle of the screen (split screen, minimaps...).
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    /// Effective viewport for a render target of `screen_size` pixels.
+    pub fn viewport_rect(&self, screen_size: (u32, u32)) -> Viewport {
+        self.viewport.unwrap_or_else(|| Viewport::full(screen_size))
+    }
+
+    /// Convert a world position to screen pixels.
+    pub fn world_to_screen(&self, world: impl Into<Vec2>, screen_size: (u32, u32)) -> Vec2 {
+        let center = self.viewport_rect(screen_size).center();
+        (world.into() - self.position).rotated(-self.rotation) * self.zoom + center
+    }
+
+    /// Convert screen pixels (e.g. `Input::mouse_position`) to a world position.
+    pub fn screen_to_world(&self, screen: impl Into<Vec2>, screen_size: (u32, u32)) -> Vec2 {
+        let center = self.viewport_rect(screen_size).center();
+        let zoom = if self.zoom != 0.0 { self.zoom } else { 1.0 };
+        ((screen.into() - center) / zoom).rotated(self.rotation) + self.position
+    }
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self::new(Vec2::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Camera2D, Viewport};
+    use crate::core::assets::ImageId;
+    use crate::math::Vec2;
+    use crate::render::{RenderContext, SpriteDrawData};
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-3, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn camera_position_maps_to_viewport_center() {
+        let camera = Camera2D::new(Vec2::new(100.0, 50.0)).with_zoom(2.0);
+        assert_close(
+            camera.world_to_screen(Vec2::new(100.0, 50.0), (800, 600)),
+            Vec2::new(400.0, 300.0),
+        );
+        assert_close(
+            camera.world_to_screen(Vec2::new(110.0, 50.0), (800, 600)),
+            Vec2::new(420.0, 300.0),
+        );
+
+        let split = camera.with_viewport(Viewport::new(400.0, 0.0, 400.0, 600.0));
+        assert_close(
+            split.world_to_screen(Vec2::new(100.0, 50.0), (800, 600)),
+            Vec2::new(600.0, 300.0),
+        );
+    }
+
+    #[test]
+    fn screen_to_world_inverts_world_to_screen() {
+        let camera = Camera2D::new(Vec2::new(-30.0, 12.0))
+            .with_zoom(0.5)
+            .with_rotation(0.7);
+        let world = Vec2::new(42.0, -17.0);
+        let screen = camera.world_to_screen(world, (640, 480));
+        assert_close(camera.screen_to_world(screen, (640, 480)), world);
+    }
+
+    #[test]
+    fn render_context_applies_camera_until_screen_space() {
+        let mut ctx = RenderContext::new((200, 100));
+        ctx.set_camera(Camera2D::new(Vec2::new(50.0, 50.0)).with_zoom(2.0));
+        // World (50, 50) is the center of the screen, i.e. NDC origin.
+        assert_close(ctx.to_ndc(Vec2::new(50.0, 50.0)), Vec2::ZERO);
+
+        let mut sprite = SpriteDrawData::new(ImageId::new(), 4, 4);
+        sprite.position = Vec2::new(60.0, 50.0);
+        ctx.draw_sprite(sprite.clone());
+        ctx.with_screen_space(|ctx| ctx.draw_sprite(sprite));
+
+        assert_close(ctx.sprites[0].position, Vec2::new(120.0, 50.0));
+        assert_close(ctx.sprites[0].scale, Vec2::new(2.0, 2.0));
+        assert_close(ctx.sprites[1].position, Vec2::new(60.0, 50.0));
+        assert!(ctx.camera().is_some());
+    }
+}
@@ -0,0 +1,158 @@
+use crate::core::assets::{ImageId, SamplerOptions};
+use crate::math::Vec2;
+use crate::render::renderer::{RenderResult, Renderer};
+use crate::render::{RenderContext, SpriteDrawData};
+
+/// Offscreen render target that can be drawn into and then used like any other image.
+///
+/// A canvas owns an `ImageId`: once something has been rendered into it (see
+/// `RenderContext::draw_to_canvas`), sprites using that id sample the canvas contents.
+/// Contents persist across frames until the canvas is drawn into again, so static
+/// backgrounds only need to be rendered once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Canvas {
+    id: ImageId,
+    width: u32,
+    height: u32,
+    sampler: SamplerOptions,
+}
+
+impl Canvas {
+    /// Create a canvas of `width` x `height` pixels with a fresh image id.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            id: ImageId::new(),
+            width: width.max(1),
+            height: height.max(1),
+            sampler: SamplerOptions::default(),
+        }
+    }
+
+    /// Builder: set how sprites sample the canvas (e.g. `SamplerOptions::pixel_art()`
+    /// to upscale a low-resolution canvas without blurring). Mipmaps are not generated.
+    pub fn with_sampler(mut self, sampler: SamplerOptions) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    pub fn id(&self) -> ImageId {
+        self.id
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn sampler(&self) -> SamplerOptions {
+        self.sampler
+    }
+
+    pub fn set_sampler(&mut self, sampler: SamplerOptions) {
+        self.sampler = sampler;
+    }
+
+    /// Resize the canvas, keeping its id. Previous contents are discarded the next time
+    /// it is drawn into.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width.max(1);
+        self.height = height.max(1);
+    }
+
+    /// Sprite draw data showing the whole canvas at its native size.
+    pub fn to_draw_data(self) -> SpriteDrawData {
+        SpriteDrawData::new(self.id, self.width, self.height)
+    }
+
+    /// Sprite draw data stretching the canvas over `size` pixels, anchored at its
+    /// top-left corner (e.g. upscaling a low-resolution pixel-art canvas to the window).
+    pub fn to_draw_data_scaled(self, position: Vec2, size: Vec2) -> SpriteDrawData {
+        let mut sprite = self.to_draw_data();
+        sprite.origin = Vec2::ZERO;
+        sprite.position = position;
+        sprite.scale = Vec2::new(size.x / self.width as f32, size.y / self.height as f32);
+        sprite
+    }
+}
+
+impl From<&Canvas> for SpriteDrawData {
+    fn from(canvas: &Canvas) -> Self {
+        canvas.to_draw_data()
+    }
+}
+
+/// Draw recorded into a canvas during a frame.
+pub(crate) struct CanvasPass {
+    pub canvas: Canvas,
+    pub ctx: RenderContext,
+}
+
+/// Render every canvas pass recorded in `ctx` (nested canvases first), so the main
+/// draw list can sample them afterwards.
+pub(crate) fn render_canvas_passes(
+    renderer: &mut dyn Renderer,
+    ctx: &RenderContext,
+) -> RenderResult<()> {
+    for pass in ctx.canvas_passes() {
+        render_canvas_passes(renderer, &pass.ctx)?;
+        for (id, params) in pass.ctx.material_params() {
+            renderer.set_material_params(*id, params);
+        }
+
+        let (width, height) = pass.canvas.size();
+        renderer.create_canvas(pass.canvas.id(), width, height, &pass.canvas.sampler())?;
+        renderer.render_to_canvas(
+            pass.canvas.id(),
+            pass.ctx.clear_color.map(|c| c.to_linear_rgba()),
+            &pass.ctx.draw_list(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Canvas, render_canvas_passes};
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{Drawable, Rectangle, RenderContext, Renderer, SoftwareRenderer};
+
+    /// Minimap-style frame: a 4x4 canvas (blue, with a red top-left quadrant) upscaled
+    /// onto the left half of an 8x4 target.
+    fn render_frame(renderer: &mut dyn Renderer, canvas: &Canvas) {
+        let mut ctx = RenderContext::new((8, 4));
+        ctx.clear(Color::BLACK);
+        ctx.draw_to_canvas(canvas, |c| {
+            c.clear(Color::BLUE);
+            Rectangle::new(Vec2::ZERO, Vec2::new(2.0, 2.0), Color::RED).draw(c);
+        });
+        ctx.draw_sprite(canvas.to_draw_data_scaled(Vec2::ZERO, Vec2::new(4.0, 4.0)));
+
+        render_canvas_passes(renderer, &ctx).unwrap();
+        renderer.set_clear_color(Color::BLACK.to_linear_rgba());
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+    }
+
+    fn assert_minimap(data: &[u8]) {
+        let pixel = |x: usize, y: usize| &data[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+        assert_eq!(pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(3, 3), [0, 0, 255, 255]);
+        assert_eq!(pixel(6, 1), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn canvas_is_sampled_as_sprite_in_software_renderer() {
+        let mut renderer = SoftwareRenderer::new(8, 4);
+        render_frame(&mut renderer, &Canvas::new(4, 4));
+        assert_minimap(&renderer.read_pixels());
+    }
+
+    #[test]
+    fn canvas_is_sampled_as_sprite_in_wgpu_renderer() {
+        let Some(mut renderer) = headless(8, 4) else {
+            return;
+        };
+        render_frame(&mut renderer, &Canvas::new(4, 4));
+        assert_minimap(&renderer.read_pixels().unwrap());
+    }
+}
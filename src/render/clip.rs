@@ -0,0 +1,147 @@
+use crate::render::camera::Viewport;
+use std::ops::Range;
+
+/// Rectangle of the render target, in pixels, that draws are clipped to.
+///
+/// Used through `RenderContext::push_clip_rect`; renderers apply it as a scissor rect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClipRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ClipRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Pixels covered by a viewport, rounded to the nearest pixel edges.
+    pub fn from_viewport(viewport: &Viewport) -> Self {
+        let x0 = viewport.x.round().max(0.0) as u32;
+        let y0 = viewport.y.round().max(0.0) as u32;
+        let x1 = (viewport.x + viewport.width).round().max(0.0) as u32;
+        let y1 = (viewport.y + viewport.height).round().max(0.0) as u32;
+        Self::new(x0, y0, x1.saturating_sub(x0), y1.saturating_sub(y0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Overlap of both rectangles (empty if they do not overlap).
+    pub fn intersect(&self, other: &ClipRect) -> ClipRect {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.width).min(other.x + other.width);
+        let y1 = (self.y + self.height).min(other.y + other.height);
+        Self::new(x0, y0, x1.saturating_sub(x0), y1.saturating_sub(y0))
+    }
+
+    /// Clamp to a render target of `size` pixels.
+    pub fn clamp_to(&self, size: (u32, u32)) -> ClipRect {
+        self.intersect(&ClipRect::new(0, 0, size.0, size.1))
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+}
+
+/// Stencil mask recorded with `RenderContext::push_mask`.
+///
+/// `vertices` is a range of shape triangles (in NDC) in the frame's vertex list that is
+/// never drawn in color. Nested masks point to the enclosing one with `parent`; draws
+/// inside a mask are only visible where the whole chain overlaps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    pub parent: Option<usize>,
+    pub vertices: Range<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClipRect;
+    use crate::math::{Color, Vec2};
+    use crate::render::camera::Viewport;
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{Camera2D, Drawable, Rectangle, RenderContext, Renderer, SoftwareRenderer};
+
+    fn render_clipped(renderer: &mut dyn Renderer) {
+        let rect = |x: f32, y: f32, w: f32, h: f32, color| {
+            Rectangle::new(Vec2::new(x, y), Vec2::new(w, h), color)
+        };
+        let mut ctx = RenderContext::new((8, 8));
+        ctx.clear(Color::BLACK);
+        ctx.with_clip_rect(ClipRect::new(0, 0, 4, 8), |ctx| {
+            rect(0.0, 0.0, 8.0, 8.0, Color::WHITE).draw(ctx);
+        });
+        // Nested masks: right half and top half leave the top-right quadrant.
+        ctx.with_mask(
+            |mask| rect(4.0, 0.0, 4.0, 8.0, Color::WHITE).draw(mask),
+            |ctx| {
+                ctx.with_mask(
+                    |mask| rect(0.0, 0.0, 8.0, 4.0, Color::WHITE).draw(mask),
+                    |ctx| rect(0.0, 0.0, 8.0, 8.0, Color::BLUE).draw(ctx),
+                );
+            },
+        );
+        rect(6.0, 6.0, 2.0, 2.0, Color::GREEN).draw(&mut ctx);
+
+        renderer.set_clear_color(Color::BLACK.to_linear_rgba());
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+    }
+
+    fn assert_clipped(data: &[u8]) {
+        let pixel = |x: usize, y: usize| &data[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+        assert_eq!(pixel(1, 5), [255, 255, 255, 255]);
+        assert_eq!(pixel(5, 1), [0, 0, 255, 255]);
+        assert_eq!(pixel(5, 5), [0, 0, 0, 255]);
+        assert_eq!(pixel(7, 7), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn clip_rects_and_masks_in_both_renderers() {
+        let mut cpu = SoftwareRenderer::new(8, 8);
+        render_clipped(&mut cpu);
+        assert_clipped(&cpu.read_pixels());
+
+        if let Some(mut gpu) = headless(8, 8) {
+            render_clipped(&mut gpu);
+            assert_clipped(&gpu.read_pixels().unwrap());
+        }
+    }
+
+    #[test]
+    fn camera_viewport_clips_its_draws() {
+        let mut ctx = RenderContext::new((100, 100));
+        ctx.push_clip_rect(ClipRect::new(0, 0, 60, 60));
+        ctx.set_camera(
+            Camera2D::new(Vec2::ZERO).with_viewport(Viewport::new(50.0, 50.0, 50.0, 50.0)),
+        );
+        Rectangle::new(Vec2::ZERO, Vec2::new(10.0, 10.0), Color::RED).draw(&mut ctx);
+        assert_eq!(
+            ctx.draw_list().commands[0].clip,
+            Some(ClipRect::new(50, 50, 10, 10))
+        );
+    }
+
+    #[test]
+    fn intersect_and_viewport_conversion() {
+        let a = ClipRect::new(0, 0, 10, 10);
+        let b = ClipRect::new(5, 8, 10, 10);
+        assert_eq!(a.intersect(&b), ClipRect::new(5, 8, 5, 2));
+        assert!(a.intersect(&ClipRect::new(20, 0, 5, 5)).is_empty());
+        assert_eq!(
+            ClipRect::from_viewport(&Viewport::new(9.6, -3.0, 20.0, 10.2)),
+            ClipRect::new(10, 0, 20, 7)
+        );
+    }
+}
@@ -1,50 +1,499 @@
+use crate::core::assets::ImageId;
 use crate::math::color::Color;
 use crate::math::vec2::Vec2;
-use crate::render::{SpriteDrawData, Vertex};
+use crate::render::blend::BlendMode;
+use crate::render::camera::Camera2D;
+use crate::render::canvas::{Canvas, CanvasPass};
+use crate::render::clip::{ClipRect, Mask};
+use crate::render::draw_list::{
+    self, DrawCommand, DrawCommandKind, DrawList, GradientDraw, MeshDraw,
+};
+use crate::render::gradient::Gradient;
+use crate::render::material::{MaterialId, MaterialParams};
+use crate::render::scaling::ScreenScale;
+use crate::render::{GradientVertex, SdfVertex, SpriteDrawData, TexturedVertex, Vertex};
 
 /// CPU-side draw list. Collects vertices and clear color; no renderer coupling.
+///
+/// Shapes, sprites, meshes, gradient fills and SDF shapes are drawn in submission order,
+/// grouped by layer: everything on a higher layer is drawn on top of lower layers (see
+/// `set_layer`).
+///
+/// Positions are screen units by default: pixels of `size`, which the engine maps to the
+/// window's physical pixels according to its `ScalePolicy` (see `screen_scale`). After
+/// `set_camera`, draws are in world space and go through the camera until
+/// `reset_camera` (see `with_screen_space`).
+///
+/// Draws can be clipped to rectangles (`push_clip_rect`) and to arbitrary shapes
+/// (`push_mask`). A camera with a viewport also clips its draws to that viewport.
 pub struct RenderContext {
     pub vertices: Vec<Vertex>,
     pub clear_color: Option<Color>,
     pub size: (u32, u32),
     pub sprites: Vec<SpriteDrawData>,
+    screen: ScreenScale,
+    mesh_vertices: Vec<TexturedVertex>,
+    mesh_indices: Vec<u32>,
+    meshes: Vec<MeshDraw>,
+    gradient_vertices: Vec<GradientVertex>,
+    gradients: Vec<GradientDraw>,
+    sdf_vertices: Vec<SdfVertex>,
+    commands: Vec<DrawCommand>,
+    layer: i32,
+    camera: Option<Camera2D>,
+    material: Option<MaterialId>,
+    blend: BlendMode,
+    material_params: Vec<(MaterialId, MaterialParams)>,
+    clip_stack: Vec<ClipRect>,
+    masks: Vec<Mask>,
+    mask_stack: Vec<usize>,
+    canvas_passes: Vec<CanvasPass>,
+    // Number of vertices/sprites already covered by `commands`.
+    recorded_vertices: usize,
+    recorded_sprites: usize,
 }
 
 impl RenderContext {
+    /// Context drawing directly in the pixels of a `size` target.
     pub fn new(size: (u32, u32)) -> Self {
+        Self::with_screen_scale(ScreenScale::identity(size))
+    }
+
+    /// Context drawing in the game units of `screen`, mapped to its physical pixels.
+    pub fn with_screen_scale(screen: ScreenScale) -> Self {
         Self {
             vertices: Vec::new(),
             clear_color: None,
-            size,
+            size: screen.size,
             sprites: Vec::new(),
+            screen,
+            mesh_vertices: Vec::new(),
+            mesh_indices: Vec::new(),
+            meshes: Vec::new(),
+            gradient_vertices: Vec::new(),
+            gradients: Vec::new(),
+            sdf_vertices: Vec::new(),
+            commands: Vec::new(),
+            layer: 0,
+            camera: None,
+            material: None,
+            blend: BlendMode::Alpha,
+            material_params: Vec::new(),
+            clip_stack: Vec::new(),
+            masks: Vec::new(),
+            mask_stack: Vec::new(),
+            canvas_passes: Vec::new(),
+            recorded_vertices: 0,
+            recorded_sprites: 0,
         }
     }
 
+    /// Mapping from this context's units to target pixels.
+    pub fn screen_scale(&self) -> &ScreenScale {
+        &self.screen
+    }
+
+    /// Target pixels per unit: rasterize text and other pixel-sized assets at this
+    /// scale to keep them crisp.
+    pub fn pixel_scale(&self) -> f32 {
+        self.screen.scale
+    }
+
     /// Request screen clear at frame start.
     pub fn clear(&mut self, color: Color) {
         self.clear_color = Some(color);
     }
 
+    /// Layer used by subsequent draws (default `0`).
+    pub fn layer(&self) -> i32 {
+        self.layer
+    }
+
+    /// Set the layer for subsequent draws. Higher layers are drawn on top; draws on
+    /// the same layer keep their submission order.
+    pub fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    /// Run `f` with draws going to `layer`, then restore the previous layer.
+    pub fn with_layer(&mut self, layer: i32, f: impl FnOnce(&mut Self)) {
+        let previous = std::mem::replace(&mut self.layer, layer);
+        f(self);
+        self.layer = previous;
+    }
+
+    /// Active camera, or `None` when drawing in screen space.
+    pub fn camera(&self) -> Option<&Camera2D> {
+        self.camera.as_ref()
+    }
+
+    /// Draw subsequent shapes and sprites in world space through `camera`.
+    pub fn set_camera(&mut self, camera: Camera2D) {
+        self.camera = Some(camera);
+    }
+
+    /// Draw subsequent shapes and sprites in screen pixels again.
+    pub fn reset_camera(&mut self) {
+        self.camera = None;
+    }
+
+    /// Run `f` in screen space (e.g. for a HUD), then restore the active camera.
+    pub fn with_screen_space(&mut self, f: impl FnOnce(&mut Self)) {
+        let previous = self.camera.take();
+        f(self);
+        self.camera = previous;
+    }
+
+    /// Convert a world position to screen pixels using the active camera.
+    /// Identity in screen space.
+    pub fn world_to_screen(&self, p: Vec2) -> Vec2 {
+        match &self.camera {
+            Some(camera) => camera.world_to_screen(p, self.size),
+            None => p,
+        }
+    }
+
+    /// Convert screen pixels to a world position using the active camera.
+    /// Identity in screen space.
+    pub fn screen_to_world(&self, p: Vec2) -> Vec2 {
+        match &self.camera {
+            Some(camera) => camera.screen_to_world(p, self.size),
+            None => p,
+        }
+    }
+
+    /// Material used by subsequent shape draws, if any.
+    pub fn material(&self) -> Option<MaterialId> {
+        self.material
+    }
+
+    /// Draw subsequent shapes with a shape material (`None` restores the default).
+    /// Sprites use `SpriteDrawData::material` instead.
+    pub fn set_material(&mut self, material: Option<MaterialId>) {
+        self.material = material;
+    }
+
+    /// Run `f` with shapes drawn using `material`, then restore the previous material.
+    pub fn with_material(&mut self, material: MaterialId, f: impl FnOnce(&mut Self)) {
+        let previous = self.material.replace(material);
+        f(self);
+        self.material = previous;
+    }
+
+    /// Blend mode used by subsequent shape draws (default `BlendMode::Alpha`).
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Blend subsequent shapes with `blend`. Sprites use `SpriteDrawData::blend` instead.
+    pub fn set_blend_mode(&mut self, blend: BlendMode) {
+        self.blend = blend;
+    }
+
+    /// Run `f` with shapes blended using `blend`, then restore the previous mode.
+    pub fn with_blend_mode(&mut self, blend: BlendMode, f: impl FnOnce(&mut Self)) {
+        let previous = std::mem::replace(&mut self.blend, blend);
+        f(self);
+        self.blend = previous;
+    }
+
+    /// Update the uniforms and extra textures of a material for this frame.
+    ///
+    /// Parameters are per material: the last update of the frame applies to all of
+    /// its draws, and stays in effect for later frames.
+    pub fn set_material_params(&mut self, material: MaterialId, params: MaterialParams) {
+        self.material_params.push((material, params));
+    }
+
+    pub(crate) fn material_params(&self) -> &[(MaterialId, MaterialParams)] {
+        &self.material_params
+    }
+
+    /// Clip rect applied to subsequent draws by `push_clip_rect`, if any.
+    pub fn clip_rect(&self) -> Option<ClipRect> {
+        self.clip_stack.last().copied()
+    }
+
+    /// Clip subsequent draws to `rect` (screen units, not affected by the camera)
+    /// until the matching `pop_clip`. Nested clip rects intersect.
+    pub fn push_clip_rect(&mut self, rect: ClipRect) {
+        let rect = match self.clip_stack.last() {
+            Some(current) => current.intersect(&rect),
+            None => rect,
+        };
+        self.clip_stack.push(rect);
+    }
+
+    /// Remove the innermost clip rect.
+    pub fn pop_clip(&mut self) {
+        self.clip_stack.pop();
+    }
+
+    /// Run `f` with draws clipped to `rect`, then pop it.
+    pub fn with_clip_rect(&mut self, rect: ClipRect, f: impl FnOnce(&mut Self)) {
+        self.push_clip_rect(rect);
+        f(self);
+        self.pop_clip();
+    }
+
+    /// Only show subsequent draws inside the shapes drawn by `f` until the matching
+    /// `pop_mask` (circular minimaps, portrait frames...).
+    ///
+    /// `f` draws into a context sharing this one's size and camera. Only the coverage
+    /// of its shapes matters: their colors are never drawn, and sprites are ignored.
+    /// Nested masks intersect.
+    pub fn push_mask(&mut self, f: impl FnOnce(&mut RenderContext)) {
+        let mut mask_ctx = RenderContext::with_screen_scale(self.screen);
+        mask_ctx.size = self.size;
+        mask_ctx.camera = self.camera;
+        f(&mut mask_ctx);
+
+        // Keep vertices pushed directly into `vertices` in their own command.
+        if self.recorded_vertices < self.vertices.len() {
+            self.record_shapes();
+        }
+        let start = self.vertices.len();
+        self.vertices.extend_from_slice(&mask_ctx.vertices);
+        self.recorded_vertices = self.vertices.len();
+
+        self.masks.push(Mask {
+            parent: self.mask_stack.last().copied(),
+            vertices: start..self.vertices.len(),
+        });
+        self.mask_stack.push(self.masks.len() - 1);
+    }
+
+    /// Remove the innermost mask.
+    pub fn pop_mask(&mut self) {
+        self.mask_stack.pop();
+    }
+
+    /// Run `content` masked by the shapes drawn by `mask`, then pop the mask.
+    pub fn with_mask(
+        &mut self,
+        mask: impl FnOnce(&mut RenderContext),
+        content: impl FnOnce(&mut Self),
+    ) {
+        self.push_mask(mask);
+        content(self);
+        self.pop_mask();
+    }
+
     /// Push a single vertex.
     pub fn push(&mut self, v: Vertex) {
         self.vertices.push(v);
+        self.record_shapes();
     }
 
     /// Push many vertices (typical path for shapes).
     pub fn extend(&mut self, verts: &[Vertex]) {
         self.vertices.extend_from_slice(verts);
+        self.record_shapes();
     }
 
     /// Queue a sprite to be rendered this frame.
     /// Accepts anything that can be converted into sprite draw data.
     pub fn draw_sprite(&mut self, sprite: impl Into<SpriteDrawData>) {
-        self.sprites.push(sprite.into());
+        let mut sprite = sprite.into();
+        if let Some(camera) = &self.camera {
+            sprite.position = camera.world_to_screen(sprite.position, self.size);
+            sprite.scale = sprite.scale * camera.zoom;
+            sprite.rotation -= camera.rotation;
+        }
+        // Sprites are submitted in target pixels.
+        if !self.screen.is_identity() {
+            sprite.position = self.screen.to_physical(sprite.position);
+            sprite.scale = sprite.scale * self.screen.scale;
+        }
+        self.sprites.push(sprite);
+        self.record_sprites();
+    }
+
+    /// Queue a textured mesh: `vertices` in NDC (see `to_ndc`) and `indices` listing
+    /// their triangles. Triangles with an out-of-range index and a trailing partial
+    /// triangle are dropped. Meshes use the current blend mode, but no material.
+    pub fn draw_mesh(
+        &mut self,
+        vertices: &[TexturedVertex],
+        indices: &[u32],
+        texture: Option<ImageId>,
+    ) {
+        let index_start = self.mesh_indices.len();
+        self.mesh_indices.extend(
+            indices
+                .chunks_exact(3)
+                .filter(|tri| tri.iter().all(|&i| (i as usize) < vertices.len()))
+                .flatten(),
+        );
+        if self.mesh_indices.len() == index_start {
+            return;
+        }
+        let vertex_start = self.mesh_vertices.len();
+        self.mesh_vertices.extend_from_slice(vertices);
+        self.meshes.push(MeshDraw {
+            texture,
+            vertices: vertex_start..self.mesh_vertices.len(),
+            indices: index_start..self.mesh_indices.len(),
+        });
+        let mesh = self.meshes.len() - 1;
+        let command = self.command(DrawCommandKind::Meshes(mesh..mesh + 1));
+        draw_list::record(&mut self.commands, command);
+    }
+
+    /// Queue triangles (`vertices` in NDC, three per triangle) filled with `gradient`,
+    /// e.g. from `Rectangle::with_gradient`. A trailing partial triangle is dropped.
+    /// Gradient fills use the current blend mode, but no material.
+    pub fn fill_gradient(&mut self, vertices: &[GradientVertex], gradient: &Gradient) {
+        let count = vertices.len() - vertices.len() % 3;
+        if count == 0 {
+            return;
+        }
+        let start = self.gradient_vertices.len();
+        self.gradient_vertices.extend_from_slice(&vertices[..count]);
+        self.gradients.push(GradientDraw {
+            gradient: gradient.clone(),
+            vertices: start..self.gradient_vertices.len(),
+        });
+        let index = self.gradients.len() - 1;
+        let command = self.command(DrawCommandKind::Gradients(index..index + 1));
+        draw_list::record(&mut self.commands, command);
+    }
+
+    /// Queue shapes drawn from their distance field (`vertices` in NDC, six per shape),
+    /// e.g. from `RoundedRect`. A trailing partial shape is dropped. SDF shapes use the
+    /// current blend mode, but no material.
+    pub fn draw_sdf(&mut self, vertices: &[SdfVertex]) {
+        let count = vertices.len() - vertices.len() % 6;
+        if count == 0 {
+            return;
+        }
+        let start = self.sdf_vertices.len();
+        self.sdf_vertices.extend_from_slice(&vertices[..count]);
+        let command = self.command(DrawCommandKind::SdfShapes(start..self.sdf_vertices.len()));
+        draw_list::record(&mut self.commands, command);
+    }
+
+    /// Record draws into `canvas` instead of the screen.
+    ///
+    /// `f` receives a fresh context sized to the canvas (no camera, layer 0). Call
+    /// `clear` on it to wipe the previous contents; otherwise new draws are composited
+    /// over what the canvas already holds. Canvases are rendered before the frame, so
+    /// the canvas image can be drawn as a sprite in the same frame.
+    pub fn draw_to_canvas(&mut self, canvas: &Canvas, f: impl FnOnce(&mut RenderContext)) {
+        let mut ctx = RenderContext::new(canvas.size());
+        f(&mut ctx);
+        self.canvas_passes.push(CanvasPass {
+            canvas: *canvas,
+            ctx,
+        });
+    }
+
+    pub(crate) fn canvas_passes(&self) -> &[CanvasPass] {
+        &self.canvas_passes
+    }
+
+    /// Draw commands sorted by layer, ready to be submitted to a renderer of
+    /// `screen_scale().physical_size` pixels.
+    ///
+    /// Vertices or sprites pushed directly into the public vectors (bypassing
+    /// `push`/`extend`/`draw_sprite`) are drawn last, on the current layer.
+    pub fn draw_list(&self) -> DrawList<'_> {
+        let mut commands = self.commands.clone();
+        if self.recorded_vertices < self.vertices.len() {
+            draw_list::record(
+                &mut commands,
+                self.command(DrawCommandKind::Shapes(
+                    self.recorded_vertices..self.vertices.len(),
+                )),
+            );
+        }
+        if self.recorded_sprites < self.sprites.len() {
+            draw_list::record(
+                &mut commands,
+                self.command(DrawCommandKind::Sprites(
+                    self.recorded_sprites..self.sprites.len(),
+                )),
+            );
+        }
+        // Stable: keeps submission order within a layer.
+        commands.sort_by_key(|cmd| cmd.layer);
+
+        DrawList {
+            vertices: &self.vertices,
+            sprites: &self.sprites,
+            mesh_vertices: &self.mesh_vertices,
+            mesh_indices: &self.mesh_indices,
+            meshes: &self.meshes,
+            gradient_vertices: &self.gradient_vertices,
+            gradients: &self.gradients,
+            sdf_vertices: &self.sdf_vertices,
+            masks: &self.masks,
+            commands,
+        }
+    }
+
+    fn record_shapes(&mut self) {
+        let range = self.recorded_vertices..self.vertices.len();
+        self.recorded_vertices = self.vertices.len();
+        let command = self.command(DrawCommandKind::Shapes(range));
+        draw_list::record(&mut self.commands, command);
+    }
+
+    fn record_sprites(&mut self) {
+        let range = self.recorded_sprites..self.sprites.len();
+        self.recorded_sprites = self.sprites.len();
+        let command = self.command(DrawCommandKind::Sprites(range));
+        draw_list::record(&mut self.commands, command);
+    }
+
+    /// Command for `kind` with the current state. Sprites carry their own material
+    /// and blend mode; meshes, gradient fills and SDF shapes have no material.
+    fn command(&self, kind: DrawCommandKind) -> DrawCommand {
+        let (material, blend) = match kind {
+            DrawCommandKind::Shapes(_) => (self.material, self.blend),
+            DrawCommandKind::Sprites(_) => (None, BlendMode::Alpha),
+            DrawCommandKind::Meshes(_)
+            | DrawCommandKind::Gradients(_)
+            | DrawCommandKind::SdfShapes(_) => (None, self.blend),
+        };
+        DrawCommand {
+            layer: self.layer,
+            material,
+            blend,
+            clip: self.effective_clip(),
+            mask: self.mask_stack.last().copied(),
+            kind,
+        }
+    }
+
+    /// Innermost clip rect intersected with the camera viewport, if any, in target
+    /// pixels. Letterboxed screens always clip to the game area.
+    fn effective_clip(&self) -> Option<ClipRect> {
+        let viewport = self
+            .camera
+            .as_ref()
+            .and_then(|camera| camera.viewport.as_ref())
+            .map(ClipRect::from_viewport);
+        let clip = match (self.clip_rect(), viewport) {
+            (Some(clip), Some(viewport)) => Some(clip.intersect(&viewport)),
+            (clip, viewport) => clip.or(viewport),
+        };
+        if self.screen.is_identity() {
+            return clip;
+        }
+        let clip = clip.map(|rect| self.screen.clip_to_physical(&rect));
+        if self.screen.offset == Vec2::ZERO {
+            return clip;
+        }
+        let frame = self.screen.frame();
+        Some(clip.map_or(frame, |rect| rect.intersect(&frame)))
     }
 
-    /// Convert pixel-space to NDC.
+    /// Convert a draw position to NDC, applying the active camera if any.
     pub fn to_ndc(&self, p: Vec2) -> Vec2 {
-        let w = self.size.0.max(1) as f32;
-        let h = self.size.1.max(1) as f32;
+        let p = self.screen.to_physical(self.world_to_screen(p));
+        let w = self.screen.physical_size.0.max(1) as f32;
+        let h = self.screen.physical_size.1.max(1) as f32;
 
         Vec2 {
             x: (p.x / w) * 2.0 - 1.0,
@@ -0,0 +1,177 @@
+use crate::core::assets::ImageId;
+use crate::render::blend::BlendMode;
+use crate::render::clip::{ClipRect, Mask};
+use crate::render::gradient::Gradient;
+use crate::render::material::MaterialId;
+use crate::render::vertex::{GradientVertex, SdfVertex, TexturedVertex};
+use crate::render::{SpriteDrawData, Vertex};
+use std::ops::Range;
+
+/// What a single draw command renders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrawCommandKind {
+    /// A run of shape triangles, as a range into the frame's vertex list.
+    Shapes(Range<usize>),
+    /// A run of sprites, as a range into the frame's sprite list.
+    Sprites(Range<usize>),
+    /// A run of textured meshes, as a range into the frame's mesh list.
+    Meshes(Range<usize>),
+    /// A run of gradient fills, as a range into the frame's gradient list.
+    Gradients(Range<usize>),
+    /// A run of shapes drawn from their distance field, as a range into the frame's
+    /// SDF vertex list (six vertices per shape).
+    SdfShapes(Range<usize>),
+}
+
+/// One textured mesh of the frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeshDraw {
+    /// Image sampled by the mesh; `None` draws vertex colors only.
+    pub texture: Option<ImageId>,
+    /// Range into `DrawList::mesh_vertices`.
+    pub vertices: Range<usize>,
+    /// Range into `DrawList::mesh_indices`: whole triangles, indexing into the mesh's
+    /// own vertices.
+    pub indices: Range<usize>,
+}
+
+/// Triangles of the frame filled with one gradient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientDraw {
+    pub gradient: Gradient,
+    /// Range into `DrawList::gradient_vertices`, three vertices per triangle.
+    pub vertices: Range<usize>,
+}
+
+/// One entry of the frame's draw list.
+///
+/// Commands are recorded in submission order. Consecutive draws of the same kind
+/// sharing the same layer, material, blend mode, clip rect and mask are merged into a
+/// single command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrawCommand {
+    /// Draw layer (z). Higher layers are drawn on top of lower ones; draws on the same
+    /// layer keep their submission order.
+    pub layer: i32,
+    /// Material for shape runs (`RenderContext::set_material`). Sprites carry their
+    /// own material in `SpriteDrawData`; meshes, gradients and SDF shapes have none.
+    pub material: Option<MaterialId>,
+    /// Blend mode for shape, mesh, gradient and SDF shape runs
+    /// (`RenderContext::set_blend_mode`).
+    /// Sprites carry their own mode in `SpriteDrawData`.
+    pub blend: BlendMode,
+    /// Scissor rect in target pixels (`RenderContext::push_clip_rect` and camera
+    /// viewports).
+    pub clip: Option<ClipRect>,
+    /// Innermost stencil mask, as an index into `DrawList::masks`.
+    pub mask: Option<usize>,
+    pub kind: DrawCommandKind,
+}
+
+/// Ordered view over a frame's shapes, sprites, meshes, gradient fills and SDF shapes,
+/// ready to be handed to a renderer.
+///
+/// Produced by `RenderContext::draw_list`. `commands` are already sorted by layer, so
+/// renderers only need to draw them in sequence.
+pub struct DrawList<'a> {
+    pub vertices: &'a [Vertex],
+    pub sprites: &'a [SpriteDrawData],
+    pub mesh_vertices: &'a [TexturedVertex],
+    pub mesh_indices: &'a [u32],
+    pub meshes: &'a [MeshDraw],
+    pub gradient_vertices: &'a [GradientVertex],
+    pub gradients: &'a [GradientDraw],
+    pub sdf_vertices: &'a [SdfVertex],
+    pub masks: &'a [Mask],
+    pub commands: Vec<DrawCommand>,
+}
+
+impl DrawList<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Triangles of `mask` and all its parents, outermost first.
+    pub fn mask_chain(&self, mask: Option<usize>) -> Vec<&[Vertex]> {
+        let mut chain = Vec::new();
+        let mut next = mask;
+        while let Some(index) = next {
+            let mask = &self.masks[index];
+            chain.push(&self.vertices[mask.vertices.clone()]);
+            next = mask.parent;
+        }
+        chain.reverse();
+        chain
+    }
+}
+
+/// Record `command`, extending the last command instead when it has the same state
+/// and its range is contiguous with the new one.
+pub(crate) fn record(commands: &mut Vec<DrawCommand>, command: DrawCommand) {
+    if let Some(last) = commands.last_mut()
+        && last.layer == command.layer
+        && last.material == command.material
+        && last.blend == command.blend
+        && last.clip == command.clip
+        && last.mask == command.mask
+    {
+        match (&mut last.kind, &command.kind) {
+            (DrawCommandKind::Shapes(prev), DrawCommandKind::Shapes(next))
+            | (DrawCommandKind::Sprites(prev), DrawCommandKind::Sprites(next))
+            | (DrawCommandKind::Meshes(prev), DrawCommandKind::Meshes(next))
+            | (DrawCommandKind::Gradients(prev), DrawCommandKind::Gradients(next))
+            | (DrawCommandKind::SdfShapes(prev), DrawCommandKind::SdfShapes(next))
+                if prev.end == next.start =>
+            {
+                prev.end = next.end;
+                return;
+            }
+            _ => {}
+        }
+    }
+    commands.push(command);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrawCommandKind::{Shapes, Sprites};
+    use crate::core::assets::ImageId;
+    use crate::render::{RenderContext, SpriteDrawData, Vertex};
+
+    fn vertex() -> Vertex {
+        Vertex {
+            pos: [0.0, 0.0],
+            color: [1.0; 4],
+        }
+    }
+
+    #[test]
+    fn merges_runs_and_sorts_layers_stably() {
+        let sprite = SpriteDrawData::new(ImageId::new(), 1, 1);
+        let mut ctx = RenderContext::new((1, 1));
+        ctx.extend(&[vertex(); 3]);
+        ctx.extend(&[vertex(); 3]);
+        ctx.set_layer(2);
+        ctx.draw_sprite(sprite.clone());
+        ctx.set_layer(0);
+        ctx.draw_sprite(sprite.clone());
+        ctx.draw_sprite(sprite);
+        ctx.push(vertex());
+
+        let kinds: Vec<_> = ctx
+            .draw_list()
+            .commands
+            .into_iter()
+            .map(|cmd| (cmd.layer, cmd.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (0, Shapes(0..6)),
+                (0, Sprites(1..3)),
+                (0, Shapes(6..7)),
+                (2, Sprites(0..1)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::math::color::Color;
+use crate::math::vec2::Vec2;
+
+/// Stops kept per gradient; later stops are ignored.
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+/// Color at a position along a gradient, `0.0` (start) to `1.0` (end).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorStop {
+    pub offset: f32,
+    pub color: Color,
+}
+
+impl ColorStop {
+    pub fn new(offset: f32, color: Color) -> Self {
+        Self { offset, color }
+    }
+}
+
+/// How the gradient position of a point is computed.
+///
+/// Coordinates are relative to the filled shape's bounds before its transform:
+/// `(0, 0)` is the top-left corner and `(1, 1)` the bottom-right one, so gradients
+/// follow the shape as it moves, rotates and resizes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientKind {
+    /// Along the line from `start` to `end`, constant across it.
+    Linear { start: Vec2, end: Vec2 },
+    /// Distance from `center`, reaching the end at `radius`.
+    Radial { center: Vec2, radius: f32 },
+    /// Angle around `center`, clockwise from `angle` (radians, `0` points right).
+    Conic { center: Vec2, angle: f32 },
+}
+
+/// Fill blending between color stops, evaluated for every pixel.
+///
+/// Colors are interpolated in linear space. Positions before the first stop take its
+/// color, positions after the last stop take the last color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    stops: Vec<ColorStop>,
+}
+
+impl Gradient {
+    /// Gradient with `stops` sorted by offset. Offsets are clamped to `0..=1` and
+    /// only the first `MAX_GRADIENT_STOPS` stops are kept.
+    pub fn new(kind: GradientKind, stops: impl IntoIterator<Item = ColorStop>) -> Self {
+        let mut stops: Vec<ColorStop> = stops
+            .into_iter()
+            .map(|stop| ColorStop::new(stop.offset.clamp(0.0, 1.0), stop.color))
+            .collect();
+        if stops.len() > MAX_GRADIENT_STOPS {
+            log::warn!(
+                "Gradient has {} stops; only the first {} are used",
+                stops.len(),
+                MAX_GRADIENT_STOPS
+            );
+            stops.truncate(MAX_GRADIENT_STOPS);
+        }
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Self { kind, stops }
+    }
+
+    pub fn linear(start: Vec2, end: Vec2, stops: impl IntoIterator<Item = ColorStop>) -> Self {
+        Self::new(GradientKind::Linear { start, end }, stops)
+    }
+
+    pub fn radial(center: Vec2, radius: f32, stops: impl IntoIterator<Item = ColorStop>) -> Self {
+        Self::new(GradientKind::Radial { center, radius }, stops)
+    }
+
+    pub fn conic(center: Vec2, angle: f32, stops: impl IntoIterator<Item = ColorStop>) -> Self {
+        Self::new(GradientKind::Conic { center, angle }, stops)
+    }
+
+    /// Left-to-right gradient from `from` to `to` (health bars, panels).
+    pub fn horizontal(from: Color, to: Color) -> Self {
+        Self::linear(
+            Vec2::new(0.0, 0.5),
+            Vec2::new(1.0, 0.5),
+            [ColorStop::new(0.0, from), ColorStop::new(1.0, to)],
+        )
+    }
+
+    /// Top-to-bottom gradient from `from` to `to`.
+    pub fn vertical(from: Color, to: Color) -> Self {
+        Self::linear(
+            Vec2::new(0.5, 0.0),
+            Vec2::new(0.5, 1.0),
+            [ColorStop::new(0.0, from), ColorStop::new(1.0, to)],
+        )
+    }
+
+    /// Stops sorted by offset.
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    /// Position along the gradient (`0..=1`) of a point in shape-relative coordinates.
+    pub fn position_at(&self, p: Vec2) -> f32 {
+        let t = match self.kind {
+            GradientKind::Linear { start, end } => {
+                let axis = end - start;
+                ((p - start) * axis) / (axis * axis).max(1e-12)
+            }
+            GradientKind::Radial { center, radius } => (p - center).length() / radius.max(1e-6),
+            GradientKind::Conic { center, angle } => {
+                let d = p - center;
+                (d.y.atan2(d.x) - angle).rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU
+            }
+        };
+        t.clamp(0.0, 1.0)
+    }
+
+    /// Linear RGBA color of the gradient at `t` (`0..=1`).
+    pub fn color_at(&self, t: f32) -> [f32; 4] {
+        let Some(first) = self.stops.first() else {
+            return [0.0; 4];
+        };
+        let mut color = first.color.to_linear_rgba();
+        for pair in self.stops.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if t <= a.offset {
+                break;
+            }
+            let f = ((t - a.offset) / (b.offset - a.offset).max(1e-6)).clamp(0.0, 1.0);
+            let (ca, cb) = (a.color.to_linear_rgba(), b.color.to_linear_rgba());
+            color = std::array::from_fn(|i| ca[i] + (cb[i] - ca[i]) * f);
+        }
+        color
+    }
+
+    /// Linear RGBA color at a point in shape-relative coordinates.
+    pub fn sample(&self, p: Vec2) -> [f32; 4] {
+        self.color_at(self.position_at(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorStop, Gradient};
+    use crate::math::{Color, Vec2};
+
+    #[test]
+    fn gradients_interpolate_between_sorted_stops() {
+        let gradient = Gradient::horizontal(Color::BLACK, Color::WHITE);
+        assert_eq!(gradient.sample(Vec2::new(-1.0, 0.0)), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(gradient.sample(Vec2::new(0.5, 0.9)), [0.5, 0.5, 0.5, 1.0]);
+
+        let stops = [
+            ColorStop::new(1.0, Color::BLUE),
+            ColorStop::new(0.0, Color::RED),
+            ColorStop::new(0.5, Color::RED),
+        ];
+        let radial = Gradient::radial(Vec2::new(0.5, 0.5), 0.5, stops);
+        assert_eq!(radial.stops()[2].color, Color::BLUE);
+        assert_eq!(
+            radial.sample(Vec2::new(0.7, 0.5)),
+            Color::RED.to_linear_rgba()
+        );
+        assert_eq!(
+            radial.sample(Vec2::new(1.0, 0.5)),
+            Color::BLUE.to_linear_rgba()
+        );
+
+        // Clockwise from pointing right: straight down is a quarter turn.
+        let conic = Gradient::conic(Vec2::new(0.5, 0.5), 0.0, [ColorStop::new(0.0, Color::RED)]);
+        assert!((conic.position_at(Vec2::new(0.5, 1.0)) - 0.25).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,369 @@
+use crate::core::assets::ImageId;
+use crate::core::id::Id;
+use crate::math::Color;
+use crate::render::renderer::{RenderError, RenderResult};
+use wgpu::naga;
+
+/// Marker type for custom shader materials.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct MaterialMarker;
+
+/// Identifier of a material registered with `Materials::create`.
+pub type MaterialId = Id<MaterialMarker>;
+
+/// Number of extra textures a material can sample (`material_texture0..3`).
+pub const MAX_MATERIAL_TEXTURES: usize = 4;
+
+/// What a material is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaterialKind {
+    /// Textured quads: `SpriteDrawData::material`.
+    Sprite,
+    /// Shape triangles: `RenderContext::set_material`.
+    Shape,
+}
+
+/// Values exposed to a material shader through the `material` uniform.
+///
+/// They are per material, not per draw: the last values set during a frame apply to
+/// every draw using the material in that frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaterialParams {
+    /// Seconds, typically `EngineState::total_time` (`material.time`).
+    pub time: f32,
+    /// Colors, uploaded in linear space (`material.colors[i]`).
+    pub colors: [Color; 4],
+    /// Free parameters, packed four per vector (`material.floats[i / 4][i % 4]`).
+    pub floats: [f32; 16],
+    /// Extra textures (`material_texture0..3`). Unset or unknown slots sample white.
+    pub textures: [Option<ImageId>; MAX_MATERIAL_TEXTURES],
+}
+
+impl Default for MaterialParams {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            colors: [Color::WHITE; 4],
+            floats: [0.0; 16],
+            textures: [None; MAX_MATERIAL_TEXTURES],
+        }
+    }
+}
+
+impl MaterialParams {
+    /// Builder: set the time value.
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Builder: set color `index`.
+    pub fn with_color(mut self, index: usize, color: Color) -> Self {
+        self.colors[index] = color;
+        self
+    }
+
+    /// Builder: set float `index`.
+    pub fn with_float(mut self, index: usize, value: f32) -> Self {
+        self.floats[index] = value;
+        self
+    }
+
+    /// Builder: bind an extra texture to `slot`.
+    pub fn with_texture(mut self, slot: usize, image: ImageId) -> Self {
+        self.textures[slot] = Some(image);
+        self
+    }
+
+    /// Uniform buffer contents matching the `MaterialParams` WGSL struct.
+    pub(crate) fn uniform_bytes(&self) -> [f32; 36] {
+        let mut data = [0.0; 36];
+        for (i, color) in self.colors.iter().enumerate() {
+            data[i * 4..i * 4 + 4].copy_from_slice(&color.to_linear_rgba());
+        }
+        data[16..32].copy_from_slice(&self.floats);
+        data[32] = self.time;
+        data
+    }
+}
+
+/// Source and settings of a custom material.
+///
+/// `source` is WGSL appended to a prelude that declares the bindings below. It must
+/// define `@fragment fn fs_main(in: MaterialVsOut) -> @location(0) vec4<f32>` and may
+/// define `@vertex fn vs_main(...) -> MaterialVsOut` to replace the default vertex stage.
+///
+/// ```wgsl
+/// struct MaterialVsOut {
+///     @builtin(position) position: vec4<f32>,
+///     @location(0) uv: vec2<f32>,      // sprite UV; screen UV for shapes
+///     @location(1) color: vec4<f32>,   // linear tint / vertex color
+/// };
+/// struct MaterialParams {
+///     colors: array<vec4<f32>, 4>,
+///     floats: array<vec4<f32>, 4>,
+///     time: f32,
+/// };
+/// @group(1) @binding(0) var<uniform> material: MaterialParams;
+/// @group(1) @binding(1) var material_sampler: sampler;
+/// @group(1) @binding(2..5) var material_texture0..3: texture_2d<f32>;
+/// // Sprite materials only:
+/// @group(0) @binding(0) var sprite_texture: texture_2d<f32>;
+/// @group(0) @binding(1) var sprite_sampler: sampler;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaterialDescriptor {
+    pub label: String,
+    pub kind: MaterialKind,
+    pub source: String,
+    pub params: MaterialParams,
+}
+
+impl MaterialDescriptor {
+    pub fn sprite(label: impl Into<String>, source: impl Into<String>) -> Self {
+        Self::new(label, MaterialKind::Sprite, source)
+    }
+
+    pub fn shape(label: impl Into<String>, source: impl Into<String>) -> Self {
+        Self::new(label, MaterialKind::Shape, source)
+    }
+
+    fn new(label: impl Into<String>, kind: MaterialKind, source: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            kind,
+            source: source.into(),
+            params: MaterialParams::default(),
+        }
+    }
+
+    /// Builder: set the initial parameters.
+    pub fn with_params(mut self, params: MaterialParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Full WGSL module: prelude followed by the user source.
+    pub fn module_source(&self) -> String {
+        let prelude = match self.kind {
+            MaterialKind::Sprite => SPRITE_PRELUDE,
+            MaterialKind::Shape => SHAPE_PRELUDE,
+        };
+        format!("{COMMON_PRELUDE}{prelude}\n{}\n", self.source)
+    }
+
+    /// Vertex entry point: the user's `vs_main` if defined, else the default one.
+    pub fn vertex_entry_point(&self, module: &naga::Module) -> &'static str {
+        let has_custom_vertex = module
+            .entry_points
+            .iter()
+            .any(|ep| ep.name == "vs_main" && ep.stage == naga::ShaderStage::Vertex);
+        match (has_custom_vertex, self.kind) {
+            (true, _) => "vs_main",
+            (false, MaterialKind::Sprite) => "material_sprite_vs",
+            (false, MaterialKind::Shape) => "material_shape_vs",
+        }
+    }
+
+    /// Parse and validate the shader, reporting errors as `RenderError::ShaderCompilation`.
+    pub fn validate(&self) -> RenderResult<naga::Module> {
+        validate_fragment_module(&format!("material '{}'", self.label), &self.module_source())
+    }
+}
+
+/// Parse and validate a full WGSL module that must define `@fragment fn fs_main`.
+/// `what` names the shader in error messages (e.g. `material 'water'`).
+pub(crate) fn validate_fragment_module(what: &str, source: &str) -> RenderResult<naga::Module> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| {
+        RenderError::ShaderCompilation(format!("{}: {}", what, e.emit_to_string(source)))
+    })?;
+
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .map_err(|e| {
+        RenderError::ShaderCompilation(format!("{}: {}", what, e.emit_to_string(source)))
+    })?;
+
+    let has_fragment = module
+        .entry_points
+        .iter()
+        .any(|ep| ep.name == "fs_main" && ep.stage == naga::ShaderStage::Fragment);
+    if !has_fragment {
+        return Err(RenderError::ShaderCompilation(format!(
+            "{}: missing `@fragment fn fs_main`",
+            what
+        )));
+    }
+
+    Ok(module)
+}
+
+/// Materials registered by the application.
+///
+/// Materials are validated on creation and compiled by the renderer once it is ready.
+#[derive(Default)]
+pub struct Materials {
+    materials: Vec<(MaterialId, MaterialDescriptor)>,
+    pending: Vec<MaterialId>,
+}
+
+impl Materials {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a material. Returns `RenderError::ShaderCompilation` if the WGSL does
+    /// not parse or validate.
+    pub fn create(&mut self, descriptor: MaterialDescriptor) -> RenderResult<MaterialId> {
+        descriptor.validate()?;
+
+        let id = MaterialId::new();
+        self.materials.push((id, descriptor));
+        self.pending.push(id);
+        Ok(id)
+    }
+
+    pub fn get(&self, id: MaterialId) -> Option<&MaterialDescriptor> {
+        self.materials
+            .iter()
+            .find(|(material, _)| *material == id)
+            .map(|(_, descriptor)| descriptor)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (MaterialId, &MaterialDescriptor)> {
+        self.materials
+            .iter()
+            .map(|(id, descriptor)| (*id, descriptor))
+    }
+
+    /// Materials created since the last call, to be compiled by the renderer.
+    pub(crate) fn take_pending(&mut self) -> Vec<MaterialId> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Queue every material for compilation again, after the renderer lost them.
+    pub(crate) fn requeue_all(&mut self) {
+        self.pending = self.materials.iter().map(|(id, _)| *id).collect();
+    }
+}
+
+const COMMON_PRELUDE: &str = r#"
+struct MaterialVsOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct MaterialParams {
+    colors: array<vec4<f32>, 4>,
+    floats: array<vec4<f32>, 4>,
+    time: f32,
+};
+
+@group(1) @binding(0) var<uniform> material: MaterialParams;
+@group(1) @binding(1) var material_sampler: sampler;
+@group(1) @binding(2) var material_texture0: texture_2d<f32>;
+@group(1) @binding(3) var material_texture1: texture_2d<f32>;
+@group(1) @binding(4) var material_texture2: texture_2d<f32>;
+@group(1) @binding(5) var material_texture3: texture_2d<f32>;
+"#;
+
+const SPRITE_PRELUDE: &str = r#"
+@group(0) @binding(0) var sprite_texture: texture_2d<f32>;
+@group(0) @binding(1) var sprite_sampler: sampler;
+
+struct SpriteInstance {
+    @location(0) tl: vec2<f32>,
+    @location(1) tr: vec2<f32>,
+    @location(2) br: vec2<f32>,
+    @location(3) bl: vec2<f32>,
+    @location(4) uv_min: vec2<f32>,
+    @location(5) uv_max: vec2<f32>,
+    @location(6) color: vec4<f32>,
+};
+
+@vertex
+fn material_sprite_vs(@builtin(vertex_index) index: u32, inst: SpriteInstance) -> MaterialVsOut {
+    var pos: vec2<f32>;
+    var uv: vec2<f32>;
+    switch index {
+        case 0u, 3u: {
+            pos = inst.tl;
+            uv = inst.uv_min;
+        }
+        case 1u: {
+            pos = inst.tr;
+            uv = vec2<f32>(inst.uv_max.x, inst.uv_min.y);
+        }
+        case 2u, 4u: {
+            pos = inst.br;
+            uv = inst.uv_max;
+        }
+        default: {
+            pos = inst.bl;
+            uv = vec2<f32>(inst.uv_min.x, inst.uv_max.y);
+        }
+    }
+
+    var out: MaterialVsOut;
+    out.position = vec4<f32>(pos, 0.0, 1.0);
+    out.uv = uv;
+    out.color = inst.color;
+    return out;
+}
+"#;
+
+const SHAPE_PRELUDE: &str = r#"
+struct ShapeVertex {
+    @location(0) pos: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn material_shape_vs(v: ShapeVertex) -> MaterialVsOut {
+    var out: MaterialVsOut;
+    out.position = vec4<f32>(v.pos, 0.0, 1.0);
+    out.uv = vec2<f32>(v.pos.x * 0.5 + 0.5, 0.5 - v.pos.y * 0.5);
+    out.color = v.color;
+    return out;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::{MaterialDescriptor, Materials};
+    use crate::render::RenderError;
+
+    #[test]
+    fn create_validates_wgsl() {
+        let mut materials = Materials::new();
+        let ok = MaterialDescriptor::sprite(
+            "pulse",
+            r#"
+            @fragment
+            fn fs_main(in: MaterialVsOut) -> @location(0) vec4<f32> {
+                let base = textureSample(sprite_texture, sprite_sampler, in.uv) * in.color;
+                return base * (0.5 + 0.5 * sin(material.time));
+            }
+            "#,
+        );
+        let id = materials.create(ok).unwrap();
+        assert!(materials.get(id).is_some());
+        assert_eq!(materials.take_pending(), vec![id]);
+
+        let broken = MaterialDescriptor::shape("broken", "fn fs_main( {");
+        assert!(matches!(
+            materials.create(broken),
+            Err(RenderError::ShaderCompilation(_))
+        ));
+
+        let no_entry = MaterialDescriptor::shape("no entry", "fn helper() {}");
+        assert!(matches!(
+            materials.create(no_entry),
+            Err(RenderError::ShaderCompilation(_))
+        ));
+    }
+}
@@ -1,18 +1,56 @@
+pub mod atlas;
+pub mod blend;
+pub mod camera;
+pub mod canvas;
+pub mod clip;
 pub mod context;
+pub mod draw_list;
+pub mod gradient;
+pub mod material;
+pub mod post;
 pub mod renderer;
+pub mod scaling;
 pub mod shapes;
+pub mod software_renderer;
 pub mod sprite_data;
+pub mod stats;
 pub mod vertex;
 pub mod wgpu_renderer;
 
+#[allow(unused_imports)]
+pub use atlas::{AtlasConfig, AtlasPageStats};
+#[allow(unused_imports)]
+pub use blend::BlendMode;
+#[allow(unused_imports)]
+pub use camera::{Camera2D, Viewport};
+#[allow(unused_imports)]
+pub use canvas::Canvas;
+#[allow(unused_imports)]
+pub use clip::{ClipRect, Mask};
 #[allow(unused_imports)]
 pub use context::RenderContext;
 #[allow(unused_imports)]
+pub use draw_list::{DrawCommand, DrawCommandKind, DrawList, GradientDraw, MeshDraw};
+#[allow(unused_imports)]
+pub use gradient::{ColorStop, Gradient, GradientKind};
+#[allow(unused_imports)]
+pub use material::{MaterialDescriptor, MaterialId, MaterialKind, MaterialParams, Materials};
+#[allow(unused_imports)]
+pub use post::{PostEffect, PostEntry, PostShader, PostStack};
+#[allow(unused_imports)]
 pub use renderer::{RenderError, RenderResult, Renderer};
 #[allow(unused_imports)]
+pub use scaling::{ScalePolicy, ScreenScale};
+#[allow(unused_imports)]
 pub use shapes::{
-    Circle, Collider, Drawable, Ellipse, Line, Polyline, Rectangle, Transform2d, Triangle,
+    Circle, Collider, Drawable, Ellipse, FillRule, Line, LineCap, LineJoin, Mesh2d, MeshVertex,
+    Path, Polyline, Rectangle, RoundedRect, StrokeStyle, Transform2d, Triangle,
 };
+#[allow(unused_imports)]
+pub use software_renderer::SoftwareRenderer;
 pub use sprite_data::SpriteDrawData;
-pub use vertex::Vertex;
-pub use wgpu_renderer::WgpuRenderer;
+#[allow(unused_imports)]
+pub use stats::{PassTiming, RenderStats};
+pub use vertex::{GradientVertex, SdfVertex, TexturedVertex, Vertex};
+#[allow(unused_imports)]
+pub use wgpu_renderer::{HeadlessConfig, WgpuRenderer};
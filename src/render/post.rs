@@ -0,0 +1,332 @@
+use crate::core::assets::ImageId;
+use crate::render::material::validate_fragment_module;
+use crate::render::renderer::RenderResult;
+
+/// Full-screen effect applied to the frame after the main pass.
+///
+/// Effects run in `PostStack` order, each reading the previous one's output.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostEffect {
+    /// Blurs pixels brighter than `threshold` (luminance, linear) and adds them back.
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        /// Blur radius in pixels.
+        radius: f32,
+    },
+    /// Darkens the corners. `radius` is where darkening starts, as a fraction of the
+    /// center-to-corner distance, and `softness` how far it takes to reach `intensity`.
+    Vignette {
+        intensity: f32,
+        radius: f32,
+        softness: f32,
+    },
+    /// Remaps colors through a lookup table image.
+    ///
+    /// The LUT is an `N*N x N` strip of `N` slices, blue increasing from slice to slice,
+    /// red along x and green along y within a slice (the common 256x16 / 1024x32 layout).
+    ColorGrading { lut: ImageId, strength: f32 },
+    /// Curved screen with darkened odd pixel rows.
+    Crt {
+        scanline_intensity: f32,
+        curvature: f32,
+    },
+    /// Splits red and blue outwards from the center by up to `offset` pixels.
+    ChromaticAberration { offset: f32 },
+    /// Separable gaussian blur with a radius in pixels.
+    Blur { radius: f32 },
+    /// User WGSL pass.
+    Custom(PostShader),
+}
+
+impl PostEffect {
+    pub fn bloom() -> Self {
+        PostEffect::Bloom {
+            threshold: 0.8,
+            intensity: 1.0,
+            radius: 8.0,
+        }
+    }
+
+    pub fn vignette() -> Self {
+        PostEffect::Vignette {
+            intensity: 0.5,
+            radius: 0.5,
+            softness: 0.5,
+        }
+    }
+
+    pub fn color_grading(lut: ImageId) -> Self {
+        PostEffect::ColorGrading { lut, strength: 1.0 }
+    }
+
+    pub fn crt() -> Self {
+        PostEffect::Crt {
+            scanline_intensity: 0.3,
+            curvature: 0.05,
+        }
+    }
+
+    pub fn chromatic_aberration(offset: f32) -> Self {
+        PostEffect::ChromaticAberration { offset }
+    }
+
+    pub fn blur(radius: f32) -> Self {
+        PostEffect::Blur { radius }
+    }
+
+    /// User pass; see `PostShader::new`.
+    pub fn custom(label: impl Into<String>, source: impl Into<String>) -> RenderResult<Self> {
+        PostShader::new(label, source).map(PostEffect::Custom)
+    }
+}
+
+/// WGSL for a custom post-processing pass.
+///
+/// `source` is appended to a prelude declaring the bindings below and must define
+/// `@fragment fn fs_main(in: PostVsOut) -> @location(0) vec4<f32>`.
+///
+/// ```wgsl
+/// struct PostVsOut {
+///     @builtin(position) position: vec4<f32>,
+///     @location(0) uv: vec2<f32>,
+/// };
+/// struct PostParams {
+///     floats: array<vec4<f32>, 4>,   // PostShader::floats
+///     resolution: vec2<f32>,         // target size in pixels
+/// };
+/// @group(0) @binding(0) var<uniform> post: PostParams;
+/// @group(0) @binding(1) var post_sampler: sampler;      // linear, clamp to edge
+/// @group(0) @binding(2) var source_texture: texture_2d<f32>;
+/// @group(0) @binding(3) var original_texture: texture_2d<f32>; // same as source here
+/// @group(0) @binding(4) var post_texture: texture_2d<f32>;     // PostShader::texture
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostShader {
+    pub label: String,
+    pub source: String,
+    /// Free parameters (`post.floats[i / 4][i % 4]`).
+    pub floats: [f32; 16],
+    /// Extra texture; samples white when unset.
+    pub texture: Option<ImageId>,
+}
+
+impl PostShader {
+    /// Returns `RenderError::ShaderCompilation` if the WGSL does not parse or validate.
+    pub fn new(label: impl Into<String>, source: impl Into<String>) -> RenderResult<Self> {
+        let shader = Self {
+            label: label.into(),
+            source: source.into(),
+            floats: [0.0; 16],
+            texture: None,
+        };
+        validate_fragment_module(
+            &format!("post effect '{}'", shader.label),
+            &shader.module_source(),
+        )?;
+        Ok(shader)
+    }
+
+    /// Builder: set float `index`.
+    pub fn with_float(mut self, index: usize, value: f32) -> Self {
+        self.floats[index] = value;
+        self
+    }
+
+    /// Builder: set the extra texture.
+    pub fn with_texture(mut self, image: ImageId) -> Self {
+        self.texture = Some(image);
+        self
+    }
+
+    /// Full WGSL module: prelude followed by the user source.
+    pub fn module_source(&self) -> String {
+        post_module_source(&self.source)
+    }
+}
+
+/// Prelude shared by built-in and custom post-processing shaders.
+pub(crate) fn post_module_source(source: &str) -> String {
+    format!("{POST_PRELUDE}\n{source}\n")
+}
+
+const POST_PRELUDE: &str = r#"
+struct PostVsOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct PostParams {
+    floats: array<vec4<f32>, 4>,
+    resolution: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> post: PostParams;
+@group(0) @binding(1) var post_sampler: sampler;
+@group(0) @binding(2) var source_texture: texture_2d<f32>;
+@group(0) @binding(3) var original_texture: texture_2d<f32>;
+@group(0) @binding(4) var post_texture: texture_2d<f32>;
+
+@vertex
+fn post_vs(@builtin(vertex_index) index: u32) -> PostVsOut {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: PostVsOut;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+"#;
+
+/// Named effect in a `PostStack`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostEntry {
+    pub name: String,
+    pub effect: PostEffect,
+    pub enabled: bool,
+}
+
+/// Ordered post-processing effects applied to every frame.
+///
+/// Entries are addressed by name so game code can toggle, reorder and tweak them at
+/// runtime. Changes reach the renderer at the next frame.
+#[derive(Debug, Clone, Default)]
+pub struct PostStack {
+    entries: Vec<PostEntry>,
+    changed: bool,
+}
+
+impl PostStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an enabled effect, replacing any entry with the same name.
+    pub fn push(&mut self, name: impl Into<String>, effect: PostEffect) {
+        let name = name.into();
+        self.remove(&name);
+        self.entries.push(PostEntry {
+            name,
+            effect,
+            enabled: true,
+        });
+        self.changed = true;
+    }
+
+    /// Insert an enabled effect at `index` (clamped), replacing any entry with the same
+    /// name.
+    pub fn insert(&mut self, index: usize, name: impl Into<String>, effect: PostEffect) {
+        let name = name.into();
+        self.remove(&name);
+        let index = index.min(self.entries.len());
+        self.entries.insert(
+            index,
+            PostEntry {
+                name,
+                effect,
+                enabled: true,
+            },
+        );
+        self.changed = true;
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<PostEffect> {
+        let index = self.position(name)?;
+        self.changed = true;
+        Some(self.entries.remove(index).effect)
+    }
+
+    /// Returns `false` if there is no effect with that name.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let Some(index) = self.position(name) else {
+            return false;
+        };
+        self.entries[index].enabled = enabled;
+        self.changed = true;
+        true
+    }
+
+    /// Move an effect to position `index` (clamped). Returns `false` if there is no
+    /// effect with that name.
+    pub fn move_to(&mut self, name: &str, index: usize) -> bool {
+        let Some(from) = self.position(name) else {
+            return false;
+        };
+        let entry = self.entries.remove(from);
+        let index = index.min(self.entries.len());
+        self.entries.insert(index, entry);
+        self.changed = true;
+        true
+    }
+
+    /// Mutable access to an effect's parameters.
+    pub fn effect_mut(&mut self, name: &str) -> Option<&mut PostEffect> {
+        let index = self.position(name)?;
+        self.changed = true;
+        Some(&mut self.entries[index].effect)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.changed = true;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PostEntry> {
+        self.entries.iter()
+    }
+
+    /// Enabled effects in order, as passed to `Renderer::set_post_effects`.
+    pub fn enabled_effects(&self) -> Vec<PostEffect> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.effect.clone())
+            .collect()
+    }
+
+    /// Whether the stack changed since the last call.
+    pub(crate) fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Send the stack to the renderer again, after it lost its effects.
+    pub(crate) fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PostEffect, PostStack};
+    use crate::render::RenderError;
+
+    #[test]
+    fn stack_orders_and_toggles_by_name() {
+        let mut stack = PostStack::new();
+        stack.push("blur", PostEffect::blur(2.0));
+        stack.push("vignette", PostEffect::vignette());
+        stack.insert(0, "crt", PostEffect::crt());
+        assert!(stack.take_changed());
+        assert!(!stack.take_changed());
+
+        assert!(stack.move_to("crt", 10));
+        assert!(stack.set_enabled("blur", false));
+        assert!(!stack.set_enabled("bloom", false));
+        assert_eq!(
+            stack.enabled_effects(),
+            vec![PostEffect::vignette(), PostEffect::crt()]
+        );
+        if let Some(PostEffect::Vignette { intensity, .. }) = stack.effect_mut("vignette") {
+            *intensity = 1.0;
+        }
+        assert!(stack.take_changed());
+
+        assert!(matches!(
+            PostEffect::custom("broken", "fn fs_main( {"),
+            Err(RenderError::ShaderCompilation(_))
+        ));
+    }
+}
@@ -1,7 +1,17 @@
 use crate::backend::surface_provider::SurfaceProvider;
 use crate::backend::window::WindowConfig;
-use crate::core::assets::ImageId;
-use crate::render::{SpriteDrawData, Vertex};
+use crate::core::assets::{ImageAsset, ImageId, SamplerOptions, check_rgba8};
+use crate::math::vec2::Vec2;
+use crate::render::atlas::AtlasPageStats;
+use crate::render::blend::BlendMode;
+use crate::render::clip::ClipRect;
+use crate::render::draw_list::{DrawCommandKind, DrawList};
+use crate::render::gradient::Gradient;
+use crate::render::material::{MaterialDescriptor, MaterialId, MaterialParams};
+use crate::render::post::PostEffect;
+use crate::render::shapes::tessellate_sdf_shape;
+use crate::render::stats::RenderStats;
+use crate::render::{GradientVertex, SdfVertex, SpriteDrawData, TexturedVertex, Vertex};
 use thiserror::Error;
 
 pub type RenderResult<T> = Result<T, RenderError>;
@@ -33,6 +43,28 @@ pub enum RenderError {
     PipelineSetup(String),
 }
 
+/// Check that `data` holds the RGBA8 pixels of a `(x, y, width, height)` rect lying
+/// inside an image of `image_size`.
+pub(crate) fn validate_region(
+    image_size: (u32, u32),
+    rect: (u32, u32, u32, u32),
+    data: &[u8],
+) -> RenderResult<()> {
+    let (x, y, width, height) = rect;
+    check_rgba8(width, height, data).map_err(RenderError::InvalidTexture)?;
+    if width > image_size.0
+        || height > image_size.1
+        || x > image_size.0 - width
+        || y > image_size.1 - height
+    {
+        return Err(RenderError::InvalidTexture(format!(
+            "region {}x{} at ({}, {}) is outside the {}x{} image",
+            width, height, x, y, image_size.0, image_size.1
+        )));
+    }
+    Ok(())
+}
+
 pub trait Renderer {
     fn init(
         &mut self,
@@ -41,9 +73,53 @@ pub trait Renderer {
     ) -> RenderResult<()>;
     fn resize(&mut self, new_size: (u32, u32));
     fn present(&mut self) -> RenderResult<()>;
+
+    /// Start over after `present` failed with `RenderError::DeviceLost`: re-create the
+    /// device and surface and rebuild the pipelines. Every image, material, canvas and
+    /// post effect is dropped and has to be uploaded again, even if this fails. A
+    /// `RenderError::SurfaceError` only needs `resize` to configure the surface again.
+    /// Renderers that cannot lose their device have nothing to do.
+    fn recover(&mut self) -> RenderResult<()> {
+        Ok(())
+    }
     fn set_clear_color(&mut self, rgba: [f32; 4]);
     fn submit(&mut self, _vertices: &[Vertex]) {}
 
+    /// Queue shape vertices drawn with an optional shape material and a blend mode.
+    /// Renderers without material or blend mode support draw them like `submit`.
+    fn submit_shapes(
+        &mut self,
+        vertices: &[Vertex],
+        _material: Option<MaterialId>,
+        _blend: BlendMode,
+    ) {
+        self.submit(vertices);
+    }
+
+    /// Clip subsequent draws to `rect` (target pixels), or stop clipping with `None`.
+    fn set_clip_rect(&mut self, _rect: Option<ClipRect>) {}
+
+    /// Only draw subsequent draws where every mask in `masks` covers the target.
+    /// Each mask is a list of triangles in NDC; an empty slice removes the mask.
+    fn set_stencil_masks(&mut self, _masks: &[&[Vertex]]) {}
+
+    /// Compile a material registered in `Materials`.
+    ///
+    /// Shader errors are reported as `RenderError::ShaderCompilation`. Renderers without
+    /// material support keep drawing affected shapes and sprites with default shaders.
+    fn create_material(
+        &mut self,
+        _id: MaterialId,
+        _descriptor: &MaterialDescriptor,
+    ) -> RenderResult<()> {
+        Err(RenderError::PipelineSetup(
+            "this renderer does not support custom materials".to_string(),
+        ))
+    }
+
+    /// Update a material's uniforms and extra textures.
+    fn set_material_params(&mut self, _id: MaterialId, _params: &MaterialParams) {}
+
     /// Upload an RGBA8 image as a GPU texture associated with the given id.
     fn upload_image(
         &mut self,
@@ -55,6 +131,219 @@ pub trait Renderer {
         Ok(())
     }
 
+    /// Upload an RGBA8 image sampled with the given options (filtering, address mode,
+    /// mipmaps). Uploading an existing id replaces its texture.
+    /// Renderers without sampler support fall back to `upload_image`.
+    fn upload_image_with_sampler(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        _sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        self.upload_image(id, width, height, data)
+    }
+
+    /// Replace the whole contents of an uploaded image, possibly with a new size or
+    /// sampler. Textures of the same size and sampler are updated in place.
+    fn replace_image(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        self.upload_image_with_sampler(id, width, height, data, sampler)
+    }
+
+    /// Overwrite the `width` x `height` rect at (`x`, `y`) of an uploaded image with
+    /// RGBA8 `data`. Only the base level is updated; mipmapped images should be
+    /// replaced instead.
+    fn update_image_region(
+        &mut self,
+        _id: ImageId,
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+        _data: &[u8],
+    ) -> RenderResult<()> {
+        Err(RenderError::InvalidTexture(
+            "this renderer does not support partial texture updates".to_string(),
+        ))
+    }
+
+    /// Free the texture of an image. Sprites still using it are skipped.
+    fn unload_image(&mut self, _id: ImageId) {}
+
+    /// Create an offscreen canvas of the given size that sprites can sample as `id`.
+    /// Calling it again with the same size is a no-op; a new size recreates it.
+    fn create_canvas(
+        &mut self,
+        _id: ImageId,
+        _width: u32,
+        _height: u32,
+        _sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        Err(RenderError::RenderFailed(
+            "this renderer does not support canvases".to_string(),
+        ))
+    }
+
+    /// Render `list` into a canvas created with `create_canvas`.
+    ///
+    /// The canvas is cleared to `clear_color` first, or keeps its previous contents when
+    /// `None`. Queued frame draws are not affected.
+    fn render_to_canvas(
+        &mut self,
+        _id: ImageId,
+        _clear_color: Option<[f32; 4]>,
+        _list: &DrawList<'_>,
+    ) -> RenderResult<()> {
+        Err(RenderError::RenderFailed(
+            "this renderer does not support canvases".to_string(),
+        ))
+    }
+
+    /// Replace the full-screen effects applied to presented frames, in order.
+    ///
+    /// Shader errors in custom passes are reported as `RenderError::ShaderCompilation`.
+    fn set_post_effects(&mut self, _effects: &[PostEffect]) -> RenderResult<()> {
+        Err(RenderError::PipelineSetup(
+            "this renderer does not support post-processing".to_string(),
+        ))
+    }
+
+    /// Keep a copy of the next presented frame, returned by `take_captured_frame`.
+    fn capture_next_frame(&mut self) {}
+
+    /// The frame captured by the last `present` after `capture_next_frame`, as sRGB
+    /// RGBA8 rows. `None` if nothing was captured or the renderer cannot read back.
+    fn take_captured_frame(&mut self) -> Option<ImageAsset> {
+        None
+    }
+
+    /// Occupancy of the pages small images are packed into. Empty for renderers that
+    /// give every image its own texture.
+    fn atlas_stats(&self) -> Vec<AtlasPageStats> {
+        Vec::new()
+    }
+
+    /// Counters of the last presented frame. All zero for renderers that do not
+    /// track them.
+    fn render_stats(&self) -> RenderStats {
+        RenderStats::default()
+    }
+
+    /// Time each render pass on the GPU, reported in `RenderStats::gpu_passes`.
+    /// Ignored when the adapter has no timestamp queries. Reading timings back waits
+    /// for the GPU at the end of every frame, so keep it for profiling.
+    fn set_gpu_timing(&mut self, _enabled: bool) {}
+
     /// Draw a list of sprites for the current frame.
     fn draw_sprites(&mut self, _sprites: &[SpriteDrawData], _viewport_size: (u32, u32)) {}
+
+    /// Draw a textured mesh: `vertices` in NDC and `indices` listing whole triangles.
+    /// Untextured meshes (`texture` is `None`) are drawn with their vertex colors.
+    fn draw_mesh(
+        &mut self,
+        _vertices: &[TexturedVertex],
+        _indices: &[u32],
+        _texture: Option<ImageId>,
+        _blend: BlendMode,
+    ) {
+    }
+
+    /// Fill triangles (`vertices` in NDC, three per triangle) with `gradient`.
+    /// Renderers without per-pixel gradients draw them as shapes colored at each vertex.
+    fn fill_gradient(
+        &mut self,
+        vertices: &[GradientVertex],
+        gradient: &Gradient,
+        blend: BlendMode,
+    ) {
+        let vertices: Vec<Vertex> = vertices
+            .iter()
+            .map(|v| Vertex {
+                pos: v.pos,
+                color: gradient.sample(Vec2::new(v.local[0], v.local[1])),
+            })
+            .collect();
+        self.submit_shapes(&vertices, None, blend);
+    }
+
+    /// Draw shapes from their distance field (`vertices` in NDC, six per shape covering
+    /// it). Renderers without per-pixel shading draw them as tessellated shapes.
+    fn draw_sdf_shapes(&mut self, vertices: &[SdfVertex], blend: BlendMode) {
+        let vertices: Vec<Vertex> = vertices
+            .chunks_exact(6)
+            .flat_map(tessellate_sdf_shape)
+            .collect();
+        self.submit_shapes(&vertices, None, blend);
+    }
+
+    /// Submit a frame's draw list.
+    ///
+    /// Commands are forwarded in order to `submit_shapes` / `draw_sprites` / `draw_mesh` /
+    /// `fill_gradient` / `draw_sdf_shapes`; renderers must draw those calls in the order
+    /// they were made so every kind of draw interleaves correctly.
+    ///
+    /// Clip rects and masks are set with `set_clip_rect` / `set_stencil_masks` whenever
+    /// they change, and removed again at the end of the list.
+    fn submit_draw_list(&mut self, list: &DrawList<'_>, viewport_size: (u32, u32)) {
+        let (mut clip, mut mask) = (None, None);
+        for command in &list.commands {
+            if command.clip.is_some_and(|rect| rect.is_empty()) {
+                continue;
+            }
+            if command.clip != clip {
+                clip = command.clip;
+                self.set_clip_rect(clip);
+            }
+            if command.mask != mask {
+                mask = command.mask;
+                self.set_stencil_masks(&list.mask_chain(mask));
+            }
+            match &command.kind {
+                DrawCommandKind::Shapes(range) => self.submit_shapes(
+                    &list.vertices[range.clone()],
+                    command.material,
+                    command.blend,
+                ),
+                DrawCommandKind::Sprites(range) => {
+                    self.draw_sprites(&list.sprites[range.clone()], viewport_size)
+                }
+                DrawCommandKind::Meshes(range) => {
+                    for mesh in &list.meshes[range.clone()] {
+                        self.draw_mesh(
+                            &list.mesh_vertices[mesh.vertices.clone()],
+                            &list.mesh_indices[mesh.indices.clone()],
+                            mesh.texture,
+                            command.blend,
+                        );
+                    }
+                }
+                DrawCommandKind::Gradients(range) => {
+                    for fill in &list.gradients[range.clone()] {
+                        self.fill_gradient(
+                            &list.gradient_vertices[fill.vertices.clone()],
+                            &fill.gradient,
+                            command.blend,
+                        );
+                    }
+                }
+                DrawCommandKind::SdfShapes(range) => {
+                    self.draw_sdf_shapes(&list.sdf_vertices[range.clone()], command.blend)
+                }
+            }
+        }
+        if clip.is_some() {
+            self.set_clip_rect(None);
+        }
+        if mask.is_some() {
+            self.set_stencil_masks(&[]);
+        }
+    }
 }
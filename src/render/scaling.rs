@@ -0,0 +1,183 @@
+use crate::math::vec2::Vec2;
+use crate::render::camera::Viewport;
+use crate::render::clip::ClipRect;
+
+/// Units game code draws and receives input in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalePolicy {
+    /// Logical pixels: the physical size divided by the window's scale factor, so
+    /// things keep the same apparent size on HiDPI displays.
+    #[default]
+    Logical,
+    /// Physical pixels of the window, ignoring the scale factor.
+    Physical,
+    /// A fixed virtual resolution, scaled uniformly to fit the window and centered.
+    /// The uncovered part of the window (letterbox bars) keeps the clear color.
+    Virtual { width: u32, height: u32 },
+}
+
+/// Mapping from game units to the window's physical pixels, for a scale policy, a
+/// window size and a scale factor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenScale {
+    pub policy: ScalePolicy,
+    /// Window size in physical pixels.
+    pub physical_size: (u32, u32),
+    /// Scale factor reported by the window system (2.0 on most HiDPI displays).
+    pub scale_factor: f64,
+    /// Size of the screen in game units.
+    pub size: (u32, u32),
+    /// Physical pixels per game unit.
+    pub scale: f32,
+    /// Physical position of the game origin; non-zero when letterboxed.
+    pub offset: Vec2,
+}
+
+impl ScreenScale {
+    pub fn new(policy: ScalePolicy, physical_size: (u32, u32), scale_factor: f64) -> Self {
+        let scale_factor = if scale_factor.is_finite() && scale_factor > 0.0 {
+            scale_factor
+        } else {
+            1.0
+        };
+        let (pw, ph) = (physical_size.0.max(1), physical_size.1.max(1));
+        let (size, scale, offset) = match policy {
+            ScalePolicy::Logical => {
+                let logical = |px: u32| ((px as f64 / scale_factor).round() as u32).max(1);
+                ((logical(pw), logical(ph)), scale_factor as f32, Vec2::ZERO)
+            }
+            ScalePolicy::Physical => (physical_size, 1.0, Vec2::ZERO),
+            ScalePolicy::Virtual { width, height } => {
+                let (vw, vh) = (width.max(1), height.max(1));
+                let scale = (pw as f32 / vw as f32).min(ph as f32 / vh as f32);
+                let offset = Vec2::new(
+                    (pw as f32 - vw as f32 * scale) * 0.5,
+                    (ph as f32 - vh as f32 * scale) * 0.5,
+                );
+                ((vw, vh), scale, offset)
+            }
+        };
+        Self {
+            policy,
+            physical_size,
+            scale_factor,
+            size,
+            scale,
+            offset,
+        }
+    }
+
+    /// One game unit per pixel of a `size` target (canvases, tests).
+    pub fn identity(size: (u32, u32)) -> Self {
+        Self::new(ScalePolicy::Physical, size, 1.0)
+    }
+
+    /// Same policy and scale factor for a resized window.
+    pub fn resized(&self, physical_size: (u32, u32)) -> Self {
+        Self::new(self.policy, physical_size, self.scale_factor)
+    }
+
+    /// Same policy and window size with a new scale factor.
+    pub fn with_scale_factor(&self, scale_factor: f64) -> Self {
+        Self::new(self.policy, self.physical_size, scale_factor)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.scale == 1.0 && self.offset == Vec2::ZERO
+    }
+
+    /// Convert a position in game units to physical pixels.
+    pub fn to_physical(self, p: Vec2) -> Vec2 {
+        p * self.scale + self.offset
+    }
+
+    /// Convert physical pixels (e.g. a cursor position) to game units.
+    pub fn to_logical(self, p: Vec2) -> Vec2 {
+        (p - self.offset) / self.scale
+    }
+
+    /// Part of the window covered by the game screen, in physical pixels: the whole
+    /// window unless letterboxed.
+    pub fn frame(&self) -> ClipRect {
+        self.clip_to_physical(&ClipRect::new(0, 0, self.size.0, self.size.1))
+    }
+
+    /// Convert a clip rect in game units to physical pixels, rounded to the nearest
+    /// pixel edges.
+    pub fn clip_to_physical(&self, rect: &ClipRect) -> ClipRect {
+        let origin = self.to_physical(Vec2::new(rect.x as f32, rect.y as f32));
+        ClipRect::from_viewport(&Viewport::new(
+            origin.x,
+            origin.y,
+            rect.width as f32 * self.scale,
+            rect.height as f32 * self.scale,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScalePolicy, ScreenScale};
+    use crate::core::assets::ImageId;
+    use crate::math::Vec2;
+    use crate::render::{ClipRect, RenderContext, SpriteDrawData};
+
+    #[test]
+    fn policies_map_game_units_to_physical_pixels() {
+        let logical = ScreenScale::new(ScalePolicy::Logical, (1600, 1200), 2.0);
+        assert_eq!((logical.size, logical.scale), ((800, 600), 2.0));
+        assert_eq!(
+            logical.to_physical(Vec2::new(10.0, 20.0)),
+            Vec2::new(20.0, 40.0)
+        );
+        assert!(logical.with_scale_factor(1.0).is_identity());
+
+        let physical = ScreenScale::new(ScalePolicy::Physical, (1600, 1200), 2.0);
+        assert_eq!(physical.size, (1600, 1200));
+        assert!(physical.is_identity());
+
+        // 320x180 in a 4:3 window: scaled by 5, with bars above and below.
+        let policy = ScalePolicy::Virtual {
+            width: 320,
+            height: 180,
+        };
+        let virtual_screen = ScreenScale::new(policy, (1600, 1200), 2.0);
+        assert_eq!(virtual_screen.scale, 5.0);
+        assert_eq!(virtual_screen.offset, Vec2::new(0.0, 150.0));
+        assert_eq!(virtual_screen.frame(), ClipRect::new(0, 150, 1600, 900));
+        assert_eq!(
+            virtual_screen.to_logical(Vec2::new(800.0, 600.0)),
+            Vec2::new(160.0, 90.0)
+        );
+    }
+
+    #[test]
+    fn render_context_draws_in_game_units() {
+        let screen = ScreenScale::new(ScalePolicy::Logical, (200, 100), 2.0);
+        let mut ctx = RenderContext::with_screen_scale(screen);
+        assert_eq!(ctx.size, (100, 50));
+        assert_eq!(ctx.to_ndc(Vec2::new(50.0, 25.0)), Vec2::ZERO);
+
+        let mut sprite = SpriteDrawData::new(ImageId::new(), 4, 4);
+        sprite.position = Vec2::new(10.0, 5.0);
+        ctx.with_clip_rect(ClipRect::new(10, 10, 20, 20), |ctx| ctx.draw_sprite(sprite));
+        assert_eq!(ctx.sprites[0].position, Vec2::new(20.0, 10.0));
+        assert_eq!(ctx.sprites[0].scale, Vec2::new(2.0, 2.0));
+        assert_eq!(
+            ctx.draw_list().commands[0].clip,
+            Some(ClipRect::new(20, 20, 40, 40))
+        );
+
+        // Letterboxed screens clip everything to the game area.
+        let policy = ScalePolicy::Virtual {
+            width: 100,
+            height: 100,
+        };
+        let mut ctx = RenderContext::with_screen_scale(ScreenScale::new(policy, (200, 100), 1.0));
+        ctx.draw_sprite(SpriteDrawData::new(ImageId::new(), 4, 4));
+        assert_eq!(
+            ctx.draw_list().commands[0].clip,
+            Some(ClipRect::new(50, 0, 100, 100))
+        );
+    }
+}
@@ -3,14 +3,21 @@ use crate::math::color::Color;
 use crate::math::vec2::Vec2;
 use crate::render::Vertex;
 use crate::render::context::RenderContext;
+use crate::render::gradient::Gradient;
 
+use super::fill::{fill_gradient, fill_rounded_box};
 use super::{Collider, Drawable, ShapeRef, Transform2d};
 
 pub struct Circle {
     pub transform: Transform,
     pub radius: f32,
     pub color: Color,
+    /// Replaces `color` when set.
+    pub gradient: Option<Gradient>,
     pub segments: u32,
+    /// Draw from the signed distance field instead of `segments` triangles, keeping
+    /// the edge smooth at any zoom. Ignored when `gradient` is set.
+    pub smooth: bool,
 }
 
 impl Circle {
@@ -20,10 +27,24 @@ impl Circle {
             transform: Transform::at(position),
             radius,
             color,
+            gradient: None,
             segments: 32,
+            smooth: false,
         }
     }
 
+    /// Builder: Fill with `gradient` instead of `color`
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
+    /// Builder: Draw from the distance field for smooth edges
+    pub fn with_smooth_edges(mut self) -> Self {
+        self.smooth = true;
+        self
+    }
+
     fn size(&self) -> Vec2 {
         Vec2::new(self.radius * 2.0, self.radius * 2.0)
     }
@@ -64,6 +85,34 @@ impl Circle {
 
 impl Drawable for Circle {
     fn draw(&self, ctx: &mut RenderContext) {
+        if let Some(gradient) = &self.gradient {
+            let center = self.local_center();
+            let mut triangles = Vec::with_capacity((self.segments * 3) as usize);
+            for i in 0..self.segments {
+                let a0 = (i as f32 / self.segments as f32) * std::f32::consts::TAU;
+                let a1 = ((i + 1) as f32 / self.segments as f32) * std::f32::consts::TAU;
+                triangles.extend([
+                    center,
+                    center + Vec2::new(a0.cos(), a0.sin()) * self.radius,
+                    center + Vec2::new(a1.cos(), a1.sin()) * self.radius,
+                ]);
+            }
+            fill_gradient(ctx, gradient, &self.transform, self.size(), &triangles);
+            return;
+        }
+        if self.smooth {
+            fill_rounded_box(
+                ctx,
+                &self.transform,
+                self.size(),
+                self.radius,
+                self.color,
+                0.0,
+                self.color,
+            );
+            return;
+        }
+
         let center = self.transform_point(self.local_center());
         let center_ndc = ctx.to_ndc(center);
         let color = self.color.to_linear_rgba();
@@ -3,13 +3,17 @@ use crate::math::color::Color;
 use crate::math::vec2::Vec2;
 use crate::render::Vertex;
 use crate::render::context::RenderContext;
+use crate::render::gradient::Gradient;
 
+use super::fill::fill_gradient;
 use super::{Collider, Drawable, ShapeRef, Transform2d};
 
 pub struct Ellipse {
     pub transform: Transform,
     pub radii: Vec2,
     pub color: Color,
+    /// Replaces `color` when set.
+    pub gradient: Option<Gradient>,
     pub segments: u32,
 }
 
@@ -22,10 +26,17 @@ impl Ellipse {
             transform: Transform::at(position),
             radii,
             color,
+            gradient: None,
             segments: 32,
         }
     }
 
+    /// Builder: Fill with `gradient` instead of `color`
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
     fn size(&self) -> Vec2 {
         self.radii * 2.0
     }
@@ -67,6 +78,22 @@ impl Ellipse {
 impl Drawable for Ellipse {
     fn draw(&self, ctx: &mut RenderContext) {
         let segments = self.segments.max(3);
+        if let Some(gradient) = &self.gradient {
+            let center = self.local_center();
+            let mut triangles = Vec::with_capacity((segments * 3) as usize);
+            for i in 0..segments {
+                let a0 = (i as f32 / segments as f32) * std::f32::consts::TAU;
+                let a1 = ((i + 1) as f32 / segments as f32) * std::f32::consts::TAU;
+                triangles.extend([
+                    center,
+                    center + Vec2::new(a0.cos() * self.radii.x, a0.sin() * self.radii.y),
+                    center + Vec2::new(a1.cos() * self.radii.x, a1.sin() * self.radii.y),
+                ]);
+            }
+            fill_gradient(ctx, gradient, &self.transform, self.size(), &triangles);
+            return;
+        }
+
         let color = self.color.to_rgba();
         let center_world = self.transform_point(self.local_center());
         let center_ndc = ctx.to_ndc(center_world);
@@ -0,0 +1,143 @@
+use crate::math::Transform;
+use crate::math::color::Color;
+use crate::math::vec2::Vec2;
+use crate::render::context::RenderContext;
+use crate::render::gradient::Gradient;
+use crate::render::{GradientVertex, SdfVertex, Vertex};
+
+/// Fill triangles given in a shape's local space (three points each) with `gradient`.
+/// Gradient coordinates are the local positions relative to the shape's `size`.
+pub(super) fn fill_gradient(
+    ctx: &mut RenderContext,
+    gradient: &Gradient,
+    transform: &Transform,
+    size: Vec2,
+    local_triangles: &[Vec2],
+) {
+    let relative = |v: f32, extent: f32| {
+        if extent.abs() > f32::EPSILON {
+            v / extent
+        } else {
+            0.0
+        }
+    };
+    let vertices: Vec<GradientVertex> = local_triangles
+        .iter()
+        .map(|&local| GradientVertex {
+            pos: ctx
+                .to_ndc(transform.transform_point(local, size))
+                .to_array(),
+            local: [relative(local.x, size.x), relative(local.y, size.y)],
+        })
+        .collect();
+    ctx.fill_gradient(&vertices, gradient);
+}
+
+/// Draw the rounded box spanning `size` in a shape's local space from its distance
+/// field. `radius` is clamped to half the smaller side; `outline` is drawn inside the
+/// edge with `outline_color`.
+pub(super) fn fill_rounded_box(
+    ctx: &mut RenderContext,
+    transform: &Transform,
+    size: Vec2,
+    radius: f32,
+    color: Color,
+    outline: f32,
+    outline_color: Color,
+) {
+    let half = Vec2::new(size.x.abs() * 0.5, size.y.abs() * 0.5);
+    let center = size * 0.5;
+    let to_ndc = |local: Vec2| ctx.to_ndc(transform.transform_point(center + local, size));
+
+    // Grow the quad by about a pixel so the smoothed edge is not cut off.
+    let (w, h) = ctx.screen_scale().physical_size;
+    let pixels = |ndc: Vec2| Vec2::new(ndc.x * w as f32 * 0.5, ndc.y * h as f32 * 0.5);
+    let origin = pixels(to_ndc(Vec2::ZERO));
+    let pixels_per_unit = (pixels(to_ndc(Vec2::new(1.0, 0.0))) - origin)
+        .length()
+        .min((pixels(to_ndc(Vec2::new(0.0, 1.0))) - origin).length());
+    let margin = 1.0 / pixels_per_unit.max(1e-3);
+
+    let (x, y) = (half.x + margin, half.y + margin);
+    let corners = [
+        Vec2::new(-x, -y),
+        Vec2::new(x, -y),
+        Vec2::new(x, y),
+        Vec2::new(-x, y),
+    ];
+    let vertices = [0, 1, 2, 0, 2, 3].map(|i| SdfVertex {
+        pos: to_ndc(corners[i]).to_array(),
+        local: corners[i].to_array(),
+        half_size: half.to_array(),
+        radius: radius.clamp(0.0, half.x.min(half.y)),
+        outline: outline.max(0.0),
+        color: color.to_linear_rgba(),
+        outline_color: outline_color.to_linear_rgba(),
+    });
+    ctx.draw_sdf(&vertices);
+}
+
+/// Triangles approximating the rounded box of an SDF shape, from the six vertices
+/// covering it: the outline color first, then the fill inset by the outline.
+pub(crate) fn tessellate_sdf_shape(quad: &[SdfVertex]) -> Vec<Vertex> {
+    const CORNER_SEGMENTS: usize = 8;
+
+    let shape = quad[0];
+    let [a, b, c] = [0, 1, 2].map(|i| {
+        (
+            Vec2::new(quad[i].local[0], quad[i].local[1]),
+            Vec2::new(quad[i].pos[0], quad[i].pos[1]),
+        )
+    });
+    // Affine map from local units to NDC, solved from the first triangle.
+    let (du, dv) = (b.0 - a.0, c.0 - a.0);
+    let det = du.x * dv.y - dv.x * du.y;
+    if det.abs() <= f32::EPSILON {
+        return Vec::new();
+    }
+    let to_ndc = |p: Vec2| {
+        let d = p - a.0;
+        let s = (d.x * dv.y - dv.x * d.y) / det;
+        let t = (du.x * d.y - d.x * du.y) / det;
+        (a.1 + (b.1 - a.1) * s + (c.1 - a.1) * t).to_array()
+    };
+
+    let mut vertices = Vec::new();
+    let mut fill = |half: Vec2, radius: f32, color: [f32; 4]| {
+        if half.x <= 0.0 || half.y <= 0.0 {
+            return;
+        }
+        let radius = radius.clamp(0.0, half.x.min(half.y));
+        let mut outline = Vec::with_capacity(4 * (CORNER_SEGMENTS + 1));
+        for (corner, sign) in [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)]
+            .into_iter()
+            .enumerate()
+        {
+            let center = Vec2::new(sign.0 * (half.x - radius), sign.1 * (half.y - radius));
+            for i in 0..=CORNER_SEGMENTS {
+                let angle = (corner as f32 + i as f32 / CORNER_SEGMENTS as f32)
+                    * std::f32::consts::FRAC_PI_2;
+                outline.push(center + Vec2::new(angle.cos(), angle.sin()) * radius);
+            }
+        }
+        for i in 0..outline.len() {
+            let next = outline[(i + 1) % outline.len()];
+            for p in [Vec2::ZERO, outline[i], next] {
+                vertices.push(Vertex {
+                    pos: to_ndc(p),
+                    color,
+                });
+            }
+        }
+    };
+
+    let half = Vec2::new(shape.half_size[0], shape.half_size[1]);
+    if shape.outline > 0.0 {
+        fill(half, shape.radius, shape.outline_color);
+        let inset = Vec2::new(shape.outline, shape.outline);
+        fill(half - inset, shape.radius - shape.outline, shape.color);
+    } else {
+        fill(half, shape.radius, shape.color);
+    }
+    vertices
+}
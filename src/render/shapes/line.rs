@@ -4,6 +4,7 @@ use crate::math::vec2::Vec2;
 use crate::render::Vertex;
 use crate::render::context::RenderContext;
 
+use super::stroke::{Stroke, StrokeStyle, stroke_path};
 use super::{Collider, Drawable, ShapeRef, Transform2d};
 
 pub struct Line {
@@ -11,6 +12,9 @@ pub struct Line {
     pub size: Vec2,
     pub color: Color,
     pub thickness: f32,
+    /// Caps and dashes; joins do not apply to a single segment. Hit tests cover the
+    /// gaps between dashes, like `world_outline`.
+    pub stroke: StrokeStyle,
 }
 
 impl Line {
@@ -30,21 +34,23 @@ impl Line {
             size,
             color,
             thickness,
+            stroke: StrokeStyle::default(),
         }
     }
 
-    fn local_corners(&self) -> [Vec2; 4] {
-        [
-            Vec2::ZERO,
-            Vec2::new(self.size.x, 0.0),
-            Vec2::new(self.size.x, self.size.y),
-            Vec2::new(0.0, self.size.y),
-        ]
+    /// Builder: Caps and dashes of the stroke
+    pub fn with_stroke(mut self, stroke: StrokeStyle) -> Self {
+        self.stroke = stroke;
+        self
     }
 
-    fn world_corners(&self) -> [Vec2; 4] {
-        self.local_corners()
-            .map(|corner| self.transform.transform_point(corner, self.size))
+    fn local_stroke(&self, style: &StrokeStyle) -> Stroke {
+        let center = self.size.y * 0.5;
+        stroke_path(
+            &[Vec2::new(0.0, center), Vec2::new(self.size.x, center)],
+            self.size.y,
+            style,
+        )
     }
 
     pub fn set_origin_keep_position(&mut self, origin: Vec2) {
@@ -55,45 +61,32 @@ impl Line {
         self.transform.set_origin_center_keep_position(self.size);
     }
 
+    /// Outline of the stroke with its caps, ignoring dashes.
     pub fn world_outline(&self) -> Vec<Vec2> {
-        self.world_corners().to_vec()
+        self.local_stroke(&self.stroke.solid())
+            .outlines
+            .pop()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| self.transform.transform_point(p, self.size))
+            .collect()
     }
 }
 
 impl Drawable for Line {
     fn draw(&self, ctx: &mut RenderContext) {
-        let [tl, tr, br, bl] = {
-            let corners = self.world_corners();
-            [corners[0], corners[1], corners[2], corners[3]]
-        };
-
         let color = self.color.to_rgba();
-        let vertices = [
-            Vertex {
-                pos: ctx.to_ndc(tl).to_array(),
-                color,
-            },
-            Vertex {
-                pos: ctx.to_ndc(tr).to_array(),
-                color,
-            },
-            Vertex {
-                pos: ctx.to_ndc(br).to_array(),
-                color,
-            },
-            Vertex {
-                pos: ctx.to_ndc(br).to_array(),
+        let vertices: Vec<Vertex> = self
+            .local_stroke(&self.stroke)
+            .triangles
+            .iter()
+            .map(|p| Vertex {
+                pos: ctx
+                    .to_ndc(self.transform.transform_point(*p, self.size))
+                    .to_array(),
                 color,
-            },
-            Vertex {
-                pos: ctx.to_ndc(bl).to_array(),
-                color,
-            },
-            Vertex {
-                pos: ctx.to_ndc(tl).to_array(),
-                color,
-            },
-        ];
+            })
+            .collect();
 
         ctx.extend(&vertices);
     }
@@ -111,11 +104,9 @@ impl Transform2d for Line {
 
 impl Collider for Line {
     fn contains_point(&self, point: Vec2) -> bool {
-        if let Some(local) = self.transform.to_local(point, self.size) {
-            local.x >= 0.0 && local.x <= self.size.x && local.y >= 0.0 && local.y <= self.size.y
-        } else {
-            false
-        }
+        self.transform
+            .to_local(point, self.size)
+            .is_some_and(|local| self.local_stroke(&self.stroke.solid()).contains(local))
     }
 
     fn as_shape(&self) -> ShapeRef<'_> {
@@ -0,0 +1,140 @@
+use crate::core::assets::ImageId;
+use crate::math::Transform;
+use crate::math::color::Color;
+use crate::math::vec2::Vec2;
+use crate::render::TexturedVertex;
+use crate::render::context::RenderContext;
+
+use super::{Drawable, Transform2d};
+
+/// Vertex of a `Mesh2d`: local position, texture coordinates (0..1 across the image)
+/// and a color multiplied with the sampled texel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshVertex {
+    pub position: Vec2,
+    pub uv: Vec2,
+    pub color: Color,
+}
+
+impl MeshVertex {
+    pub fn new(position: Vec2, uv: Vec2, color: Color) -> Self {
+        Self {
+            position,
+            uv,
+            color,
+        }
+    }
+}
+
+/// Indexed triangles with UVs and colors, optionally textured: deformable sprites,
+/// textured polygons, trails, terrain strips...
+///
+/// Vertex positions are local to `transform`, which rotates and scales them around
+/// the local origin. UVs outside 0..1 repeat or clamp according to the image's
+/// sampler; images packed into the atlas always clamp.
+pub struct Mesh2d {
+    pub transform: Transform,
+    pub vertices: Vec<MeshVertex>,
+    /// Three indices into `vertices` per triangle.
+    pub indices: Vec<u32>,
+    /// Sampled image; `None` draws vertex colors only.
+    pub texture: Option<ImageId>,
+}
+
+impl Mesh2d {
+    pub fn new(vertices: Vec<MeshVertex>, indices: Vec<u32>) -> Self {
+        Self {
+            transform: Transform::new().with_origin(Vec2::ZERO),
+            vertices,
+            indices,
+            texture: None,
+        }
+    }
+
+    /// Mesh whose triangles join each vertex to the two before it, e.g. a trail or a
+    /// terrain strip with vertices alternating between its two edges.
+    pub fn triangle_strip(vertices: Vec<MeshVertex>) -> Self {
+        let count = vertices.len() as u32;
+        let indices = (2..count.max(2))
+            .flat_map(|i| {
+                // Alternate the winding so every triangle faces the same way.
+                if i % 2 == 0 {
+                    [i - 2, i - 1, i]
+                } else {
+                    [i - 1, i - 2, i]
+                }
+            })
+            .collect();
+        Self::new(vertices, indices)
+    }
+
+    /// Builder: Sample `texture`
+    pub fn with_texture(mut self, texture: ImageId) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+}
+
+impl Drawable for Mesh2d {
+    fn draw(&self, ctx: &mut RenderContext) {
+        let vertices: Vec<TexturedVertex> = self
+            .vertices
+            .iter()
+            .map(|v| TexturedVertex {
+                pos: ctx
+                    .to_ndc(self.transform.transform_point(v.position, Vec2::ZERO))
+                    .to_array(),
+                uv: v.uv.to_array(),
+                color: v.color.to_linear_rgba(),
+            })
+            .collect();
+        ctx.draw_mesh(&vertices, &self.indices, self.texture);
+    }
+}
+
+impl Transform2d for Mesh2d {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mesh2d, MeshVertex};
+    use crate::core::assets::ImageId;
+    use crate::math::{Color, Vec2};
+    use crate::render::{DrawCommandKind, Drawable, RenderContext};
+
+    fn vertex(x: f32, y: f32) -> MeshVertex {
+        MeshVertex::new(Vec2::new(x, y), Vec2::new(x / 4.0, y / 4.0), Color::WHITE)
+    }
+
+    #[test]
+    fn meshes_are_recorded_with_whole_valid_triangles() {
+        let strip = Mesh2d::triangle_strip(vec![
+            vertex(0.0, 0.0),
+            vertex(0.0, 4.0),
+            vertex(4.0, 0.0),
+            vertex(4.0, 4.0),
+        ]);
+        assert_eq!(strip.indices, vec![0, 1, 2, 2, 1, 3]);
+
+        let mut ctx = RenderContext::new((4, 4));
+        strip.with_texture(ImageId::new()).draw(&mut ctx);
+        // The second triangle references a missing vertex, the last one is partial.
+        Mesh2d::new(vec![vertex(0.0, 0.0); 3], vec![0, 1, 2, 0, 1, 3, 0]).draw(&mut ctx);
+        Mesh2d::new(vec![vertex(0.0, 0.0); 3], vec![0, 1, 5]).draw(&mut ctx);
+
+        let list = ctx.draw_list();
+        assert_eq!(list.commands.len(), 1);
+        assert_eq!(list.commands[0].kind, DrawCommandKind::Meshes(0..2));
+        assert_eq!(list.meshes[1].indices, 6..9);
+        assert_eq!(list.mesh_indices[6..], [0, 1, 2]);
+        assert_eq!(list.mesh_vertices[0].pos, [-1.0, 1.0]);
+        assert_eq!(list.mesh_vertices[3].uv, [1.0, 1.0]);
+    }
+}
@@ -1,21 +1,32 @@
 mod circle;
 mod ellipse;
+mod fill;
 mod line;
+mod mesh;
+mod path;
 mod polygon;
 mod polyline;
 mod rectangle;
+mod rounded_rect;
 mod shape_ref;
+mod stroke;
+mod tessellate;
 mod traits;
 mod transform;
 mod triangle;
 
 pub use circle::Circle;
 pub use ellipse::Ellipse;
+pub(crate) use fill::tessellate_sdf_shape;
 pub use line::Line;
+pub use mesh::{Mesh2d, MeshVertex};
+pub use path::{FillRule, Path};
 pub use polygon::Polygon;
 pub use polyline::Polyline;
 pub use rectangle::Rectangle;
+pub use rounded_rect::RoundedRect;
 pub use shape_ref::{ShapeRef, shapes_intersect};
+pub use stroke::{LineCap, LineJoin, StrokeStyle};
 pub use traits::{Collider, Drawable};
 pub use transform::Transform2d;
 pub use triangle::Triangle;
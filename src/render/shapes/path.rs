@@ -0,0 +1,385 @@
+use crate::math::Transform;
+use crate::math::color::Color;
+use crate::math::vec2::Vec2;
+use crate::render::Vertex;
+use crate::render::context::RenderContext;
+
+use super::stroke::{StrokeStyle, stroke_closed_path, stroke_path};
+use super::tessellate::fill_contours;
+use super::{Drawable, Transform2d};
+
+/// How the inside of a filled path is decided where its contours overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Inside where contours wind around a point a non-zero number of times: a
+    /// contour running the other way cuts a hole.
+    #[default]
+    NonZero,
+    /// Inside where a ray from a point crosses an odd number of edges: every nested
+    /// contour cuts a hole.
+    EvenOdd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathCommand {
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    QuadTo(Vec2, Vec2),
+    CubicTo(Vec2, Vec2, Vec2),
+    ArcTo { center: Vec2, sweep: f32 },
+    Close,
+}
+
+/// Subpath flattened into line segments.
+struct Contour {
+    points: Vec<Vec2>,
+    closed: bool,
+}
+
+/// Vector shape built from lines, Bezier curves and arcs, filled and/or stroked.
+///
+/// Points are in the path's local space. Subpaths are implicitly closed when filled;
+/// only those ending with `close` are stroked closed.
+///
+/// ```ignore
+/// let ring = Path::new()
+///     .move_to(Vec2::new(0.0, 0.0))
+///     .line_to(Vec2::new(40.0, 0.0))
+///     .quad_to(Vec2::new(60.0, 20.0), Vec2::new(40.0, 40.0))
+///     .close()
+///     .move_to(Vec2::new(25.0, 20.0))
+///     .arc_to(Vec2::new(20.0, 20.0), -TAU)
+///     .close()
+///     .with_fill(Color::RED)
+///     .with_stroke(Color::WHITE, 2.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub transform: Transform,
+    commands: Vec<PathCommand>,
+    /// Fill color; `None` to only stroke.
+    pub fill: Option<Color>,
+    pub fill_rule: FillRule,
+    /// Stroke color; `None` to only fill.
+    pub stroke_color: Option<Color>,
+    pub stroke_thickness: f32,
+    pub stroke: StrokeStyle,
+    /// Largest distance between a curve and the segments approximating it, in local
+    /// units.
+    pub tolerance: f32,
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self {
+            transform: Transform::new(),
+            commands: Vec::new(),
+            fill: None,
+            fill_rule: FillRule::NonZero,
+            stroke_color: None,
+            stroke_thickness: 1.0,
+            stroke: StrokeStyle::default(),
+            tolerance: 0.25,
+        }
+    }
+
+    /// Start a new subpath at `point`.
+    pub fn move_to(mut self, point: Vec2) -> Self {
+        self.commands.push(PathCommand::MoveTo(point));
+        self
+    }
+
+    pub fn line_to(mut self, point: Vec2) -> Self {
+        self.commands.push(PathCommand::LineTo(point));
+        self
+    }
+
+    /// Quadratic Bezier curve to `point`, pulled towards `control`.
+    pub fn quad_to(mut self, control: Vec2, point: Vec2) -> Self {
+        self.commands.push(PathCommand::QuadTo(control, point));
+        self
+    }
+
+    /// Cubic Bezier curve to `point`, leaving towards `control1` and arriving from
+    /// `control2`.
+    pub fn cubic_to(mut self, control1: Vec2, control2: Vec2, point: Vec2) -> Self {
+        self.commands
+            .push(PathCommand::CubicTo(control1, control2, point));
+        self
+    }
+
+    /// Circular arc around `center` from the current point, turning by `sweep`
+    /// radians: clockwise on screen when positive.
+    pub fn arc_to(mut self, center: Vec2, sweep: f32) -> Self {
+        self.commands.push(PathCommand::ArcTo { center, sweep });
+        self
+    }
+
+    /// Close the current subpath back to its first point.
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    /// Builder: Fill with `color`
+    pub fn with_fill(mut self, color: Color) -> Self {
+        self.fill = Some(color);
+        self
+    }
+
+    /// Builder: Rule deciding which overlapping areas are filled
+    pub fn with_fill_rule(mut self, rule: FillRule) -> Self {
+        self.fill_rule = rule;
+        self
+    }
+
+    /// Builder: Stroke of `thickness` with `color`
+    pub fn with_stroke(mut self, color: Color, thickness: f32) -> Self {
+        self.stroke_color = Some(color);
+        self.stroke_thickness = thickness;
+        self
+    }
+
+    /// Builder: Joins, caps and dashes of the stroke
+    pub fn with_stroke_style(mut self, style: StrokeStyle) -> Self {
+        self.stroke = style;
+        self
+    }
+
+    /// Builder: Flattening tolerance of curves and arcs
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Extent of the path from the local origin, which `transform` origins are
+    /// relative to.
+    pub fn size(&self) -> Vec2 {
+        extent(&self.contours())
+    }
+
+    pub fn set_origin_keep_position(&mut self, origin: Vec2) {
+        let size = self.size();
+        self.transform.set_origin_keep_position(origin, size);
+    }
+
+    pub fn set_origin_center_keep_position(&mut self) {
+        let size = self.size();
+        self.transform.set_origin_center_keep_position(size);
+    }
+
+    /// Triangle list covering the fill, in local space.
+    pub fn fill_triangles(&self) -> Vec<Vec2> {
+        let contours: Vec<Vec<Vec2>> = self
+            .contours()
+            .into_iter()
+            .map(|contour| contour.points)
+            .collect();
+        fill_contours(&contours, self.fill_rule)
+    }
+
+    /// Triangle list covering the stroke, in local space.
+    pub fn stroke_triangles(&self) -> Vec<Vec2> {
+        self.contours()
+            .iter()
+            .flat_map(|contour| {
+                let stroke = if contour.closed {
+                    stroke_closed_path(&contour.points, self.stroke_thickness, &self.stroke)
+                } else {
+                    stroke_path(&contour.points, self.stroke_thickness, &self.stroke)
+                };
+                stroke.triangles
+            })
+            .collect()
+    }
+
+    fn contours(&self) -> Vec<Contour> {
+        let tolerance = self.tolerance.max(1e-3);
+        let mut contours = Vec::new();
+        let mut points: Vec<Vec2> = Vec::new();
+        let mut cursor = Vec2::ZERO;
+        let mut start = Vec2::ZERO;
+
+        let mut flush = |points: &mut Vec<Vec2>, closed: bool| {
+            let points = std::mem::take(points);
+            if points.len() >= 2 {
+                contours.push(Contour { points, closed });
+            }
+        };
+
+        for command in &self.commands {
+            if points.is_empty() && !matches!(command, PathCommand::MoveTo(_)) {
+                points.push(cursor);
+                start = cursor;
+            }
+            match *command {
+                PathCommand::MoveTo(point) => {
+                    flush(&mut points, false);
+                    points.push(point);
+                    start = point;
+                }
+                PathCommand::LineTo(point) => points.push(point),
+                PathCommand::QuadTo(control, point) => {
+                    let bend = (cursor - control * 2.0 + point).length();
+                    let steps = segment_count((bend / (4.0 * tolerance)).sqrt());
+                    points.extend((1..=steps).map(|i| {
+                        let t = i as f32 / steps as f32;
+                        let u = 1.0 - t;
+                        cursor * (u * u) + control * (2.0 * u * t) + point * (t * t)
+                    }));
+                }
+                PathCommand::CubicTo(control1, control2, point) => {
+                    let bend = (cursor - control1 * 2.0 + control2)
+                        .length()
+                        .max((control1 - control2 * 2.0 + point).length());
+                    let steps = segment_count((0.75 * bend / tolerance).sqrt());
+                    points.extend((1..=steps).map(|i| {
+                        let t = i as f32 / steps as f32;
+                        let u = 1.0 - t;
+                        cursor * (u * u * u)
+                            + control1 * (3.0 * u * u * t)
+                            + control2 * (3.0 * u * t * t)
+                            + point * (t * t * t)
+                    }));
+                }
+                PathCommand::ArcTo { center, sweep } => {
+                    let offset = cursor - center;
+                    let radius = offset.length();
+                    let start_angle = offset.y.atan2(offset.x);
+                    let step = 2.0 * (1.0 - (tolerance / radius.max(1e-6)).min(1.0)).acos();
+                    let steps = segment_count(sweep.abs() / step.max(1e-3));
+                    points.extend((1..=steps).map(|i| {
+                        let angle = start_angle + sweep * i as f32 / steps as f32;
+                        center + Vec2::new(angle.cos(), angle.sin()) * radius
+                    }));
+                }
+                PathCommand::Close => {
+                    flush(&mut points, true);
+                    cursor = start;
+                    continue;
+                }
+            }
+            cursor = points.last().copied().unwrap_or(cursor);
+        }
+        flush(&mut points, false);
+
+        contours
+    }
+}
+
+/// Segments approximating a curve, capped so huge curves stay affordable.
+fn segment_count(estimate: f32) -> usize {
+    (estimate.ceil() as usize).clamp(1, 256)
+}
+
+fn extent(contours: &[Contour]) -> Vec2 {
+    contours
+        .iter()
+        .flat_map(|contour| &contour.points)
+        .fold(Vec2::ZERO, |size, p| {
+            Vec2::new(size.x.max(p.x), size.y.max(p.y))
+        })
+}
+
+impl Drawable for Path {
+    fn draw(&self, ctx: &mut RenderContext) {
+        let size = self.size();
+        let mut vertices = Vec::new();
+        let mut push = |triangles: Vec<Vec2>, color: Color| {
+            let color = color.to_linear_rgba();
+            vertices.extend(triangles.into_iter().map(|p| {
+                Vertex {
+                    pos: ctx
+                        .to_ndc(self.transform.transform_point(p, size))
+                        .to_array(),
+                    color,
+                }
+            }));
+        };
+        if let Some(color) = self.fill {
+            push(self.fill_triangles(), color);
+        }
+        if let Some(color) = self.stroke_color {
+            push(self.stroke_triangles(), color);
+        }
+
+        ctx.extend(&vertices);
+    }
+}
+
+impl Transform2d for Path {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{PI, TAU};
+
+    use super::{FillRule, Path};
+    use crate::math::Vec2;
+
+    fn area(triangles: &[Vec2]) -> f32 {
+        triangles
+            .chunks_exact(3)
+            .map(|t| {
+                let (a, b) = (t[1] - t[0], t[2] - t[0]);
+                (a.x * b.y - a.y * b.x).abs() * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn paths_flatten_curves_and_fill_holes() {
+        // Square with a round hole cut by the even-odd rule.
+        let path = Path::new()
+            .move_to(Vec2::new(0.0, 0.0))
+            .line_to(Vec2::new(10.0, 0.0))
+            .line_to(Vec2::new(10.0, 10.0))
+            .line_to(Vec2::new(0.0, 10.0))
+            .close()
+            .move_to(Vec2::new(8.0, 5.0))
+            .arc_to(Vec2::new(5.0, 5.0), TAU)
+            .close()
+            .with_fill_rule(FillRule::EvenOdd)
+            .with_tolerance(0.01);
+        let hole = PI * 9.0;
+        assert!((area(&path.fill_triangles()) - (100.0 - hole)).abs() < 0.2);
+        assert_eq!(path.size(), Vec2::new(10.0, 10.0));
+
+        // The curve stays within the tolerance of its true peak at t = 0.5.
+        let curve = Path::new().move_to(Vec2::new(0.0, 0.0)).cubic_to(
+            Vec2::new(0.0, 10.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(10.0, 0.0),
+        );
+        let contours = curve.contours();
+        assert_eq!(contours.len(), 1);
+        assert!(!contours[0].closed);
+        let peak = contours[0]
+            .points
+            .iter()
+            .fold(0.0f32, |peak, p| peak.max(p.y));
+        assert!(peak <= 7.5 && peak > 7.5 - curve.tolerance);
+        assert_eq!(contours[0].points.last(), Some(&Vec2::new(10.0, 0.0)));
+
+        // Open subpaths are stroked too.
+        let stroked = curve.with_stroke(crate::math::Color::WHITE, 2.0);
+        assert!(!stroked.stroke_triangles().is_empty());
+    }
+}
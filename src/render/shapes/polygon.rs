@@ -3,13 +3,19 @@ use crate::math::color::Color;
 use crate::math::vec2::Vec2;
 use crate::render::Vertex;
 use crate::render::context::RenderContext;
+use crate::render::gradient::Gradient;
 
+use super::fill::fill_gradient;
+use super::path::FillRule;
+use super::tessellate::fill_contours;
 use super::{Collider, Drawable, ShapeRef, Transform2d};
 
 pub struct Polygon {
     pub transform: Transform,
     pub local_points: Vec<Vec2>,
     pub color: Color,
+    /// Replaces `color` when set.
+    pub gradient: Option<Gradient>,
     pub size: Vec2,
 }
 
@@ -20,6 +26,7 @@ impl Polygon {
                 transform: Transform::new(),
                 local_points: Vec::new(),
                 color,
+                gradient: None,
                 size: Vec2::ZERO,
             };
         }
@@ -44,10 +51,17 @@ impl Polygon {
             transform: Transform::at(position),
             local_points,
             color,
+            gradient: None,
             size,
         }
     }
 
+    /// Builder: Fill with `gradient` instead of `color`
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
     fn transform_point(&self, local: Vec2) -> Vec2 {
         self.transform.transform_point(local, self.size)
     }
@@ -74,35 +88,26 @@ impl Polygon {
 
 impl Drawable for Polygon {
     fn draw(&self, ctx: &mut RenderContext) {
-        let point_count = self.local_points.len();
-        if point_count < 3 {
+        if self.local_points.len() < 3 {
+            return;
+        }
+
+        // Even-odd, like `contains_point`: concave and self-intersecting outlines
+        // are covered exactly.
+        let triangles = fill_contours(std::slice::from_ref(&self.local_points), FillRule::EvenOdd);
+        if let Some(gradient) = &self.gradient {
+            fill_gradient(ctx, gradient, &self.transform, self.size, &triangles);
             return;
         }
 
         let color = self.color.to_linear_rgba();
-        let world_points = self.world_points();
-        let ndc_points: Vec<Vec2> = world_points.iter().map(|p| ctx.to_ndc(*p)).collect();
-
-        // Fan triangulation around the first point to cover the polygon area.
-        let mut vertices: Vec<Vertex> = Vec::with_capacity((point_count - 2) * 3);
-        let anchor = ndc_points[0];
-        for i in 1..(point_count - 1) {
-            let v1 = ndc_points[i];
-            let v2 = ndc_points[i + 1];
-
-            vertices.push(Vertex {
-                pos: anchor.to_array(),
-                color,
-            });
-            vertices.push(Vertex {
-                pos: v1.to_array(),
-                color,
-            });
-            vertices.push(Vertex {
-                pos: v2.to_array(),
+        let vertices: Vec<Vertex> = triangles
+            .iter()
+            .map(|p| Vertex {
+                pos: ctx.to_ndc(self.transform_point(*p)).to_array(),
                 color,
-            });
-        }
+            })
+            .collect();
 
         ctx.extend(&vertices);
     }
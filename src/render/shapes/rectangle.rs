@@ -3,13 +3,17 @@ use crate::math::color::Color;
 use crate::math::vec2::Vec2;
 use crate::render::Vertex;
 use crate::render::context::RenderContext;
+use crate::render::gradient::Gradient;
 
+use super::fill::fill_gradient;
 use super::{Collider, Drawable, ShapeRef, Transform2d};
 
 pub struct Rectangle {
     pub transform: Transform,
     pub size: Vec2,
     pub color: Color,
+    /// Replaces `color` for the fill when set; the outline keeps `outline_color`.
+    pub gradient: Option<Gradient>,
 
     pub filled: bool,
     pub outline_thickness: f32,
@@ -22,6 +26,7 @@ impl Rectangle {
             transform: Transform::at(position),
             size,
             color,
+            gradient: None,
 
             filled: true,
             outline_thickness: 0.0,
@@ -38,6 +43,12 @@ impl Rectangle {
         rect
     }
 
+    /// Builder: Fill with `gradient` instead of `color`.
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
     pub fn set_filled(&mut self, filled: bool) {
         self.filled = filled;
     }
@@ -182,6 +193,21 @@ impl Transform2d for Rectangle {
 
 impl Drawable for Rectangle {
     fn draw(&self, ctx: &mut RenderContext) {
+        if self.filled
+            && let Some(gradient) = &self.gradient
+        {
+            let (w, h) = (self.size.x, self.size.y);
+            let [tl, tr, bl, br] = [
+                Vec2::ZERO,
+                Vec2::new(w, 0.0),
+                Vec2::new(0.0, h),
+                Vec2::new(w, h),
+            ];
+            // Same triangles as the solid fill.
+            let triangles = [tl, tr, bl, tr, br, bl];
+            fill_gradient(ctx, gradient, &self.transform, self.size, &triangles);
+        }
+
         let mut push_quad = |local_min: Vec2, local_max: Vec2, color: Color| {
             let tl = self.transform_point(local_min);
             let tr = self.transform_point(Vec2::new(local_max.x, local_min.y));
@@ -228,7 +254,7 @@ impl Drawable for Rectangle {
         };
 
         // Fill
-        if self.filled {
+        if self.filled && self.gradient.is_none() {
             push_quad(Vec2::ZERO, self.size, self.color);
         }
 
@@ -0,0 +1,143 @@
+use crate::math::Transform;
+use crate::math::color::Color;
+use crate::math::vec2::Vec2;
+use crate::render::context::RenderContext;
+
+use super::fill::fill_rounded_box;
+use super::{Drawable, Transform2d};
+
+/// Rectangle with rounded corners, drawn from its signed distance field: its edges stay
+/// smooth at any zoom instead of being tessellated.
+///
+/// A corner radius of half the height makes a capsule (see `RoundedRect::capsule`);
+/// half of a square's side makes a circle.
+pub struct RoundedRect {
+    pub transform: Transform,
+    pub size: Vec2,
+    /// Radius of the corners, clamped to half the smaller side.
+    pub corner_radius: f32,
+    pub color: Color,
+    /// Thickness of the outline, drawn inside the edge; `0.0` for none.
+    pub outline_thickness: f32,
+    pub outline_color: Color,
+}
+
+impl RoundedRect {
+    pub fn new(position: Vec2, size: Vec2, corner_radius: f32, color: Color) -> Self {
+        Self {
+            transform: Transform::at(position),
+            size,
+            corner_radius,
+            color,
+            outline_thickness: 0.0,
+            outline_color: color,
+        }
+    }
+
+    /// Segment from `start` to `end` with round caps: a thick line or a pill whose
+    /// edges are `radius` away from the segment.
+    pub fn capsule(start: Vec2, end: Vec2, radius: f32, color: Color) -> Self {
+        let delta = end - start;
+        let size = Vec2::new(delta.length() + radius * 2.0, radius * 2.0);
+        let center = (start + end) * 0.5;
+        let mut capsule = Self::new(center - size * 0.5, size, radius, color);
+        capsule.transform = capsule
+            .transform
+            .with_rotation(delta.y.atan2(delta.x))
+            .with_origin(Vec2::new(0.5, 0.5));
+        capsule
+    }
+
+    /// Builder: Outline of `thickness` drawn inside the edge with `color`
+    pub fn with_outline(mut self, thickness: f32, color: Color) -> Self {
+        self.outline_thickness = thickness;
+        self.outline_color = color;
+        self
+    }
+
+    pub fn set_origin_keep_position(&mut self, origin: Vec2) {
+        self.transform.set_origin_keep_position(origin, self.size);
+    }
+
+    pub fn set_origin_center_keep_position(&mut self) {
+        self.transform.set_origin_center_keep_position(self.size);
+    }
+}
+
+impl Drawable for RoundedRect {
+    fn draw(&self, ctx: &mut RenderContext) {
+        fill_rounded_box(
+            ctx,
+            &self.transform,
+            self.size,
+            self.corner_radius,
+            self.color,
+            self.outline_thickness,
+            self.outline_color,
+        );
+    }
+}
+
+impl Transform2d for RoundedRect {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoundedRect;
+    use crate::math::{Color, Vec2};
+    use crate::render::{Circle, DrawCommandKind, Drawable, RenderContext};
+
+    #[test]
+    fn sdf_shapes_cover_their_box_with_a_pixel_margin() {
+        let mut ctx = RenderContext::new((100, 100));
+        RoundedRect::new(
+            Vec2::new(10.0, 10.0),
+            Vec2::new(40.0, 20.0),
+            50.0,
+            Color::RED,
+        )
+        .with_outline(2.0, Color::BLACK)
+        .draw(&mut ctx);
+        RoundedRect::capsule(
+            Vec2::new(50.0, 50.0),
+            Vec2::new(50.0, 90.0),
+            5.0,
+            Color::RED,
+        )
+        .draw(&mut ctx);
+        Circle::new(Vec2::new(50.0, 50.0), 10.0, Color::RED)
+            .with_smooth_edges()
+            .draw(&mut ctx);
+
+        let list = ctx.draw_list();
+        assert_eq!(list.commands.len(), 1);
+        assert_eq!(list.commands[0].kind, DrawCommandKind::SdfShapes(0..18));
+
+        let close = |a: [f32; 2], b: [f32; 2]| (a[0] - b[0]).abs() + (a[1] - b[1]).abs() < 1e-4;
+        let rect = &list.sdf_vertices[..6];
+        assert_eq!(rect[0].half_size, [20.0, 10.0]);
+        assert_eq!(rect[0].radius, 10.0);
+        assert_eq!(rect[0].outline, 2.0);
+        assert!(close(rect[0].local, [-21.0, -11.0]));
+        assert!(close(rect[0].pos, [-0.82, 0.82]));
+
+        // Vertical capsule: its long local axis is turned a quarter turn, so the first
+        // corner lands at (56, 44).
+        let capsule = &list.sdf_vertices[6..12];
+        assert_eq!(capsule[0].half_size, [25.0, 5.0]);
+        assert!(close(capsule[0].pos, [0.12, 0.12]));
+
+        let circle = &list.sdf_vertices[12..];
+        assert_eq!(
+            (circle[0].half_size, circle[0].radius),
+            ([10.0, 10.0], 10.0)
+        );
+    }
+}
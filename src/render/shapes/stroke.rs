@@ -0,0 +1,561 @@
+use std::f32::consts::PI;
+
+use crate::math::vec2::Vec2;
+
+/// How two segments of a stroke meet on the outer side of a turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Edges extended until they meet, beveled past the miter limit.
+    #[default]
+    Miter,
+    /// Arc around the shared point.
+    Round,
+    /// Edges cut straight across.
+    Bevel,
+}
+
+/// How the open ends of a stroke, and of every dash, are finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Ends exactly at the end point.
+    #[default]
+    Butt,
+    /// Half disc around the end point.
+    Round,
+    /// Extended by half the thickness past the end point.
+    Square,
+}
+
+/// Joins, caps and dash pattern of a `Line` or `Polyline`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    pub join: LineJoin,
+    /// Longest miter, as a multiple of half the thickness, before it is beveled.
+    pub miter_limit: f32,
+    pub cap: LineCap,
+    /// Alternating dash and gap lengths; empty for a solid stroke. An odd number of
+    /// entries is repeated to make the pattern even.
+    pub dashes: Vec<f32>,
+    /// Distance into the pattern at the first point. Animating it moves the dashes
+    /// along the stroke.
+    pub dash_offset: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            cap: LineCap::Butt,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: Join used at every interior point
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Builder: Miter limit, as a multiple of half the thickness
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    /// Builder: Cap used at both ends and around every dash
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Builder: Dash pattern starting `offset` into the pattern
+    pub fn with_dashes(mut self, dashes: impl Into<Vec<f32>>, offset: f32) -> Self {
+        self.dashes = dashes.into();
+        self.dash_offset = offset;
+        self
+    }
+
+    /// Same style without dashes.
+    pub fn solid(&self) -> Self {
+        Self {
+            dashes: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Stroked geometry of a path, in the path's coordinates.
+pub(super) struct Stroke {
+    /// Triangle list covering the stroke.
+    pub triangles: Vec<Vec2>,
+    /// Outline of each dash, or of the whole stroke when solid. A solid closed stroke
+    /// has two: its left and right rims.
+    pub outlines: Vec<Vec<Vec2>>,
+}
+
+impl Stroke {
+    fn empty() -> Self {
+        Self {
+            triangles: Vec::new(),
+            outlines: Vec::new(),
+        }
+    }
+
+    pub fn contains(&self, p: Vec2) -> bool {
+        self.triangles
+            .chunks_exact(3)
+            .any(|t| point_in_triangle(p, t[0], t[1], t[2]))
+    }
+}
+
+/// Stroke the open path through `points` with `thickness` and `style`.
+pub(super) fn stroke_path(points: &[Vec2], thickness: f32, style: &StrokeStyle) -> Stroke {
+    let mut stroke = Stroke::empty();
+    let half = thickness * 0.5;
+    if half <= 0.0 {
+        return stroke;
+    }
+    let deduped = dedup(points);
+    if let [point] = deduped[..] {
+        // Shorter than a pixel's fraction: only the caps remain, facing along the
+        // path if it has any length at all.
+        let delta = points[points.len() - 1] - points[0];
+        let dir = if delta.length() > 0.0 {
+            delta.normalize()
+        } else {
+            Vec2::new(1.0, 0.0)
+        };
+        stroke_dot(point, dir, half, style.cap, &mut stroke);
+        return stroke;
+    }
+    for piece in dash_pieces(&deduped, style) {
+        stroke_piece(&piece, half, style, &mut stroke);
+    }
+    stroke
+}
+
+/// Stroke the closed path through `points`: the last point joins back to the first
+/// one instead of being capped.
+pub(super) fn stroke_closed_path(points: &[Vec2], thickness: f32, style: &StrokeStyle) -> Stroke {
+    let mut points = dedup(points);
+    if points.len() > 2 && same_point(points[0], points[points.len() - 1]) {
+        points.pop();
+    }
+    if points.len() < 3 {
+        return stroke_path(&points, thickness, style);
+    }
+    let mut stroke = Stroke::empty();
+    let half = thickness * 0.5;
+    if half <= 0.0 {
+        return stroke;
+    }
+    if dash_pattern(style).is_none() {
+        let (left, right) = stroke_segments(&points, true, half, style, &mut stroke);
+        stroke.outlines.extend([left, right]);
+        return stroke;
+    }
+
+    let start = points[0];
+    points.push(start);
+    let mut pieces = dash_pieces(&points, style);
+    // A dash running over the start continues the last one.
+    if pieces.len() > 1
+        && same_point(pieces[0][0], start)
+        && pieces
+            .last()
+            .is_some_and(|last| same_point(last[last.len() - 1], start))
+    {
+        let first = pieces.remove(0);
+        if let Some(last) = pieces.last_mut() {
+            last.extend_from_slice(&first[1..]);
+        }
+    }
+    for piece in pieces {
+        stroke_piece(&piece, half, style, &mut stroke);
+    }
+    stroke
+}
+
+fn same_point(a: Vec2, b: Vec2) -> bool {
+    (a - b).length() <= 1e-3
+}
+
+fn dedup(points: &[Vec2]) -> Vec<Vec2> {
+    let mut out: Vec<Vec2> = Vec::with_capacity(points.len());
+    for &p in points {
+        if out.last().is_none_or(|&last| !same_point(p, last)) {
+            out.push(p);
+        }
+    }
+    out
+}
+
+/// Even dash pattern of `style`; `None` for a solid stroke.
+fn dash_pattern(style: &StrokeStyle) -> Option<Vec<f32>> {
+    let mut pattern = style.dashes.clone();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_slice(&style.dashes);
+    }
+    let total: f32 = pattern.iter().sum();
+    let valid = pattern.iter().all(|d| *d >= 0.0 && d.is_finite());
+    (valid && total > 1e-3).then_some(pattern)
+}
+
+/// Split a path into the pieces covered by dashes.
+fn dash_pieces(points: &[Vec2], style: &StrokeStyle) -> Vec<Vec<Vec2>> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+    let Some(pattern) = dash_pattern(style) else {
+        return vec![points.to_vec()];
+    };
+    let total: f32 = pattern.iter().sum();
+
+    let mut index = 0;
+    let mut on = true;
+    let mut phase = style.dash_offset.rem_euclid(total);
+    while phase >= pattern[index] {
+        phase -= pattern[index];
+        index = (index + 1) % pattern.len();
+        on = !on;
+    }
+    let mut remaining = pattern[index] - phase;
+
+    let mut pieces = Vec::new();
+    let mut current = if on { vec![points[0]] } else { Vec::new() };
+    for segment in points.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let length = (b - a).length();
+        let mut t = 0.0;
+        while length - t > remaining {
+            t += remaining;
+            let p = a + (b - a) / length * t;
+            if on {
+                current.push(p);
+                pieces.push(std::mem::take(&mut current));
+            } else {
+                current = vec![p];
+            }
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+            on = !on;
+        }
+        remaining -= length - t;
+        if on {
+            current.push(b);
+        }
+    }
+    if on {
+        pieces.push(current);
+    }
+
+    pieces
+        .into_iter()
+        .map(|piece| dedup(&piece))
+        .filter(|piece| piece.len() >= 2)
+        .collect()
+}
+
+fn stroke_piece(points: &[Vec2], half: f32, style: &StrokeStyle, stroke: &mut Stroke) {
+    let (left, right) = stroke_segments(points, false, half, style, stroke);
+
+    let last = points.len() - 1;
+    let end_cap = cap_points(
+        points[last],
+        (points[last] - points[last - 1]).normalize(),
+        half,
+        style.cap,
+    );
+    let start_cap = cap_points(
+        points[0],
+        (points[0] - points[1]).normalize(),
+        half,
+        style.cap,
+    );
+    for (center, cap) in [(points[last], &end_cap), (points[0], &start_cap)] {
+        for edge in cap.windows(2) {
+            stroke.triangles.extend([center, edge[0], edge[1]]);
+        }
+    }
+
+    // The end cap runs from the left rim to the right one, the start cap back.
+    let mut outline = left;
+    outline.extend(inner_of(&end_cap));
+    outline.extend(right.into_iter().rev());
+    outline.extend(inner_of(&start_cap));
+    stroke.outlines.push(outline);
+}
+
+/// Caps of a path of zero length at `point`: a dot for round caps, a square for
+/// square caps and nothing for butt caps.
+fn stroke_dot(point: Vec2, dir: Vec2, half: f32, cap: LineCap, stroke: &mut Stroke) {
+    if cap == LineCap::Butt {
+        return;
+    }
+    let end_cap = cap_points(point, dir, half, cap);
+    let start_cap = cap_points(point, -dir, half, cap);
+    let mut outline = end_cap.clone();
+    outline.extend(inner_of(&start_cap));
+    for edge in outline.windows(2) {
+        stroke.triangles.extend([point, edge[0], edge[1]]);
+    }
+    stroke
+        .triangles
+        .extend([point, outline[outline.len() - 1], outline[0]]);
+    stroke.outlines.push(outline);
+}
+
+/// Bodies and joins of the segments through `points`, also joining the last point
+/// back to the first one when `closed`. Returns the left and right rims, from the
+/// first point to the last.
+///
+/// Each body runs between the rim points of its two ends, so bodies never overlap
+/// each other or the joins: the inner side of a turn ends on the crossing of the
+/// inner edges.
+fn stroke_segments(
+    points: &[Vec2],
+    closed: bool,
+    half: f32,
+    style: &StrokeStyle,
+    stroke: &mut Stroke,
+) -> (Vec<Vec2>, Vec<Vec2>) {
+    let count = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let directions: Vec<Vec2> = (0..count)
+        .map(|i| (points[(i + 1) % points.len()] - points[i]).normalize())
+        .collect();
+    let normals: Vec<Vec2> = directions.iter().map(|d| Vec2::new(-d.y, d.x)).collect();
+
+    // Left and right rim points at every point, from the incoming segment's side to
+    // the outgoing one's.
+    let mut rims: Vec<(Vec<Vec2>, Vec<Vec2>)> = Vec::with_capacity(points.len());
+    for (i, &p) in points.iter().enumerate() {
+        if !closed && (i == 0 || i == count) {
+            let n = normals[i.min(count - 1)] * half;
+            rims.push((vec![p + n], vec![p - n]));
+            continue;
+        }
+        let prev = (i + count - 1) % count;
+        let (n0, n1) = (normals[prev], normals[i]);
+        let turn = cross(directions[prev], directions[i]);
+        if turn.abs() <= 1e-6 && directions[prev] * directions[i] > 0.0 {
+            rims.push((vec![p + n1 * half], vec![p - n1 * half]));
+            continue;
+        }
+        // Turning left, the outer side is on the right.
+        let side: f32 = if turn > 0.0 { -1.0 } else { 1.0 };
+        let join = join_points(p, n0 * side, n1 * side, half, style);
+        let inner = inner_points(p, n0 * -side, n1 * -side, half);
+        // Fill the gap between the two bodies: from the inner crossing when there is
+        // one, otherwise around the point itself.
+        if let [crossing] = inner[..] {
+            for edge in join.windows(2) {
+                stroke.triangles.extend([crossing, edge[0], edge[1]]);
+            }
+        } else {
+            let mut ring = vec![inner[0]];
+            ring.extend_from_slice(&join);
+            ring.extend([inner[1], inner[0]]);
+            for edge in ring.windows(2) {
+                stroke.triangles.extend([p, edge[0], edge[1]]);
+            }
+        }
+        rims.push(if side > 0.0 {
+            (join, inner)
+        } else {
+            (inner, join)
+        });
+    }
+
+    // Segment bodies.
+    for i in 0..count {
+        let (start, end) = (&rims[i], &rims[(i + 1) % points.len()]);
+        let (start_left, start_right) = (start.0[start.0.len() - 1], start.1[start.1.len() - 1]);
+        let (end_left, end_right) = (end.0[0], end.1[0]);
+        stroke.triangles.extend([
+            start_left,
+            end_left,
+            end_right,
+            end_right,
+            start_right,
+            start_left,
+        ]);
+    }
+
+    rims.into_iter()
+        .fold((Vec::new(), Vec::new()), |(mut left, mut right), rim| {
+            left.extend(rim.0);
+            right.extend(rim.1);
+            (left, right)
+        })
+}
+
+/// Outer side of a join, from the edge of the incoming segment to the outgoing one.
+/// `n0` and `n1` are unit normals pointing to the outer side.
+fn join_points(p: Vec2, n0: Vec2, n1: Vec2, half: f32, style: &StrokeStyle) -> Vec<Vec2> {
+    let (a, b) = (p + n0 * half, p + n1 * half);
+    match style.join {
+        LineJoin::Bevel => vec![a, b],
+        LineJoin::Miter => {
+            let cos = (n0 + n1).normalize() * n1;
+            if cos > 1e-6 && 1.0 / cos <= style.miter_limit {
+                vec![a, p + (n0 + n1).normalize() * (half / cos), b]
+            } else {
+                vec![a, b]
+            }
+        }
+        LineJoin::Round => {
+            let sweep = cross(n0, n1).atan2(n0 * n1);
+            arc(p, n0.y.atan2(n0.x), sweep, half)
+        }
+    }
+}
+
+/// Inner side of a join: where both edges cross, or both edge ends when they cross
+/// too far away (nearly reversing paths).
+fn inner_points(p: Vec2, n0: Vec2, n1: Vec2, half: f32) -> Vec<Vec2> {
+    let cos = (n0 + n1).normalize() * n1;
+    if cos > 0.25 {
+        vec![p + (n0 + n1).normalize() * (half / cos)]
+    } else {
+        vec![p + n0 * half, p + n1 * half]
+    }
+}
+
+/// Cap around `end`, going outwards along `dir`: from the left edge to the right one.
+fn cap_points(end: Vec2, dir: Vec2, half: f32, cap: LineCap) -> Vec<Vec2> {
+    let n = Vec2::new(-dir.y, dir.x) * half;
+    match cap {
+        LineCap::Butt => vec![end + n, end - n],
+        LineCap::Square => {
+            let out = dir * half;
+            vec![end + n, end + n + out, end - n + out, end - n]
+        }
+        LineCap::Round => arc(end, n.y.atan2(n.x), -PI, half),
+    }
+}
+
+fn inner_of(points: &[Vec2]) -> &[Vec2] {
+    &points[1..points.len() - 1]
+}
+
+/// Points on an arc of `radius` around `center`, both ends included.
+fn arc(center: Vec2, start: f32, sweep: f32, radius: f32) -> Vec<Vec2> {
+    let steps = (sweep.abs() / (PI / 12.0)).ceil().max(1.0) as usize;
+    (0..=steps)
+        .map(|i| {
+            let angle = start + sweep * i as f32 / steps as f32;
+            center + Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn point_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    let d0 = cross(b - a, p - a);
+    let d1 = cross(c - b, p - b);
+    let d2 = cross(a - c, p - c);
+    let negative = d0 < 0.0 || d1 < 0.0 || d2 < 0.0;
+    let positive = d0 > 0.0 || d1 > 0.0 || d2 > 0.0;
+    !(negative && positive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineCap, LineJoin, StrokeStyle, stroke_path};
+    use crate::math::Vec2;
+
+    fn bounds(points: &[Vec2]) -> (Vec2, Vec2) {
+        points.iter().fold(
+            (Vec2::new(f32::MAX, f32::MAX), Vec2::new(f32::MIN, f32::MIN)),
+            |(min, max), p| {
+                (
+                    Vec2::new(min.x.min(p.x), min.y.min(p.y)),
+                    Vec2::new(max.x.max(p.x), max.y.max(p.y)),
+                )
+            },
+        )
+    }
+
+    fn area(triangles: &[Vec2]) -> f32 {
+        triangles
+            .chunks_exact(3)
+            .map(|t| {
+                let (a, b) = (t[1] - t[0], t[2] - t[0]);
+                (a.x * b.y - a.y * b.x).abs() * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn strokes_apply_joins_caps_and_dashes() {
+        // Right angle: the miter reaches the corner of the offset edges, a bevel cuts it.
+        let corner = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+        ];
+        let miter = stroke_path(&corner, 2.0, &StrokeStyle::default());
+        assert_eq!(miter.outlines.len(), 1);
+        assert!(miter.contains(Vec2::new(10.9, -0.9)));
+        let bevel = stroke_path(&corner, 2.0, &StrokeStyle::new().with_miter_limit(1.2));
+        assert!(!bevel.contains(Vec2::new(10.9, -0.9)));
+        let round = stroke_path(&corner, 2.0, &StrokeStyle::new().with_join(LineJoin::Round));
+        assert!(round.contains(Vec2::new(10.6, -0.6)));
+        assert!(!round.contains(Vec2::new(10.9, -0.9)));
+        // The L covers 22 + 18 units; overlapping triangles on the inner side of the
+        // turn would add up to more.
+        assert!((area(&miter.triangles) - 40.0).abs() < 1e-3);
+
+        // Square caps extend the ends by half the thickness.
+        let segment = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0)];
+        let butt = stroke_path(&segment, 2.0, &StrokeStyle::default());
+        assert_eq!(bounds(&butt.outlines[0]).0.x, 0.0);
+        let square = stroke_path(&segment, 2.0, &StrokeStyle::new().with_cap(LineCap::Square));
+        let (min, max) = bounds(&square.outlines[0]);
+        assert_eq!((min.x, max.x), (-1.0, 11.0));
+
+        // Dashes of 3 with gaps of 2, shifted by 1: [0, 2], [4, 7], [9, 10].
+        let dashed = StrokeStyle::new().with_dashes([3.0, 2.0], 1.0);
+        let stroke = stroke_path(&segment, 2.0, &dashed);
+        let spans: Vec<(f32, f32)> = stroke
+            .outlines
+            .iter()
+            .map(|outline| {
+                let (min, max) = bounds(outline);
+                (min.x, max.x)
+            })
+            .collect();
+        assert_eq!(spans, [(0.0, 2.0), (4.0, 7.0), (9.0, 10.0)]);
+        assert!(stroke.contains(Vec2::new(5.0, 0.5)));
+        assert!(!stroke.contains(Vec2::new(3.0, 0.0)));
+
+        // A path too short to have a direction still shows its caps.
+        let dot = [Vec2::new(5.0, 5.0), Vec2::new(5.0, 5.0)];
+        assert!(
+            stroke_path(&dot, 2.0, &StrokeStyle::default())
+                .triangles
+                .is_empty()
+        );
+        let square = stroke_path(&dot, 2.0, &StrokeStyle::new().with_cap(LineCap::Square));
+        assert_eq!(
+            bounds(&square.outlines[0]),
+            (Vec2::new(4.0, 4.0), Vec2::new(6.0, 6.0))
+        );
+        assert!((area(&square.triangles) - 4.0).abs() < 1e-3);
+    }
+}
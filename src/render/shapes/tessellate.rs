@@ -0,0 +1,182 @@
+use crate::math::vec2::Vec2;
+
+use super::path::FillRule;
+
+/// Non-horizontal edge of a contour, stored top to bottom.
+struct Edge {
+    top: Vec2,
+    bottom: Vec2,
+    /// `1` if the contour goes down along the edge, `-1` if it goes up.
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        self.top.x + (self.bottom.x - self.top.x) * t
+    }
+
+    /// Height where both edges cross, strictly inside both.
+    fn crossing_y(&self, other: &Edge) -> Option<f32> {
+        let r = self.bottom - self.top;
+        let s = other.bottom - other.top;
+        let denom = cross(r, s);
+        if denom.abs() <= 1e-9 {
+            return None;
+        }
+        let q = other.top - self.top;
+        let t = cross(q, s) / denom;
+        let u = cross(q, r) / denom;
+        (t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0).then_some(self.top.y + r.y * t)
+    }
+}
+
+/// Triangle list covering the inside of the closed `contours` under `rule`.
+///
+/// Contours may be concave, intersect themselves or each other, and cut holes into
+/// one another. The area is split into horizontal bands at every vertex and edge
+/// crossing; inside a band no edges cross, so each span between two edges is an
+/// exact trapezoid.
+pub(super) fn fill_contours(contours: &[Vec<Vec2>], rule: FillRule) -> Vec<Vec2> {
+    let mut edges = Vec::new();
+    for contour in contours.iter().filter(|contour| contour.len() >= 3) {
+        for (i, &a) in contour.iter().enumerate() {
+            let b = contour[(i + 1) % contour.len()];
+            if a.y < b.y {
+                edges.push(Edge {
+                    top: a,
+                    bottom: b,
+                    winding: 1,
+                });
+            } else if a.y > b.y {
+                edges.push(Edge {
+                    top: b,
+                    bottom: a,
+                    winding: -1,
+                });
+            }
+        }
+    }
+
+    let mut ys: Vec<f32> = edges.iter().flat_map(|e| [e.top.y, e.bottom.y]).collect();
+    for (i, a) in edges.iter().enumerate() {
+        ys.extend(edges[i + 1..].iter().filter_map(|b| a.crossing_y(b)));
+    }
+    ys.sort_by(|a, b| a.total_cmp(b));
+    ys.dedup_by(|a, b| (*a - *b).abs() <= 1e-5);
+
+    let mut triangles = Vec::new();
+    let mut spans: Vec<(f32, f32, f32, i32)> = Vec::new();
+    for band in ys.windows(2) {
+        let (y0, y1) = (band[0], band[1]);
+        let mid = (y0 + y1) * 0.5;
+        spans.clear();
+        spans.extend(
+            edges
+                .iter()
+                .filter(|e| e.top.y <= mid && e.bottom.y >= mid)
+                .map(|e| (e.x_at(mid), e.x_at(y0), e.x_at(y1), e.winding)),
+        );
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        for pair in spans.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            winding += left.3;
+            let inside = match rule {
+                FillRule::NonZero => winding != 0,
+                FillRule::EvenOdd => winding % 2 != 0,
+            };
+            if inside {
+                let top_left = Vec2::new(left.1, y0);
+                let top_right = Vec2::new(right.1, y0);
+                let bottom_right = Vec2::new(right.2, y1);
+                let bottom_left = Vec2::new(left.2, y1);
+                triangles.extend([
+                    top_left,
+                    top_right,
+                    bottom_right,
+                    bottom_right,
+                    bottom_left,
+                    top_left,
+                ]);
+            }
+        }
+    }
+
+    triangles
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fill_contours;
+    use crate::math::Vec2;
+    use crate::render::FillRule;
+
+    fn area(triangles: &[Vec2]) -> f32 {
+        triangles
+            .chunks_exact(3)
+            .map(|t| {
+                let (a, b) = (t[1] - t[0], t[2] - t[0]);
+                (a.x * b.y - a.y * b.x).abs() * 0.5
+            })
+            .sum()
+    }
+
+    fn square(min: f32, max: f32, clockwise: bool) -> Vec<Vec2> {
+        let mut points = vec![
+            Vec2::new(min, min),
+            Vec2::new(max, min),
+            Vec2::new(max, max),
+            Vec2::new(min, max),
+        ];
+        if !clockwise {
+            points.reverse();
+        }
+        points
+    }
+
+    #[test]
+    fn fills_concave_contours_holes_and_overlaps() {
+        // Concave "L": a fan from the first point would cover the notch.
+        let l_shape = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 4.0),
+            Vec2::new(4.0, 4.0),
+            Vec2::new(4.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let triangles = fill_contours(&[l_shape], FillRule::NonZero);
+        assert!((area(&triangles) - 64.0).abs() < 1e-3);
+
+        // A reversed inner square is a hole under both rules.
+        let outer = square(0.0, 10.0, true);
+        let hole = square(2.0, 8.0, false);
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let triangles = fill_contours(&[outer.clone(), hole.clone()], rule);
+            assert!((area(&triangles) - 64.0).abs() < 1e-3);
+        }
+
+        // Same direction: only even-odd cuts the hole.
+        let inner = square(2.0, 8.0, true);
+        let nonzero = fill_contours(&[outer.clone(), inner.clone()], FillRule::NonZero);
+        assert!((area(&nonzero) - 100.0).abs() < 1e-3);
+        let even_odd = fill_contours(&[outer, inner], FillRule::EvenOdd);
+        assert!((area(&even_odd) - 64.0).abs() < 1e-3);
+
+        // Self-intersecting bow tie: two triangles meeting at (5, 5).
+        let bow_tie = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let triangles = fill_contours(&[bow_tie], FillRule::NonZero);
+        assert!((area(&triangles) - 50.0).abs() < 1e-3);
+    }
+}
@@ -3,13 +3,17 @@ use crate::math::color::Color;
 use crate::math::vec2::Vec2;
 use crate::render::Vertex;
 use crate::render::context::RenderContext;
+use crate::render::gradient::Gradient;
 
+use super::fill::fill_gradient;
 use super::{Collider, Drawable, ShapeRef, Transform2d};
 
 pub struct Triangle {
     pub transform: Transform,
     pub local_points: [Vec2; 3],
     pub color: Color,
+    /// Replaces `color` when set.
+    pub gradient: Option<Gradient>,
     pub size: Vec2,
 }
 
@@ -28,10 +32,17 @@ impl Triangle {
             transform: Transform::at(position),
             local_points,
             color,
+            gradient: None,
             size,
         }
     }
 
+    /// Builder: Fill with `gradient` instead of `color`
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
     fn transform_point(&self, local: Vec2) -> Vec2 {
         self.transform.transform_point(local, self.size)
     }
@@ -59,6 +70,17 @@ impl Triangle {
 
 impl Drawable for Triangle {
     fn draw(&self, ctx: &mut RenderContext) {
+        if let Some(gradient) = &self.gradient {
+            fill_gradient(
+                ctx,
+                gradient,
+                &self.transform,
+                self.size,
+                &self.local_points,
+            );
+            return;
+        }
+
         let color = self.color.to_linear_rgba();
         let [v1, v2, v3] = self.world_points();
 
@@ -0,0 +1,754 @@
+use crate::backend::surface_provider::SurfaceProvider;
+use crate::backend::window::WindowConfig;
+use crate::core::assets::{ImageAsset, ImageId, SamplerOptions, check_rgba8};
+use crate::math::Vec2;
+use crate::render::blend::BlendMode;
+use crate::render::clip::ClipRect;
+use crate::render::gradient::Gradient;
+use crate::render::material::MaterialId;
+use crate::render::renderer::{RenderError, RenderResult, Renderer, validate_region};
+use crate::render::{DrawList, GradientVertex, SdfVertex, SpriteDrawData, TexturedVertex, Vertex};
+use std::collections::HashMap;
+
+mod raster;
+
+use raster::{Framebuffer, RasterVertex, SoftwareTexture};
+
+/// Reference renderer that rasterizes everything on the CPU into an in-memory framebuffer.
+///
+/// It follows the same conventions as `WgpuRenderer` (NDC vertices, sRGB textures,
+/// linear-space alpha blending, draws in submission order) so its output can be used
+/// to diff GPU frames. It never presents to a window: `init` only picks up the surface
+/// size, and frames are read back with `read_pixels` / `read_image`.
+pub struct SoftwareRenderer {
+    clear_color: [f32; 4],
+    framebuffer: Framebuffer,
+    /// Draws queued for the next `present`, in submission order.
+    draws: Vec<SoftwareDraw>,
+    textures: HashMap<ImageId, SoftwareTexture>,
+    canvases: HashMap<ImageId, Framebuffer>,
+    capture_requested: bool,
+    captured: Option<ImageAsset>,
+}
+
+enum SoftwareDraw {
+    Shapes(Vec<Vertex>, BlendMode),
+    Sprite(SpriteDraw),
+    Mesh(MeshTriangles),
+    Gradient(Vec<GradientVertex>, Gradient, BlendMode),
+    Sdf(Vec<SdfVertex>, BlendMode),
+    Clip(Option<ClipRect>),
+    /// Stencil masks as triangles in NDC.
+    Masks(Vec<Vec<Vertex>>),
+}
+
+struct SpriteDraw {
+    texture_id: ImageId,
+    /// Quad corners in NDC: top-left, top-right, bottom-right, bottom-left.
+    corners: [[f32; 2]; 4],
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+    color: [f32; 4],
+    blend: BlendMode,
+}
+
+struct MeshTriangles {
+    /// `None` draws vertex colors only.
+    texture_id: Option<ImageId>,
+    /// Three NDC vertices per triangle, expanded from the mesh indices.
+    vertices: Vec<TexturedVertex>,
+    blend: BlendMode,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            clear_color: [1.0, 1.0, 1.0, 1.0],
+            framebuffer: Framebuffer::new(width, height),
+            draws: Vec::new(),
+            textures: HashMap::new(),
+            canvases: HashMap::new(),
+            capture_requested: false,
+            captured: None,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.framebuffer.width(), self.framebuffer.height())
+    }
+
+    /// Read back the last presented frame as tightly packed sRGB RGBA8 rows.
+    pub fn read_pixels(&self) -> Vec<u8> {
+        self.framebuffer.to_rgba8()
+    }
+
+    /// Read back the last presented frame as an `ImageAsset`.
+    pub fn read_image(&self) -> ImageAsset {
+        let (width, height) = self.size();
+        ImageAsset {
+            width,
+            height,
+            data: self.read_pixels(),
+            sampler: SamplerOptions::default(),
+        }
+    }
+
+    /// Map an NDC position to framebuffer pixel space (Y-down).
+    fn ndc_to_pixel(&self, ndc: [f32; 2]) -> [f32; 2] {
+        let (w, h) = self.size();
+        [
+            (ndc[0] + 1.0) * 0.5 * w as f32,
+            (1.0 - ndc[1]) * 0.5 * h as f32,
+        ]
+    }
+
+    /// Rasterize every queued draw into the current framebuffer.
+    fn flush_draws(&mut self) {
+        for draw in std::mem::take(&mut self.draws) {
+            match draw {
+                SoftwareDraw::Shapes(vertices, blend) => self.draw_shapes(&vertices, blend),
+                SoftwareDraw::Sprite(sprite) => self.draw_sprite_quad(&sprite),
+                SoftwareDraw::Mesh(mesh) => self.draw_mesh_triangles(&mesh),
+                SoftwareDraw::Gradient(vertices, gradient, blend) => {
+                    self.draw_gradient(&vertices, &gradient, blend)
+                }
+                SoftwareDraw::Sdf(vertices, blend) => self.draw_sdf(&vertices, blend),
+                SoftwareDraw::Clip(rect) => self.framebuffer.set_clip(rect),
+                SoftwareDraw::Masks(masks) => {
+                    let masks: Vec<Vec<[f32; 2]>> = masks
+                        .iter()
+                        .map(|mask| mask.iter().map(|v| self.ndc_to_pixel(v.pos)).collect())
+                        .collect();
+                    self.framebuffer.set_masks(&masks);
+                }
+            }
+        }
+        self.framebuffer.set_clip(None);
+        self.framebuffer.set_masks(&[]);
+    }
+
+    fn draw_shapes(&mut self, vertices: &[Vertex], blend: BlendMode) {
+        for tri in vertices.chunks_exact(3) {
+            let tri = [0, 1, 2].map(|i| RasterVertex {
+                pos: self.ndc_to_pixel(tri[i].pos),
+                uv: [0.0, 0.0],
+                color: tri[i].color,
+            });
+            self.framebuffer.fill_triangle(tri, blend, |_, color| color);
+        }
+    }
+
+    fn draw_sprite_quad(&mut self, draw: &SpriteDraw) {
+        let [tl, tr, br, bl] = draw.corners.map(|c| self.ndc_to_pixel(c));
+        let Some(texture) = self.textures.get(&draw.texture_id) else {
+            return;
+        };
+
+        let (uv_min, uv_max) = (draw.uv_min, draw.uv_max);
+        let vertex = |pos, uv| RasterVertex {
+            pos,
+            uv,
+            color: draw.color,
+        };
+
+        // Same triangle split as the GPU sprite quad.
+        let triangles = [
+            [
+                vertex(tl, uv_min),
+                vertex(tr, [uv_max[0], uv_min[1]]),
+                vertex(br, uv_max),
+            ],
+            [
+                vertex(tl, uv_min),
+                vertex(br, uv_max),
+                vertex(bl, [uv_min[0], uv_max[1]]),
+            ],
+        ];
+
+        for tri in triangles {
+            self.framebuffer
+                .fill_triangle(tri, draw.blend, |uv, color| {
+                    let texel = texture.sample(uv);
+                    [
+                        texel[0] * color[0],
+                        texel[1] * color[1],
+                        texel[2] * color[2],
+                        texel[3] * color[3],
+                    ]
+                });
+        }
+    }
+
+    fn draw_gradient(
+        &mut self,
+        vertices: &[GradientVertex],
+        gradient: &Gradient,
+        blend: BlendMode,
+    ) {
+        for tri in vertices.chunks_exact(3) {
+            // The interpolated UV carries the shape-relative position.
+            let tri = [0, 1, 2].map(|i| RasterVertex {
+                pos: self.ndc_to_pixel(tri[i].pos),
+                uv: tri[i].local,
+                color: [0.0; 4],
+            });
+            self.framebuffer.fill_triangle(tri, blend, |local, _| {
+                gradient.sample(Vec2::new(local[0], local[1]))
+            });
+        }
+    }
+
+    fn draw_sdf(&mut self, vertices: &[SdfVertex], blend: BlendMode) {
+        let distance = |a: [f32; 2], b: [f32; 2]| (a[0] - b[0]).hypot(a[1] - b[1]);
+        for shape in vertices.chunks_exact(3) {
+            let tri = [0, 1, 2].map(|i| RasterVertex {
+                pos: self.ndc_to_pixel(shape[i].pos),
+                uv: shape[i].local,
+                color: [0.0; 4],
+            });
+            // Local units per pixel along the triangle's edges: the smoothed edge width.
+            let local = distance(tri[0].uv, tri[1].uv) + distance(tri[0].uv, tri[2].uv);
+            let pixels = distance(tri[0].pos, tri[1].pos) + distance(tri[0].pos, tri[2].pos);
+            if pixels <= f32::EPSILON {
+                continue;
+            }
+            let (shape, aa) = (shape[0], local / pixels);
+            self.framebuffer
+                .fill_triangle(tri, blend, |local, _| shape.shade(local, aa));
+        }
+    }
+
+    fn draw_mesh_triangles(&mut self, mesh: &MeshTriangles) {
+        let texture = match mesh.texture_id {
+            Some(id) => match self.textures.get(&id) {
+                Some(texture) => Some(texture),
+                None => return,
+            },
+            None => None,
+        };
+        for tri in mesh.vertices.chunks_exact(3) {
+            let tri = [0, 1, 2].map(|i| RasterVertex {
+                pos: self.ndc_to_pixel(tri[i].pos),
+                uv: tri[i].uv,
+                color: tri[i].color,
+            });
+            self.framebuffer
+                .fill_triangle(tri, mesh.blend, |uv, color| {
+                    let Some(texture) = texture else {
+                        return color;
+                    };
+                    let texel = texture.sample(uv);
+                    [
+                        texel[0] * color[0],
+                        texel[1] * color[1],
+                        texel[2] * color[2],
+                        texel[3] * color[3],
+                    ]
+                });
+        }
+    }
+}
+
+impl Default for SoftwareRenderer {
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn init(
+        &mut self,
+        surface: &dyn SurfaceProvider,
+        _config: Option<&WindowConfig>,
+    ) -> RenderResult<()> {
+        self.resize(surface.size());
+        Ok(())
+    }
+
+    fn resize(&mut self, new_size: (u32, u32)) {
+        self.framebuffer = Framebuffer::new(new_size.0, new_size.1);
+    }
+
+    fn present(&mut self) -> RenderResult<()> {
+        self.framebuffer.clear(self.clear_color);
+        self.flush_draws();
+        if std::mem::take(&mut self.capture_requested) {
+            self.captured = Some(self.read_image());
+        }
+        Ok(())
+    }
+
+    fn capture_next_frame(&mut self) {
+        self.capture_requested = true;
+    }
+
+    fn take_captured_frame(&mut self) -> Option<ImageAsset> {
+        self.captured.take()
+    }
+
+    fn set_clear_color(&mut self, rgba: [f32; 4]) {
+        self.clear_color = rgba;
+    }
+
+    fn submit(&mut self, vertices: &[Vertex]) {
+        self.submit_shapes(vertices, None, BlendMode::Alpha);
+    }
+
+    /// Materials are not supported: shapes always use their vertex colors.
+    fn submit_shapes(
+        &mut self,
+        vertices: &[Vertex],
+        _material: Option<MaterialId>,
+        blend: BlendMode,
+    ) {
+        match self.draws.last_mut() {
+            Some(SoftwareDraw::Shapes(pending, mode)) if *mode == blend => {
+                pending.extend_from_slice(vertices)
+            }
+            _ if !vertices.is_empty() => self
+                .draws
+                .push(SoftwareDraw::Shapes(vertices.to_vec(), blend)),
+            _ => {}
+        }
+    }
+
+    fn set_clip_rect(&mut self, rect: Option<ClipRect>) {
+        self.draws.push(SoftwareDraw::Clip(rect));
+    }
+
+    fn set_stencil_masks(&mut self, masks: &[&[Vertex]]) {
+        let masks = masks.iter().map(|mask| mask.to_vec()).collect();
+        self.draws.push(SoftwareDraw::Masks(masks));
+    }
+
+    fn upload_image(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> RenderResult<()> {
+        self.upload_image_with_sampler(id, width, height, data, &SamplerOptions::default())
+    }
+
+    fn upload_image_with_sampler(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        check_rgba8(width, height, data).map_err(RenderError::InvalidTexture)?;
+
+        self.textures.insert(
+            id,
+            SoftwareTexture::from_rgba8(width, height, data, *sampler),
+        );
+        Ok(())
+    }
+
+    fn update_image_region(
+        &mut self,
+        id: ImageId,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> RenderResult<()> {
+        let texture = self.textures.get_mut(&id).ok_or_else(|| {
+            RenderError::InvalidTexture(format!("no image uploaded for {:?}", id))
+        })?;
+        validate_region(texture.size(), (x, y, width, height), data)?;
+        texture.write_region(x, y, width, data);
+        Ok(())
+    }
+
+    fn unload_image(&mut self, id: ImageId) {
+        self.textures.remove(&id);
+        self.canvases.remove(&id);
+    }
+
+    fn create_canvas(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        let (width, height) = (width.max(1), height.max(1));
+        let unchanged = self.canvases.get(&id).is_some_and(|fb| {
+            fb.width() == width
+                && fb.height() == height
+                && self.textures.get(&id).map(|t| t.sampler()) == Some(*sampler)
+        });
+        if !unchanged {
+            let canvas = match self.canvases.remove(&id) {
+                Some(fb) if fb.width() == width && fb.height() == height => fb,
+                _ => Framebuffer::new(width, height),
+            };
+            self.textures.insert(
+                id,
+                SoftwareTexture::from_rgba8(width, height, &canvas.to_rgba8(), *sampler),
+            );
+            self.canvases.insert(id, canvas);
+        }
+        Ok(())
+    }
+
+    fn render_to_canvas(
+        &mut self,
+        id: ImageId,
+        clear_color: Option<[f32; 4]>,
+        list: &DrawList<'_>,
+    ) -> RenderResult<()> {
+        let mut canvas = self.canvases.remove(&id).ok_or_else(|| {
+            RenderError::InvalidTexture(format!("no canvas created for {:?}", id))
+        })?;
+        let size = (canvas.width(), canvas.height());
+
+        // Draw into the canvas with the frame's queued draws kept aside.
+        std::mem::swap(&mut self.framebuffer, &mut canvas);
+        let frame_draws = std::mem::take(&mut self.draws);
+        if let Some(color) = clear_color {
+            self.framebuffer.clear(color);
+        }
+        self.submit_draw_list(list, size);
+        // Like on the GPU, a canvas cannot sample itself while being drawn.
+        self.draws.retain(|draw| match draw {
+            SoftwareDraw::Sprite(sprite) => sprite.texture_id != id,
+            SoftwareDraw::Mesh(mesh) => mesh.texture_id != Some(id),
+            _ => true,
+        });
+        self.flush_draws();
+        self.draws = frame_draws;
+        std::mem::swap(&mut self.framebuffer, &mut canvas);
+
+        // Round-trip through RGBA8 to match the 8-bit sRGB canvas textures on the GPU.
+        let sampler = self
+            .textures
+            .get(&id)
+            .map(|texture| texture.sampler())
+            .unwrap_or_default();
+        self.textures.insert(
+            id,
+            SoftwareTexture::from_rgba8(size.0, size.1, &canvas.to_rgba8(), sampler),
+        );
+        self.canvases.insert(id, canvas);
+        Ok(())
+    }
+
+    fn draw_mesh(
+        &mut self,
+        vertices: &[TexturedVertex],
+        indices: &[u32],
+        texture: Option<ImageId>,
+        blend: BlendMode,
+    ) {
+        if texture.is_some_and(|id| !self.textures.contains_key(&id)) {
+            return;
+        }
+        self.draws.push(SoftwareDraw::Mesh(MeshTriangles {
+            texture_id: texture,
+            // Like `RenderContext::draw_mesh`, drop triangles with an out-of-range index.
+            vertices: indices
+                .chunks_exact(3)
+                .filter(|tri| tri.iter().all(|&i| (i as usize) < vertices.len()))
+                .flatten()
+                .map(|&i| vertices[i as usize])
+                .collect(),
+            blend,
+        }));
+    }
+
+    fn fill_gradient(
+        &mut self,
+        vertices: &[GradientVertex],
+        gradient: &Gradient,
+        blend: BlendMode,
+    ) {
+        self.draws.push(SoftwareDraw::Gradient(
+            vertices.to_vec(),
+            gradient.clone(),
+            blend,
+        ));
+    }
+
+    fn draw_sdf_shapes(&mut self, vertices: &[SdfVertex], blend: BlendMode) {
+        self.draws.push(SoftwareDraw::Sdf(vertices.to_vec(), blend));
+    }
+
+    fn draw_sprites(&mut self, sprites: &[SpriteDrawData], viewport_size: (u32, u32)) {
+        let (w, h) = (viewport_size.0.max(1) as f32, viewport_size.1.max(1) as f32);
+        let to_ndc = |p: crate::math::Vec2| [(p.x / w) * 2.0 - 1.0, 1.0 - (p.y / h) * 2.0];
+
+        for sprite in sprites {
+            if !self.textures.contains_key(&sprite.image_id) {
+                continue;
+            }
+
+            self.draws.push(SoftwareDraw::Sprite(SpriteDraw {
+                texture_id: sprite.image_id,
+                corners: sprite.textured_corners().map(to_ndc),
+                uv_min: sprite.uv_min.to_array(),
+                uv_max: sprite.uv_max.to_array(),
+                color: sprite.tint.to_linear_rgba(),
+                blend: sprite.blend,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SoftwareRenderer;
+    use crate::core::assets::ImageId;
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::{headless, pixel};
+    use crate::render::{
+        BlendMode, Circle, Drawable, Mesh2d, MeshVertex, Rectangle, RenderContext, Renderer,
+        SpriteDrawData, TexturedVertex,
+    };
+
+    fn render(renderer: &mut dyn Renderer, ctx: &RenderContext) {
+        if let Some(color) = ctx.clear_color {
+            renderer.set_clear_color(color.to_linear_rgba());
+        }
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+    }
+
+    #[test]
+    fn rectangle_edges_follow_pixel_centers() {
+        let mut renderer = SoftwareRenderer::new(16, 16);
+        let mut ctx = RenderContext::new((16, 16));
+        ctx.clear(Color::BLACK);
+        Rectangle::new(Vec2::new(4.0, 4.0), Vec2::new(8.0, 8.0), Color::BLUE).draw(&mut ctx);
+        render(&mut renderer, &ctx);
+
+        let data = renderer.read_pixels();
+        let covered = data.chunks(4).filter(|p| *p == [0, 0, 255, 255]).count();
+        assert_eq!(covered, 64);
+        assert_eq!(pixel(&data, 16, 4, 4), [0, 0, 255, 255]);
+        assert_eq!(pixel(&data, 16, 11, 11), [0, 0, 255, 255]);
+        assert_eq!(pixel(&data, 16, 12, 12), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn alpha_blending_happens_in_linear_space() {
+        let mut renderer = SoftwareRenderer::new(4, 4);
+        let mut ctx = RenderContext::new((4, 4));
+        ctx.clear(Color::BLACK);
+        let half_white = Color::new(1.0, 1.0, 1.0, 0.5);
+        Rectangle::new(Vec2::ZERO, Vec2::new(4.0, 4.0), half_white).draw(&mut ctx);
+        render(&mut renderer, &ctx);
+
+        // 0.5 linear encodes to ~0.735 in sRGB.
+        assert_eq!(
+            pixel(&renderer.read_pixels(), 4, 1, 1),
+            [188, 188, 188, 255]
+        );
+    }
+
+    #[test]
+    fn sprites_sample_uploaded_textures_and_skip_unknown_ids() {
+        let mut renderer = SoftwareRenderer::new(8, 8);
+        let texture = ImageId::new();
+        let green = [0u8, 255, 0, 255].repeat(4);
+        renderer.upload_image(texture, 2, 2, &green).unwrap();
+
+        let mut ctx = RenderContext::new((8, 8));
+        ctx.clear(Color::BLACK);
+        let mut sprite = SpriteDrawData::new(texture, 4, 4);
+        sprite.position = Vec2::new(4.0, 4.0);
+        ctx.draw_sprite(sprite.clone());
+        sprite.image_id = ImageId::new();
+        sprite.position = Vec2::new(0.0, 0.0);
+        ctx.draw_sprite(sprite);
+        render(&mut renderer, &ctx);
+
+        let data = renderer.read_pixels();
+        assert_eq!(pixel(&data, 8, 4, 4), [0, 255, 0, 255]);
+        assert_eq!(pixel(&data, 8, 2, 2), [0, 255, 0, 255]);
+        assert_eq!(pixel(&data, 8, 0, 0), [0, 0, 0, 255]);
+        assert_eq!(pixel(&data, 8, 7, 7), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn meshes_sample_textures_or_draw_vertex_colors() {
+        let mut renderer = SoftwareRenderer::new(8, 4);
+        let texture = ImageId::new();
+        let pixels = [[255u8, 0, 0, 255], [0, 0, 255, 255]].concat();
+        renderer.upload_image(texture, 2, 1, &pixels).unwrap();
+
+        // Two 4x4 quads: the left one textured, the right one untextured and green.
+        let quad = |x: f32, color: Color| {
+            let corner = |dx: f32, dy: f32| {
+                MeshVertex::new(Vec2::new(x + dx, dy), Vec2::new(dx / 4.0, dy / 4.0), color)
+            };
+            Mesh2d::new(
+                vec![
+                    corner(0.0, 0.0),
+                    corner(4.0, 0.0),
+                    corner(4.0, 4.0),
+                    corner(0.0, 4.0),
+                ],
+                vec![0, 1, 2, 0, 2, 3],
+            )
+        };
+        let mut ctx = RenderContext::new((8, 4));
+        ctx.clear(Color::BLACK);
+        quad(0.0, Color::WHITE).with_texture(texture).draw(&mut ctx);
+        quad(4.0, Color::rgb(0, 255, 0)).draw(&mut ctx);
+        render(&mut renderer, &ctx);
+
+        let data = renderer.read_pixels();
+        assert_eq!(pixel(&data, 8, 0, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&data, 8, 3, 2), [0, 0, 255, 255]);
+        assert_eq!(pixel(&data, 8, 6, 3), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn draws_interleave_in_submission_order_and_by_layer() {
+        let mut renderer = SoftwareRenderer::new(8, 8);
+        let texture = ImageId::new();
+        renderer
+            .upload_image(texture, 1, 1, &[0, 255, 0, 255])
+            .unwrap();
+        let mut sprite = SpriteDrawData::new(texture, 8, 8);
+        sprite.origin = Vec2::ZERO;
+
+        let mut ctx = RenderContext::new((8, 8));
+        ctx.clear(Color::BLACK);
+        // Left half: a rectangle submitted after the sprite covers it.
+        ctx.draw_sprite(sprite);
+        Rectangle::new(Vec2::ZERO, Vec2::new(4.0, 8.0), Color::RED).draw(&mut ctx);
+        // Right half: a rectangle on a lower layer stays under the sprite.
+        ctx.with_layer(-1, |ctx| {
+            Rectangle::new(Vec2::new(4.0, 0.0), Vec2::new(4.0, 8.0), Color::BLUE).draw(ctx);
+        });
+        render(&mut renderer, &ctx);
+
+        let data = renderer.read_pixels();
+        assert_eq!(pixel(&data, 8, 1, 4), [255, 0, 0, 255]);
+        assert_eq!(pixel(&data, 8, 6, 4), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn upload_rejects_mismatched_data_length() {
+        let mut renderer = SoftwareRenderer::new(4, 4);
+        assert!(
+            renderer
+                .upload_image(ImageId::new(), 2, 2, &[0; 3])
+                .is_err()
+        );
+        // Zero-sized textures are rejected like in the wgpu renderer.
+        assert!(renderer.upload_image(ImageId::new(), 0, 2, &[]).is_err());
+    }
+
+    #[test]
+    fn meshes_drop_triangles_with_out_of_range_indices() {
+        let mut renderer = SoftwareRenderer::new(2, 2);
+        let vertex = |x, y| TexturedVertex {
+            pos: [x, y],
+            uv: [0.0, 0.0],
+            color: [1.0; 4],
+        };
+        let vertices = [vertex(-1.0, -1.0), vertex(3.0, -1.0), vertex(-1.0, 3.0)];
+        renderer.draw_mesh(&vertices, &[0, 1, 2, 0, 1, 7], None, BlendMode::Alpha);
+        renderer.present().unwrap();
+        assert_eq!(pixel(&renderer.read_pixels(), 2, 1, 1), [255; 4]);
+    }
+
+    #[test]
+    fn blend_modes_match_wgpu_headless_output() {
+        let Some(mut gpu) = headless(6, 2) else {
+            return;
+        };
+        let mut cpu = SoftwareRenderer::new(6, 2);
+        let texture = ImageId::new();
+        gpu.upload_image(texture, 1, 1, &[255, 128, 0, 128])
+            .unwrap();
+        cpu.upload_image(texture, 1, 1, &[255, 128, 0, 128])
+            .unwrap();
+
+        // One column per mode: a shape on the top row, a sprite on the bottom row.
+        let mut ctx = RenderContext::new((6, 2));
+        ctx.clear(Color::rgb(100, 150, 200));
+        for (i, mode) in BlendMode::ALL.into_iter().enumerate() {
+            let x = i as f32;
+            ctx.with_blend_mode(mode, |ctx| {
+                let color = Color::rgba(255, 128, 0, 0.5);
+                Rectangle::new(Vec2::new(x, 0.0), Vec2::new(1.0, 1.0), color).draw(ctx);
+            });
+            let mut sprite = SpriteDrawData::new(texture, 1, 1);
+            sprite.origin = Vec2::ZERO;
+            sprite.position = Vec2::new(x, 1.0);
+            sprite.blend = mode;
+            ctx.draw_sprite(sprite);
+        }
+        render(&mut gpu, &ctx);
+        render(&mut cpu, &ctx);
+
+        let gpu_pixels = gpu.read_pixels().unwrap();
+        let cpu_pixels = cpu.read_pixels();
+        for (i, (a, b)) in gpu_pixels.chunks(4).zip(cpu_pixels.chunks(4)).enumerate() {
+            let close = a.iter().zip(b).all(|(x, y)| x.abs_diff(*y) <= 3);
+            assert!(close, "pixel {i}: wgpu {a:?}, software {b:?}");
+        }
+        // Additive brightens, multiply darkens.
+        assert!(pixel(&cpu_pixels, 6, 1, 0)[1] > pixel(&cpu_pixels, 6, 0, 0)[1]);
+        assert!(pixel(&cpu_pixels, 6, 2, 0)[1] < pixel(&cpu_pixels, 6, 0, 0)[1]);
+    }
+
+    #[test]
+    fn matches_wgpu_headless_output() {
+        let (w, h) = (64, 48);
+        let Some(mut gpu) = headless(w, h) else {
+            return;
+        };
+        let mut cpu = SoftwareRenderer::new(w, h);
+
+        let texture = ImageId::new();
+        let checker: Vec<u8> = (0..16)
+            .flat_map(|i| {
+                if (i % 4 + i / 4) % 2 == 0 {
+                    [255, 128, 0, 255]
+                } else {
+                    [20, 40, 220, 255]
+                }
+            })
+            .collect();
+        gpu.upload_image(texture, 4, 4, &checker).unwrap();
+        cpu.upload_image(texture, 4, 4, &checker).unwrap();
+
+        let mut ctx = RenderContext::new((w, h));
+        ctx.clear(Color::rgb(30, 30, 30));
+        Rectangle::new(Vec2::new(3.0, 5.0), Vec2::new(30.0, 20.0), Color::RED).draw(&mut ctx);
+        Circle::new(Vec2::new(40.0, 24.0), 14.0, Color::rgba(0, 255, 0, 0.5)).draw(&mut ctx);
+        let mut sprite = SpriteDrawData::new(texture, 16, 16);
+        sprite.position = Vec2::new(20.0, 30.0);
+        sprite.rotation = 0.3;
+        ctx.draw_sprite(sprite);
+
+        render(&mut gpu, &ctx);
+        render(&mut cpu, &ctx);
+
+        let gpu_pixels = gpu.read_pixels().unwrap();
+        let cpu_pixels = cpu.read_pixels();
+        assert_eq!(gpu_pixels.len(), cpu_pixels.len());
+
+        // Allow small rounding differences, and a handful of edge pixels where GPU
+        // rasterizers are free to differ (sub-pixel precision, filtering precision).
+        let mismatched = gpu_pixels
+            .chunks(4)
+            .zip(cpu_pixels.chunks(4))
+            .filter(|(a, b)| a.iter().zip(b.iter()).any(|(x, y)| x.abs_diff(*y) > 3))
+            .count();
+        assert!(
+            mismatched <= (w * h) as usize / 100,
+            "{mismatched} pixels differ between wgpu and the software renderer"
+        );
+    }
+}
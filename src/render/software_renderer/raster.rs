@@ -0,0 +1,305 @@
+use crate::core::assets::{AddressMode, FilterMode, SamplerOptions};
+use crate::math::Color;
+use crate::render::blend::BlendMode;
+use crate::render::clip::ClipRect;
+
+/// Vertex in framebuffer pixel space (Y-down), ready for rasterization.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct RasterVertex {
+    pub pos: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Linear-space RGBA framebuffer. Blending happens in linear space, like the
+/// sRGB render targets used by `WgpuRenderer`.
+pub(super) struct Framebuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+    clip: Option<ClipRect>,
+    /// Pixels left visible by the active stencil masks.
+    mask: Option<Vec<bool>>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        Self {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; (width * height) as usize],
+            clip: None,
+            mask: None,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn clear(&mut self, color: [f32; 4]) {
+        self.pixels.fill(color);
+    }
+
+    /// Blend `src` into a pixel. Results are clamped like writes to a unorm target.
+    fn blend(&mut self, x: u32, y: u32, src: [f32; 4], mode: BlendMode) {
+        let dst = &mut self.pixels[(y * self.width + x) as usize];
+        *dst = mode.apply(src, *dst).map(|c| c.clamp(0.0, 1.0));
+    }
+
+    /// Encode the framebuffer as tightly packed sRGB RGBA8 rows.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.pixels.len() * 4);
+        for px in &self.pixels {
+            for (c, value) in px.iter().enumerate() {
+                let value = value.clamp(0.0, 1.0);
+                let encoded = if c < 3 {
+                    Color::linear_to_srgb(value)
+                } else {
+                    value
+                };
+                out.push((encoded * 255.0).round() as u8);
+            }
+        }
+        out
+    }
+
+    /// Restrict subsequent triangles to `rect` (the GPU scissor rect), or lift it.
+    pub fn set_clip(&mut self, rect: Option<ClipRect>) {
+        self.clip = rect;
+    }
+
+    /// Restrict subsequent triangles to pixels covered by every mask (the GPU stencil
+    /// test). Each mask is a list of triangles in pixel space; empty lifts the mask.
+    pub fn set_masks(&mut self, masks: &[Vec<[f32; 2]>]) {
+        if masks.is_empty() {
+            self.mask = None;
+            return;
+        }
+
+        let size = (self.width, self.height);
+        let mut visible = vec![true; self.pixels.len()];
+        for mask in masks {
+            let mut covered = vec![false; self.pixels.len()];
+            for tri in mask.chunks_exact(3) {
+                for_each_covered([tri[0], tri[1], tri[2]], size, |x, y, _| {
+                    covered[(y * size.0 + x) as usize] = true;
+                });
+            }
+            for (visible, covered) in visible.iter_mut().zip(covered) {
+                *visible &= covered;
+            }
+        }
+        self.mask = Some(visible);
+    }
+
+    /// Rasterize one triangle, calling `shade` for every covered pixel center with the
+    /// interpolated UV and vertex color. The returned linear color is blended with `mode`.
+    /// Pixels outside the clip rect or masks are skipped.
+    pub fn fill_triangle(
+        &mut self,
+        tri: [RasterVertex; 3],
+        mode: BlendMode,
+        mut shade: impl FnMut([f32; 2], [f32; 4]) -> [f32; 4],
+    ) {
+        let [v0, v1, v2] = tri;
+        let size = (self.width, self.height);
+        for_each_covered([v0.pos, v1.pos, v2.pos], size, |x, y, l| {
+            if self.clip.is_some_and(|clip| !clip.contains(x, y))
+                || self
+                    .mask
+                    .as_ref()
+                    .is_some_and(|mask| !mask[(y * size.0 + x) as usize])
+            {
+                return;
+            }
+
+            let uv = [
+                l[0] * v0.uv[0] + l[1] * v1.uv[0] + l[2] * v2.uv[0],
+                l[0] * v0.uv[1] + l[1] * v1.uv[1] + l[2] * v2.uv[1],
+            ];
+            let mut color = [0.0; 4];
+            for (c, out) in color.iter_mut().enumerate() {
+                *out = l[0] * v0.color[c] + l[1] * v1.color[c] + l[2] * v2.color[c];
+            }
+
+            let src = shade(uv, color);
+            self.blend(x, y, src, mode);
+        });
+    }
+}
+
+/// Call `f` with the barycentric weights of every pixel center covered by a triangle
+/// inside a `size` target.
+///
+/// Coverage follows the top-left fill rule so that triangles sharing an edge never
+/// touch the same pixel twice (no seams and no double blending).
+fn for_each_covered(pos: [[f32; 2]; 3], size: (u32, u32), mut f: impl FnMut(u32, u32, [f32; 3])) {
+    let [p0, mut p1, mut p2] = pos;
+    let mut area = edge(p0, p1, p2);
+    if !area.is_finite() || area.abs() <= f32::EPSILON {
+        return;
+    }
+    // Culling is disabled on the GPU pipelines; normalize the winding instead.
+    let flipped = area < 0.0;
+    if flipped {
+        std::mem::swap(&mut p1, &mut p2);
+        area = -area;
+    }
+
+    let min_x = p0[0].min(p1[0]).min(p2[0]).floor().max(0.0) as u32;
+    let min_y = p0[1].min(p1[1]).min(p2[1]).floor().max(0.0) as u32;
+    let max_x = p0[0].max(p1[0]).max(p2[0]).ceil();
+    let max_y = p0[1].max(p1[1]).max(p2[1]).ceil();
+    let max_x = (max_x.max(0.0) as u32).min(size.0);
+    let max_y = (max_y.max(0.0) as u32).min(size.1);
+
+    let top_left = [
+        is_top_left(p1, p2),
+        is_top_left(p2, p0),
+        is_top_left(p0, p1),
+    ];
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let p = [x as f32 + 0.5, y as f32 + 0.5];
+            let w = [edge(p1, p2, p), edge(p2, p0, p), edge(p0, p1, p)];
+
+            let covered = w
+                .iter()
+                .zip(top_left)
+                .all(|(&w, top_left)| w > 0.0 || (w == 0.0 && top_left));
+            if !covered {
+                continue;
+            }
+
+            let mut l = [w[0] / area, w[1] / area, w[2] / area];
+            if flipped {
+                l.swap(1, 2);
+            }
+            f(x, y, l);
+        }
+    }
+}
+
+/// Edge function: positive when `p` lies on the interior side of `a -> b`.
+fn edge(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Top edges are horizontal with the interior below; left edges have the interior
+/// to their right (Y-down, positive winding as produced by `edge`).
+fn is_top_left(a: [f32; 2], b: [f32; 2]) -> bool {
+    let dx = b[0] - a[0];
+    let dy = b[1] - a[1];
+    (dy == 0.0 && dx > 0.0) || dy < 0.0
+}
+
+/// Decode one RGBA8 sRGB texel to linear space.
+fn decode_texel(p: &[u8]) -> [f32; 4] {
+    [
+        Color::srgb_to_linear(p[0] as f32 / 255.0),
+        Color::srgb_to_linear(p[1] as f32 / 255.0),
+        Color::srgb_to_linear(p[2] as f32 / 255.0),
+        p[3] as f32 / 255.0,
+    ]
+}
+
+/// CPU copy of an uploaded image, decoded to linear space once at upload time.
+///
+/// Sampling honors the filter and address modes; mipmaps are not generated, the base
+/// level is always sampled.
+pub(super) struct SoftwareTexture {
+    width: u32,
+    height: u32,
+    texels: Vec<[f32; 4]>,
+    sampler: SamplerOptions,
+}
+
+impl SoftwareTexture {
+    /// Decode RGBA8 sRGB data (the `Rgba8UnormSrgb` texture format used on the GPU).
+    pub fn from_rgba8(width: u32, height: u32, data: &[u8], sampler: SamplerOptions) -> Self {
+        let texels = data.chunks_exact(4).map(decode_texel).collect();
+        Self {
+            width,
+            height,
+            texels,
+            sampler,
+        }
+    }
+
+    pub fn sampler(&self) -> SamplerOptions {
+        self.sampler
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Overwrite the rect at (`x`, `y`) with `width`-pixel rows of RGBA8 sRGB `data`.
+    /// The rect must lie inside the texture.
+    pub fn write_region(&mut self, x: u32, y: u32, width: u32, data: &[u8]) {
+        for (row, src) in data.chunks_exact(width as usize * 4).enumerate() {
+            let start = ((y as usize + row) * self.width as usize) + x as usize;
+            for (texel, p) in self.texels[start..].iter_mut().zip(src.chunks_exact(4)) {
+                *texel = decode_texel(p);
+            }
+        }
+    }
+
+    fn texel(&self, x: i64, y: i64) -> [f32; 4] {
+        let address = |i: i64, size: u32| {
+            let size = size as i64;
+            match self.sampler.address_mode {
+                AddressMode::ClampToEdge => i.clamp(0, size - 1),
+                AddressMode::Repeat => i.rem_euclid(size),
+                AddressMode::MirrorRepeat => {
+                    let i = i.rem_euclid(size * 2);
+                    if i >= size { size * 2 - 1 - i } else { i }
+                }
+            }
+        };
+        let x = address(x, self.width) as u32;
+        let y = address(y, self.height) as u32;
+        self.texels[(y * self.width + x) as usize]
+    }
+
+    /// Nearest or bilinear sample, like the texture's sampler on the GPU.
+    pub fn sample(&self, uv: [f32; 2]) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [0.0; 4];
+        }
+
+        if self.sampler.filter == FilterMode::Nearest {
+            let x = (uv[0] * self.width as f32).floor() as i64;
+            let y = (uv[1] * self.height as f32).floor() as i64;
+            return self.texel(x, y);
+        }
+
+        let x = uv[0] * self.width as f32 - 0.5;
+        let y = uv[1] * self.height as f32 - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let t00 = self.texel(x0, y0);
+        let t10 = self.texel(x0 + 1, y0);
+        let t01 = self.texel(x0, y0 + 1);
+        let t11 = self.texel(x0 + 1, y0 + 1);
+
+        let mut out = [0.0; 4];
+        for (c, value) in out.iter_mut().enumerate() {
+            let top = t00[c] + (t10[c] - t00[c]) * fx;
+            let bottom = t01[c] + (t11[c] - t01[c]) * fx;
+            *value = top + (bottom - top) * fy;
+        }
+        out
+    }
+}
@@ -1,6 +1,8 @@
 use crate::core::assets::ImageId;
 use crate::math::color::Color;
 use crate::math::vec2::Vec2;
+use crate::render::blend::BlendMode;
+use crate::render::material::MaterialId;
 
 /// Generic sprite drawing data - decoupled from the Sprite type itself.
 /// This allows any system (Sprite, AnimatedSprite, custom renderers, etc.)
@@ -18,6 +20,15 @@ pub struct SpriteDrawData {
     // UV coordinates for atlas support
     pub uv_min: Vec2,
     pub uv_max: Vec2,
+    /// Mirror the image horizontally inside the quad.
+    pub flip_x: bool,
+    /// Mirror the image vertically inside the quad.
+    pub flip_y: bool,
+
+    /// Custom sprite material; `None` uses the default textured shader.
+    pub material: Option<MaterialId>,
+    /// How the sprite is blended with what is behind it.
+    pub blend: BlendMode,
 }
 
 impl SpriteDrawData {
@@ -33,6 +44,59 @@ impl SpriteDrawData {
             tint: Color::WHITE,
             uv_min: Vec2::new(0.0, 0.0),
             uv_max: Vec2::new(1.0, 1.0),
+            flip_x: false,
+            flip_y: false,
+            material: None,
+            blend: BlendMode::Alpha,
+        }
+    }
+
+    /// Compute the corners of the sprite quad in pixel space.
+    /// Order: top-left, top-right, bottom-right, bottom-left.
+    pub fn world_corners(&self) -> [Vec2; 4] {
+        let size = self.size;
+        let origin_px = Vec2::new(self.origin.x * size.x, self.origin.y * size.y);
+
+        // Local corners (unscaled, unrotated)
+        let local_tl = Vec2::new(0.0, 0.0) - origin_px;
+        let local_tr = Vec2::new(size.x, 0.0) - origin_px;
+        let local_br = Vec2::new(size.x, size.y) - origin_px;
+        let local_bl = Vec2::new(0.0, size.y) - origin_px;
+
+        // Apply scale, rotation, and translation
+        let cos_r = self.rotation.cos();
+        let sin_r = self.rotation.sin();
+
+        let transform = |p: Vec2| -> Vec2 {
+            let scaled = Vec2::new(p.x * self.scale.x, p.y * self.scale.y);
+            let rotated = Vec2::new(
+                scaled.x * cos_r - scaled.y * sin_r,
+                scaled.x * sin_r + scaled.y * cos_r,
+            );
+            rotated + self.position
+        };
+
+        [
+            transform(local_tl),
+            transform(local_tr),
+            transform(local_br),
+            transform(local_bl),
+        ]
+    }
+
+    /// World corners in the order the top-left, top-right, bottom-right and
+    /// bottom-left of the UV region are mapped to: `world_corners` swapped by
+    /// `flip_x` and `flip_y`, so the image is mirrored without moving the quad.
+    pub fn textured_corners(&self) -> [Vec2; 4] {
+        let [mut tl, mut tr, mut br, mut bl] = self.world_corners();
+        if self.flip_x {
+            std::mem::swap(&mut tl, &mut tr);
+            std::mem::swap(&mut bl, &mut br);
+        }
+        if self.flip_y {
+            std::mem::swap(&mut tl, &mut bl);
+            std::mem::swap(&mut tr, &mut br);
         }
+        [tl, tr, br, bl]
     }
 }
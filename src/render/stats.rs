@@ -0,0 +1,36 @@
+/// Counters for the last presented frame, as reported by `Renderer::render_stats`.
+///
+/// Draws into canvases during the frame are included.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderStats {
+    /// Draw calls recorded, including stencil mask and post-processing passes.
+    pub draw_calls: u32,
+    /// Shape, mask, mesh, gradient and SDF shape vertices submitted.
+    pub vertices: u32,
+    /// Sprite instances submitted.
+    pub sprites: u32,
+    /// Bind groups set while recording passes.
+    pub bind_group_switches: u32,
+    /// Memory held by image textures, atlas pages and canvases.
+    pub texture_memory_bytes: u64,
+    /// Bytes copied to the GPU: vertices, instances, uniforms and texture updates.
+    pub uploaded_bytes: u64,
+    /// GPU time of each render pass in submission order. Empty unless GPU timing is
+    /// enabled (see `Renderer::set_gpu_timing`) and supported by the adapter.
+    pub gpu_passes: Vec<PassTiming>,
+}
+
+impl RenderStats {
+    /// Total GPU time of the timed passes, in milliseconds.
+    pub fn gpu_time_ms(&self) -> f32 {
+        self.gpu_passes.iter().map(|pass| pass.millis).sum()
+    }
+}
+
+/// GPU duration of one render pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PassTiming {
+    /// What the pass drew: `"scene"`, `"canvas"` or `"post"`.
+    pub label: &'static str,
+    pub millis: f32,
+}
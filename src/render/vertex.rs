@@ -4,3 +4,68 @@ pub struct Vertex {
     pub pos: [f32; 2],
     pub color: [f32; 4],
 }
+
+/// Vertex of a textured mesh (see `Mesh2d`), ready for the renderer: position in NDC,
+/// texture coordinates and linear color.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TexturedVertex {
+    pub pos: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Vertex of a gradient fill, ready for the renderer: position in NDC and position in
+/// the gradient's shape-relative coordinates (see `GradientKind`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GradientVertex {
+    pub pos: [f32; 2],
+    pub local: [f32; 2],
+}
+
+/// Vertex of a shape drawn from its signed distance field (see `RoundedRect`), ready
+/// for the renderer: position in NDC and position relative to the shape's center in
+/// its local units. The other fields describe the whole shape, a rounded box, and are
+/// the same for each of its vertices.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SdfVertex {
+    pub pos: [f32; 2],
+    pub local: [f32; 2],
+    /// Half the size of the box.
+    pub half_size: [f32; 2],
+    /// Corner radius, at most the smaller half extent.
+    pub radius: f32,
+    /// Thickness of the outline inside the edge; `0.0` for none.
+    pub outline: f32,
+    pub color: [f32; 4],
+    pub outline_color: [f32; 4],
+}
+
+impl SdfVertex {
+    /// Signed distance from `local` to the edge of the shape, negative inside.
+    pub fn distance(&self, local: [f32; 2]) -> f32 {
+        let qx = local[0].abs() - self.half_size[0] + self.radius;
+        let qy = local[1].abs() - self.half_size[1] + self.radius;
+        let outside = qx.max(0.0).hypot(qy.max(0.0));
+        outside + qx.max(qy).min(0.0) - self.radius
+    }
+
+    /// Linear RGBA color of the shape at `local`, with alpha scaled by its coverage.
+    /// `aa` is the width of the smoothed edge (one pixel) in local units.
+    pub fn shade(&self, local: [f32; 2], aa: f32) -> [f32; 4] {
+        let d = self.distance(local);
+        let aa = aa.max(1e-6);
+        let coverage = (0.5 - d / aa).clamp(0.0, 1.0);
+        let mut color = self.color;
+        if self.outline > 0.0 {
+            let fill = (0.5 - (d + self.outline) / aa).clamp(0.0, 1.0);
+            color = std::array::from_fn(|i| {
+                self.outline_color[i] + (self.color[i] - self.outline_color[i]) * fill
+            });
+        }
+        color[3] *= coverage;
+        color
+    }
+}
@@ -0,0 +1,395 @@
+use super::WgpuRenderer;
+use crate::core::assets::{AddressMode, FilterMode, ImageId, SamplerOptions};
+use crate::math::vec2::Vec2;
+use crate::render::atlas::{AtlasConfig, AtlasPageStats, ShelfPacker, extrude, extrude_region};
+use crate::render::renderer::{RenderResult, validate_region};
+use std::collections::HashMap;
+
+/// Shared page texture. Registered in `WgpuRenderer::textures` under `id`, so sprites
+/// of every image packed into it batch together.
+pub(super) struct AtlasPage {
+    pub id: ImageId,
+    filter: FilterMode,
+    pub texture: wgpu::Texture,
+    packer: ShelfPacker,
+}
+
+/// Where a packed image lives: its pixel rect (padding excluded) in a page.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AtlasRegion {
+    pub page: usize,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// Padding the slot was reserved with, on every side.
+    padding: u32,
+    uv_min: Vec2,
+    uv_max: Vec2,
+}
+
+impl AtlasRegion {
+    /// Image-space UV (`0..1`) to page UV.
+    pub fn map(&self, uv: Vec2) -> Vec2 {
+        let size = self.uv_max - self.uv_min;
+        Vec2::new(self.uv_min.x + size.x * uv.x, self.uv_min.y + size.y * uv.y)
+    }
+}
+
+pub(super) struct Atlas {
+    /// `None` disables packing.
+    pub config: Option<AtlasConfig>,
+    pub pages: Vec<AtlasPage>,
+    pub regions: HashMap<ImageId, AtlasRegion>,
+}
+
+impl Atlas {
+    pub fn new() -> Self {
+        Self {
+            config: Some(AtlasConfig::default()),
+            pages: Vec::new(),
+            regions: HashMap::new(),
+        }
+    }
+
+    fn packable(&self, width: u32, height: u32, sampler: &SamplerOptions) -> bool {
+        self.config.is_some_and(|config| {
+            config.accepts(width, height)
+                && sampler.address_mode == AddressMode::ClampToEdge
+                && !sampler.mipmaps
+        })
+    }
+}
+
+impl WgpuRenderer {
+    /// Pack `data` into an atlas page if the image qualifies, returning whether it did.
+    /// Images that stop qualifying (new size or sampler) are dropped from the atlas.
+    pub(super) fn pack_image(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        sampler: &SamplerOptions,
+    ) -> bool {
+        if !self.atlas.packable(width, height, sampler) {
+            self.release_region(id);
+            return false;
+        }
+
+        // Reuse the previous slot when the image keeps its size and page kind.
+        let region = match self.atlas.regions.get(&id) {
+            Some(region)
+                if (region.width, region.height) == (width, height)
+                    && self.atlas.pages[region.page].filter == sampler.filter =>
+            {
+                *region
+            }
+            _ => {
+                self.release_region(id);
+                self.allocate_region(width, height, sampler.filter)
+            }
+        };
+
+        let padding = region.padding;
+        let page = &self.atlas.pages[region.page];
+        self.write_rgba8(
+            &page.texture,
+            0,
+            (region.x - padding, region.y - padding),
+            (width + 2 * padding, height + 2 * padding),
+            &extrude(width, height, data, padding),
+        );
+        self.atlas.regions.insert(id, region);
+        // A standalone copy (see `unpack_images`) would now be stale.
+        self.textures.remove(&id);
+        self.image_textures.remove(&id);
+        true
+    }
+
+    /// Overwrite a rect of a packed image, extruding it into the page padding where it
+    /// touches the image edges.
+    pub(super) fn update_packed_region(
+        &mut self,
+        id: ImageId,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> RenderResult<()> {
+        let Some(region) = self.atlas.regions.get(&id).copied() else {
+            return Ok(());
+        };
+        let rect = (x, y, width, height);
+        validate_region((region.width, region.height), rect, data)?;
+        let padding = region.padding;
+        let (origin, size, pixels) =
+            extrude_region((region.width, region.height), rect, data, padding);
+
+        let page = &self.atlas.pages[region.page];
+        self.write_rgba8(
+            &page.texture,
+            0,
+            (region.x - padding + origin.0, region.y - padding + origin.1),
+            size,
+            &pixels,
+        );
+        // Like in `pack_image`, drop the now stale standalone copy.
+        self.textures.remove(&id);
+        Ok(())
+    }
+
+    /// Drop the atlas slot of `id` so its space can be reused, and the page with it once
+    /// no image is left in it.
+    pub(super) fn release_region(&mut self, id: ImageId) {
+        let Some(region) = self.atlas.regions.remove(&id) else {
+            return;
+        };
+        if self.atlas.regions.values().any(|r| r.page == region.page) {
+            let padding = region.padding;
+            self.atlas.pages[region.page].packer.remove(
+                region.x - padding,
+                region.y - padding,
+                region.width + 2 * padding,
+                region.height + 2 * padding,
+            );
+            return;
+        }
+        let page = self.atlas.pages.remove(region.page);
+        self.textures.remove(&page.id);
+        for r in self.atlas.regions.values_mut() {
+            if r.page > region.page {
+                r.page -= 1;
+            }
+        }
+    }
+
+    fn allocate_region(&mut self, width: u32, height: u32, filter: FilterMode) -> AtlasRegion {
+        let config = self.atlas.config.unwrap_or_default();
+        let (padded_w, padded_h) = (width + 2 * config.padding, height + 2 * config.padding);
+        let slot = self
+            .atlas
+            .pages
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, page)| page.filter == filter)
+            .find_map(|(index, page)| {
+                page.packer
+                    .insert(padded_w, padded_h)
+                    .map(|pos| (index, pos))
+            });
+        let (page, (x, y)) = match slot {
+            Some(slot) => slot,
+            None => {
+                self.add_page(config.page_size, filter);
+                let index = self.atlas.pages.len() - 1;
+                let pos = self.atlas.pages[index]
+                    .packer
+                    .insert(padded_w, padded_h)
+                    .expect("AtlasConfig::accepts guarantees the image fits a page");
+                (index, pos)
+            }
+        };
+
+        let (x, y) = (x + config.padding, y + config.padding);
+        let size = config.page_size as f32;
+        AtlasRegion {
+            page,
+            x,
+            y,
+            width,
+            height,
+            padding: config.padding,
+            uv_min: Vec2::new(x as f32 / size, y as f32 / size),
+            uv_max: Vec2::new((x + width) as f32 / size, (y + height) as f32 / size),
+        }
+    }
+
+    fn add_page(&mut self, size: u32, filter: FilterMode) {
+        let texture = self.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("atlas page"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = SamplerOptions::default().with_filter(filter);
+        let id = ImageId::new();
+        let texture_gpu = self.texture_gpu(view, &sampler);
+        self.textures.insert(id, texture_gpu);
+        self.atlas.pages.push(AtlasPage {
+            id,
+            filter,
+            texture,
+            packer: ShelfPacker::new(size, size),
+        });
+    }
+
+    /// Page texture and remapped UVs to draw a sprite of `image` with.
+    pub(super) fn sprite_source(
+        &self,
+        image: ImageId,
+        uv_min: Vec2,
+        uv_max: Vec2,
+    ) -> (ImageId, Vec2, Vec2) {
+        match self.atlas.regions.get(&image) {
+            Some(region) => (
+                self.atlas.pages[region.page].id,
+                region.map(uv_min),
+                region.map(uv_max),
+            ),
+            None => (image, uv_min, uv_max),
+        }
+    }
+
+    /// Give packed images used as material textures a standalone texture, copied out
+    /// of their page, since materials sample them with their own UVs.
+    pub(super) fn unpack_images(&mut self, images: &[Option<ImageId>]) {
+        for &id in images.iter().flatten() {
+            if self.textures.contains_key(&id) {
+                continue;
+            }
+            let Some(region) = self.atlas.regions.get(&id).copied() else {
+                continue;
+            };
+            let page = &self.atlas.pages[region.page];
+            let size = wgpu::Extent3d {
+                width: region.width,
+                height: region.height,
+                depth_or_array_layers: 1,
+            };
+            let texture = self.device().create_texture(&wgpu::TextureDescriptor {
+                label: Some("unpacked atlas image"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            let mut encoder =
+                self.device()
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("atlas unpack encoder"),
+                    });
+            encoder.copy_texture_to_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &page.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: region.x,
+                        y: region.y,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                size,
+            );
+            self.queue().submit(std::iter::once(encoder.finish()));
+
+            let sampler = SamplerOptions::default().with_filter(page.filter);
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let texture_gpu = self.texture_gpu(view, &sampler);
+            self.textures.insert(id, texture_gpu);
+        }
+    }
+
+    pub(super) fn atlas_page_stats(&self) -> Vec<AtlasPageStats> {
+        self.atlas
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| {
+                let regions = self.atlas.regions.values().filter(|r| r.page == index);
+                let (images, used_pixels) = regions.fold((0, 0), |(n, used), r| {
+                    (n + 1, used + r.width as u64 * r.height as u64)
+                });
+                AtlasPageStats {
+                    width: page.texture.width(),
+                    height: page.texture.height(),
+                    images,
+                    used_pixels,
+                }
+            })
+            .collect()
+    }
+
+    /// Change how images uploaded from now on are packed; `None` gives every image its
+    /// own texture. Images already packed stay in their pages.
+    pub fn set_atlas_config(&mut self, config: Option<AtlasConfig>) {
+        self.atlas.config = config;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::assets::ImageId;
+    use crate::math::Vec2;
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{RenderContext, Renderer, SpriteDrawData};
+
+    #[test]
+    fn small_images_share_a_page_and_keep_their_pixels() {
+        let Some(mut renderer) = headless(8, 4) else {
+            return;
+        };
+        let red = ImageId::new();
+        let checker = ImageId::new();
+        let large = ImageId::new();
+        renderer.upload_image(red, 1, 1, &[255, 0, 0, 255]).unwrap();
+        let blue_green = [0, 0, 255, 255, 0, 255, 0, 255];
+        renderer.upload_image(checker, 2, 1, &blue_green).unwrap();
+        renderer
+            .upload_image(large, 300, 1, &[255; 300 * 4])
+            .unwrap();
+
+        let stats = renderer.atlas_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].images, stats[0].used_pixels), (2, 3));
+        assert!(stats[0].occupancy() > 0.0);
+
+        let mut ctx = RenderContext::new((8, 4));
+        let mut sprite = SpriteDrawData::new(red, 4, 4);
+        sprite.origin = Vec2::ZERO;
+        ctx.draw_sprite(sprite);
+        // Only the right (green) half of the checker image.
+        let mut sprite = SpriteDrawData::new(checker, 4, 4);
+        sprite.origin = Vec2::ZERO;
+        sprite.position = Vec2::new(4.0, 0.0);
+        sprite.uv_min = Vec2::new(0.75, 0.0);
+        ctx.draw_sprite(sprite);
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+
+        let data = renderer.read_pixels().unwrap();
+        assert_eq!(&data[(8 + 1) * 4..(8 + 1) * 4 + 4], [255, 0, 0, 255]);
+        assert_eq!(&data[(8 + 6) * 4..(8 + 6) * 4 + 4], [0, 255, 0, 255]);
+
+        // Freed slots are reused, and the page goes once it is empty.
+        renderer.unload_image(red);
+        let other = ImageId::new();
+        renderer.upload_image(other, 1, 1, &[0; 4]).unwrap();
+        assert_eq!(renderer.atlas.regions[&other].x, 1);
+        renderer.unload_image(other);
+        renderer.unload_image(checker);
+        assert!(renderer.atlas_stats().is_empty());
+    }
+}
@@ -0,0 +1,56 @@
+/// GPU buffer that is reused across frames and only reallocated when it needs to grow.
+///
+/// Capacity grows to the next power of two so steady-state frames never allocate.
+pub(super) struct GrowableBuffer {
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    buffer: Option<wgpu::Buffer>,
+}
+
+impl GrowableBuffer {
+    pub fn new(label: &'static str, usage: wgpu::BufferUsages) -> Self {
+        Self {
+            label,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            buffer: None,
+        }
+    }
+
+    /// Upload `contents` at offset 0, growing the buffer if it is too small.
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, contents: &[u8]) {
+        if contents.is_empty() {
+            return;
+        }
+
+        let needed = contents.len() as wgpu::BufferAddress;
+        if self.capacity() < needed {
+            self.buffer = Some(
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(self.label),
+                    size: needed
+                        .next_power_of_two()
+                        .max(wgpu::COPY_BUFFER_ALIGNMENT * 64),
+                    usage: self.usage,
+                    mapped_at_creation: false,
+                }),
+            );
+        }
+
+        let buffer = self.buffer.as_ref().expect("buffer allocated above");
+        // write_buffer needs a 4-byte aligned size; vertex data always is.
+        queue.write_buffer(buffer, 0, contents);
+    }
+
+    pub fn capacity(&self) -> wgpu::BufferAddress {
+        self.buffer.as_ref().map_or(0, |b| b.size())
+    }
+
+    pub fn buffer(&self) -> Option<&wgpu::Buffer> {
+        self.buffer.as_ref()
+    }
+
+    /// Drop the buffer, e.g. when its device is gone.
+    pub fn reset(&mut self) {
+        self.buffer = None;
+    }
+}
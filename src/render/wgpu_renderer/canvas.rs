@@ -0,0 +1,149 @@
+use super::WgpuRenderer;
+use crate::core::assets::{ImageId, SamplerOptions};
+use crate::render::DrawList;
+use crate::render::renderer::{RenderError, RenderResult, Renderer};
+
+/// Offscreen texture backing a `Canvas`. Its view is also registered in `textures`
+/// so sprites can sample it.
+pub(super) struct CanvasTarget {
+    pub texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    /// Multisampled texture drawn into and resolved into `texture` with MSAA. Each
+    /// canvas keeps its own so passes that do not clear still see previous samples.
+    pub msaa: Option<wgpu::Texture>,
+    sampler: SamplerOptions,
+}
+
+impl WgpuRenderer {
+    pub(super) fn create_canvas_target(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        let (width, height) = (width.max(1), height.max(1));
+        if let Some(existing) = self.canvases.get(&id)
+            && existing.texture.width() == width
+            && existing.texture.height() == height
+        {
+            // Same size: keep the contents, only swap the sampler if it changed.
+            if existing.sampler != *sampler {
+                let texture_gpu = self.texture_gpu(existing.view.clone(), sampler);
+                self.textures.insert(id, texture_gpu);
+                if let Some(canvas) = self.canvases.get_mut(&id) {
+                    canvas.sampler = *sampler;
+                }
+            }
+            return Ok(());
+        }
+
+        let format = self.color_format.ok_or_else(|| {
+            RenderError::RenderFailed("canvas created before renderer init".to_string())
+        })?;
+        let texture = self.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("canvas"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let msaa = (self.sample_count > 1)
+            .then(|| self.create_msaa_texture((width, height), "canvas msaa color"));
+
+        let texture_gpu = self.texture_gpu(view.clone(), sampler);
+        self.textures.insert(id, texture_gpu);
+        self.canvases.insert(
+            id,
+            CanvasTarget {
+                texture,
+                view,
+                msaa,
+                sampler: *sampler,
+            },
+        );
+        Ok(())
+    }
+
+    pub(super) fn render_canvas(
+        &mut self,
+        id: ImageId,
+        clear_color: Option<[f32; 4]>,
+        list: &DrawList<'_>,
+    ) -> RenderResult<()> {
+        let target = self.canvases.get(&id).ok_or_else(|| {
+            RenderError::InvalidTexture(format!("no canvas created for {:?}", id))
+        })?;
+        let view = target.view.clone();
+        let msaa = target
+            .msaa
+            .as_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let size = (target.texture.width(), target.texture.height());
+
+        // Keep the draws already queued for the frame aside while the canvas is drawn.
+        let frame_vertices = std::mem::take(&mut self.pending_vertices);
+        let frame_instances = std::mem::take(&mut self.sprite_instances);
+        let frame_mesh_vertices = std::mem::take(&mut self.mesh_vertices);
+        let frame_mesh_indices = std::mem::take(&mut self.mesh_indices);
+        let frame_gradient_vertices = std::mem::take(&mut self.gradient_fills.vertices);
+        let frame_gradients = std::mem::take(&mut self.gradient_fills.gradients);
+        let frame_sdf_vertices = std::mem::take(&mut self.sdf_vertices);
+        let frame_batches = std::mem::take(&mut self.batches);
+
+        self.submit_draw_list(list, size);
+        // A texture cannot be sampled while it is the render target.
+        self.batches.retain(|batch| match batch {
+            super::DrawBatch::Sprites { texture_id, .. } => *texture_id != id,
+            super::DrawBatch::Meshes { texture_id, .. } => *texture_id != Some(id),
+            _ => true,
+        });
+        self.upload_frame_buffers();
+        let stencil = self.stencil_view(size);
+
+        let load = match clear_color {
+            Some([r, g, b, a]) => wgpu::LoadOp::Clear(wgpu::Color {
+                r: r as f64,
+                g: g as f64,
+                b: b as f64,
+                a: a as f64,
+            }),
+            None => wgpu::LoadOp::Load,
+        };
+        let mut encoder = self
+            .device()
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("canvas encoder"),
+            });
+        self.encode_pass(
+            &mut encoder,
+            &view,
+            msaa.as_ref(),
+            &stencil,
+            size,
+            load,
+            "canvas",
+        );
+        self.queue().submit(std::iter::once(encoder.finish()));
+
+        self.pending_vertices = frame_vertices;
+        self.sprite_instances = frame_instances;
+        self.mesh_vertices = frame_mesh_vertices;
+        self.mesh_indices = frame_mesh_indices;
+        self.gradient_fills.vertices = frame_gradient_vertices;
+        self.gradient_fills.gradients = frame_gradients;
+        self.sdf_vertices = frame_sdf_vertices;
+        self.batches = frame_batches;
+        Ok(())
+    }
+}
@@ -0,0 +1,366 @@
+use super::buffers::GrowableBuffer;
+use super::{DrawBatch, WgpuRenderer, blend_state, stencil};
+use crate::render::GradientVertex;
+use crate::render::blend::BlendMode;
+use crate::render::gradient::{Gradient, GradientKind, MAX_GRADIENT_STOPS};
+
+/// Gradient fill vertex: position in NDC, shape-relative position and index of its
+/// gradient in the frame's gradient buffer.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(super) struct GradientVertexGPU {
+    pos: [f32; 2],
+    local: [f32; 2],
+    gradient: u32,
+}
+
+impl GradientVertexGPU {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Uint32,
+    ];
+
+    fn buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GradientVertexGPU>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// One gradient in the storage buffer, laid out like `Gradient` in the shader.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(super) struct GradientGPU {
+    /// `0` linear, `1` radial, `2` conic.
+    kind: u32,
+    stop_count: u32,
+    _pad: [u32; 2],
+    /// Linear: start and end. Radial: center and radius. Conic: center and angle.
+    params: [f32; 4],
+    offsets: [[f32; 4]; MAX_GRADIENT_STOPS / 4],
+    /// Linear colors.
+    colors: [[f32; 4]; MAX_GRADIENT_STOPS],
+}
+
+impl GradientGPU {
+    fn new(gradient: &Gradient) -> Self {
+        let (kind, params) = match gradient.kind {
+            GradientKind::Linear { start, end } => (0, [start.x, start.y, end.x, end.y]),
+            GradientKind::Radial { center, radius } => (1, [center.x, center.y, radius, 0.0]),
+            GradientKind::Conic { center, angle } => (2, [center.x, center.y, angle, 0.0]),
+        };
+        let mut gpu = Self {
+            kind,
+            stop_count: gradient.stops().len() as u32,
+            _pad: [0; 2],
+            params,
+            offsets: [[0.0; 4]; MAX_GRADIENT_STOPS / 4],
+            colors: [[0.0; 4]; MAX_GRADIENT_STOPS],
+        };
+        for (i, stop) in gradient.stops().iter().enumerate() {
+            gpu.offsets[i / 4][i % 4] = stop.offset;
+            gpu.colors[i] = stop.color.to_linear_rgba();
+        }
+        gpu
+    }
+}
+
+/// Per-pixel gradient fills: the frame's triangles and gradients, and the pipelines
+/// evaluating them.
+pub(super) struct GradientFills {
+    /// One pipeline per `BlendMode`.
+    pipelines: Vec<wgpu::RenderPipeline>,
+    bind_group_layout: Option<wgpu::BindGroupLayout>,
+    pub vertices: Vec<GradientVertexGPU>,
+    pub gradients: Vec<GradientGPU>,
+    vertex_buffer: GrowableBuffer,
+    gradient_buffer: GrowableBuffer,
+    /// Binds `gradient_buffer`; rebuilt on every upload since the buffer may grow.
+    bind_group: Option<wgpu::BindGroup>,
+}
+
+impl Default for GradientFills {
+    fn default() -> Self {
+        Self {
+            pipelines: Vec::new(),
+            bind_group_layout: None,
+            vertices: Vec::new(),
+            gradients: Vec::new(),
+            vertex_buffer: GrowableBuffer::new("gradient vertices", wgpu::BufferUsages::VERTEX),
+            gradient_buffer: GrowableBuffer::new("gradients", wgpu::BufferUsages::STORAGE),
+            bind_group: None,
+        }
+    }
+}
+
+impl GradientFills {
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.gradients.clear();
+    }
+
+    /// Copy the queued fills to the GPU, returning the number of bytes uploaded.
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> usize {
+        if self.vertices.is_empty() {
+            return 0;
+        }
+        let vertex_bytes: &[u8] = bytemuck::cast_slice(&self.vertices);
+        let gradient_bytes: &[u8] = bytemuck::cast_slice(&self.gradients);
+        self.vertex_buffer.write(device, queue, vertex_bytes);
+        self.gradient_buffer.write(device, queue, gradient_bytes);
+        if let (Some(layout), Some(buffer)) = (
+            self.bind_group_layout.as_ref(),
+            self.gradient_buffer.buffer(),
+        ) {
+            self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("gradient bind group"),
+                layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            }));
+        }
+        vertex_bytes.len() + gradient_bytes.len()
+    }
+
+    /// Record a draw of `range` (into `vertices`) with the `blend` pipeline.
+    pub fn draw(
+        &self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        blend: BlendMode,
+        range: std::ops::Range<u32>,
+    ) -> bool {
+        let (Some(bind_group), Some(vb)) = (self.bind_group.as_ref(), self.vertex_buffer.buffer())
+        else {
+            return false;
+        };
+        rpass.set_pipeline(&self.pipelines[blend.index()]);
+        rpass.set_bind_group(0, bind_group, &[]);
+        rpass.set_vertex_buffer(0, vb.slice(..));
+        rpass.draw(range, 0..1);
+        true
+    }
+}
+
+impl WgpuRenderer {
+    /// Create the gradient fill pipelines, one per `BlendMode`.
+    pub(super) fn build_gradient_pipelines(&mut self, format: wgpu::TextureFormat) {
+        let device = self.device();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("gradient shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                r#"
+                struct Gradient {
+                    kind: u32,
+                    stop_count: u32,
+                    params: vec4<f32>,
+                    offsets: array<vec4<f32>, 2>,
+                    colors: array<vec4<f32>, 8>,
+                };
+
+                @group(0) @binding(0) var<storage, read> gradients: array<Gradient>;
+
+                struct GradientVsOut {
+                    @builtin(position) pos: vec4<f32>,
+                    @location(0) local: vec2<f32>,
+                    @location(1) @interpolate(flat) gradient: u32,
+                };
+
+                @vertex
+                fn vs(
+                    @location(0) pos: vec2<f32>,
+                    @location(1) local: vec2<f32>,
+                    @location(2) gradient: u32,
+                ) -> GradientVsOut {
+                    var out: GradientVsOut;
+                    out.pos = vec4<f32>(pos, 0.0, 1.0);
+                    out.local = local;
+                    out.gradient = gradient;
+                    return out;
+                }
+
+                fn stop_offset(g: u32, i: u32) -> f32 {
+                    return gradients[g].offsets[i / 4u][i % 4u];
+                }
+
+                @fragment
+                fn fs(input: GradientVsOut) -> @location(0) vec4<f32> {
+                    let g = input.gradient;
+                    let params = gradients[g].params;
+                    var t: f32;
+                    switch gradients[g].kind {
+                        case 0u: {
+                            let axis = params.zw - params.xy;
+                            t = dot(input.local - params.xy, axis) / max(dot(axis, axis), 1e-12);
+                        }
+                        case 1u: {
+                            t = length(input.local - params.xy) / max(params.z, 1e-6);
+                        }
+                        default: {
+                            let turn = 6.28318530718;
+                            let d = input.local - params.xy;
+                            let angle = atan2(d.y, d.x) - params.z;
+                            t = (angle - floor(angle / turn) * turn) / turn;
+                        }
+                    }
+                    t = clamp(t, 0.0, 1.0);
+
+                    var color = gradients[g].colors[0];
+                    for (var i = 1u; i < gradients[g].stop_count; i++) {
+                        let a = stop_offset(g, i - 1u);
+                        if t <= a {
+                            break;
+                        }
+                        let b = stop_offset(g, i);
+                        let f = clamp((t - a) / max(b - a, 1e-6), 0.0, 1.0);
+                        color = mix(gradients[g].colors[i - 1u], gradients[g].colors[i], f);
+                    }
+                    return color;
+                }
+                "#
+                .into(),
+            ),
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("gradient bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("gradient pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let vertex_layout = GradientVertexGPU::buffer_layout();
+
+        let pipelines = BlendMode::ALL
+            .iter()
+            .map(|&blend| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("gradient pipeline"),
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: Some("vs"),
+                        buffers: std::slice::from_ref(&vertex_layout),
+                        compilation_options: Default::default(),
+                    },
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        cull_mode: None,
+                        ..Default::default()
+                    },
+                    depth_stencil: Some(stencil::content_depth_stencil()),
+                    multisample: self.multisample_state(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: Some("fs"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: blend_state(blend),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: Default::default(),
+                    }),
+                    cache: None,
+                    multiview: None,
+                })
+            })
+            .collect();
+
+        self.gradient_fills.pipelines = pipelines;
+        self.gradient_fills.bind_group_layout = Some(bind_group_layout);
+    }
+
+    /// Queue triangles filled with `gradient`. Consecutive fills with the same blend
+    /// mode share one draw call.
+    pub(super) fn queue_gradient(
+        &mut self,
+        vertices: &[GradientVertex],
+        gradient: &Gradient,
+        blend: BlendMode,
+    ) {
+        let fills = &mut self.gradient_fills;
+        let index = fills.gradients.len() as u32;
+        fills.gradients.push(GradientGPU::new(gradient));
+        let start = fills.vertices.len() as u32;
+        fills
+            .vertices
+            .extend(vertices.iter().map(|v| GradientVertexGPU {
+                pos: v.pos,
+                local: v.local,
+                gradient: index,
+            }));
+        let end = fills.vertices.len() as u32;
+
+        match self.batches.last_mut() {
+            Some(DrawBatch::Gradients {
+                blend: batch_blend,
+                range,
+            }) if *batch_blend == blend && range.end == start => range.end = end,
+            _ if start < end => self.batches.push(DrawBatch::Gradients {
+                blend,
+                range: start..end,
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{
+        ColorStop, Drawable, Gradient, Rectangle, RenderContext, Renderer, SoftwareRenderer,
+    };
+
+    #[test]
+    fn gradient_fills_match_the_software_renderer() {
+        let (w, h) = (32, 16);
+        let Some(mut gpu) = headless(w, h) else {
+            return;
+        };
+        let mut cpu = SoftwareRenderer::new(w, h);
+
+        let mut ctx = RenderContext::new((w, h));
+        let stops = [
+            ColorStop::new(0.0, Color::RED),
+            ColorStop::new(0.5, Color::YELLOW),
+            ColorStop::new(1.0, Color::BLUE),
+        ];
+        Rectangle::new(Vec2::ZERO, Vec2::new(16.0, 16.0), Color::WHITE)
+            .with_gradient(Gradient::linear(Vec2::ZERO, Vec2::new(1.0, 0.0), stops))
+            .draw(&mut ctx);
+        Rectangle::new(Vec2::new(16.0, 0.0), Vec2::new(16.0, 16.0), Color::WHITE)
+            .with_gradient(Gradient::conic(Vec2::new(0.5, 0.5), 0.0, stops))
+            .draw(&mut ctx);
+        for renderer in [&mut gpu as &mut dyn Renderer, &mut cpu] {
+            renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+            renderer.present().unwrap();
+        }
+        assert_eq!(gpu.render_stats().draw_calls, 1);
+
+        let gpu_pixels = gpu.read_pixels().unwrap();
+        let cpu_pixels = cpu.read_pixels();
+        let mismatched = gpu_pixels
+            .chunks(4)
+            .zip(cpu_pixels.chunks(4))
+            .filter(|(a, b)| a.iter().zip(b.iter()).any(|(x, y)| x.abs_diff(*y) > 3))
+            .count();
+        // Conic gradients wrap around: allow the seam pixels to differ.
+        assert!(mismatched <= 8, "{mismatched} pixels differ");
+    }
+}
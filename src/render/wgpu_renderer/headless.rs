@@ -0,0 +1,439 @@
+use super::{InitTarget, WgpuRenderer, msaa, request_device};
+use crate::core::assets::{ImageAsset, SamplerOptions};
+use crate::render::renderer::{RenderError, RenderResult};
+
+/// Color format of the offscreen target. Matches the sRGB swapchain formats used
+/// on screen, and reads back as plain RGBA8 bytes.
+const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Options for creating a windowless `WgpuRenderer`.
+///
+/// Headless renderers draw into an offscreen texture instead of a window surface,
+/// which makes them usable in CI and tests (golden images, render regressions).
+#[derive(Debug, Clone)]
+pub struct HeadlessConfig {
+    /// Width of the offscreen target in pixels.
+    pub width: u32,
+    /// Height of the offscreen target in pixels.
+    pub height: u32,
+    /// Only accept a software/fallback adapter (WARP, llvmpipe, SwiftShader...).
+    ///
+    /// When `false`, a hardware adapter is preferred and the fallback adapter is
+    /// only tried if no hardware adapter is available.
+    pub force_fallback_adapter: bool,
+    /// Backends to consider when looking for an adapter.
+    pub backends: wgpu::Backends,
+    /// Samples per pixel for multisample anti-aliasing; `1` turns it off. Lowered to
+    /// what the adapter supports (see `WgpuRenderer::msaa_samples`).
+    pub msaa_samples: u32,
+}
+
+impl HeadlessConfig {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            force_fallback_adapter: false,
+            backends: wgpu::Backends::all(),
+            msaa_samples: 1,
+        }
+    }
+
+    /// Builder: require a software/fallback adapter.
+    pub fn with_fallback_adapter(mut self, force: bool) -> Self {
+        self.force_fallback_adapter = force;
+        self
+    }
+
+    /// Builder: restrict the backends used to find an adapter.
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// Builder: draw with `samples` samples per pixel.
+    pub fn with_msaa_samples(mut self, samples: u32) -> Self {
+        self.msaa_samples = samples;
+        self
+    }
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> Self {
+        Self::new(256, 256)
+    }
+}
+
+/// Texture the renderer draws into when there is no window surface.
+pub(super) struct OffscreenTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+impl OffscreenTarget {
+    fn new(device: &wgpu::Device, size: (u32, u32)) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen target"),
+            size: wgpu::Extent3d {
+                width: size.0.max(1),
+                height: size.1.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: OFFSCREEN_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self { texture, view }
+    }
+}
+
+impl WgpuRenderer {
+    /// Create a renderer that draws into an offscreen texture instead of a window.
+    pub fn new_headless(config: &HeadlessConfig) -> RenderResult<Self> {
+        let mut renderer = Self::new();
+        renderer.init_headless(config)?;
+        Ok(renderer)
+    }
+
+    /// Initialize the renderer without a window surface.
+    ///
+    /// Frames produced by `present` stay in an offscreen texture and can be read back
+    /// with `read_pixels` / `read_image`.
+    pub fn init_headless(&mut self, config: &HeadlessConfig) -> RenderResult<()> {
+        self.size = (config.width.max(1), config.height.max(1));
+
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: config.backends,
+            ..Default::default()
+        });
+
+        let adapter = request_headless_adapter(&instance, config.force_fallback_adapter)?;
+        let info = adapter.get_info();
+        log::info!(
+            "Headless renderer using adapter {:?} ({:?}, {:?})",
+            info.name,
+            info.backend,
+            info.device_type
+        );
+
+        let (device, queue) = request_device(&adapter)?;
+        let target = OffscreenTarget::new(&device, self.size);
+        self.sample_count =
+            msaa::supported_sample_count(&adapter, &device, OFFSCREEN_FORMAT, config.msaa_samples);
+
+        self.instance = Some(instance);
+        self.surface = None;
+        self.config = None;
+        self.adapter = Some(adapter);
+        self.device = Some(device);
+        self.queue = Some(queue);
+        self.offscreen = Some(target);
+        self.init_target = Some(InitTarget::Headless(config.clone()));
+        self.watch_device_loss();
+        self.build_pipelines(OFFSCREEN_FORMAT);
+
+        Ok(())
+    }
+
+    /// Whether this renderer draws into an offscreen texture rather than a window.
+    pub fn is_headless(&self) -> bool {
+        self.offscreen.is_some()
+    }
+
+    /// Read back the last presented headless frame as tightly packed RGBA8 rows.
+    pub fn read_pixels(&self) -> RenderResult<Vec<u8>> {
+        let target = self.offscreen.as_ref().ok_or_else(|| {
+            RenderError::RenderFailed("read_pixels requires a headless renderer".to_string())
+        })?;
+        read_texture_rgba8(self.device(), self.queue(), &target.texture)
+    }
+
+    /// Read back the last presented headless frame as an `ImageAsset`.
+    pub fn read_image(&self) -> RenderResult<ImageAsset> {
+        let data = self.read_pixels()?;
+        Ok(ImageAsset {
+            width: self.size.0.max(1),
+            height: self.size.1.max(1),
+            data,
+            sampler: SamplerOptions::default(),
+        })
+    }
+
+    pub(super) fn present_offscreen(&mut self, capture: bool) -> RenderResult<()> {
+        let size = (self.size.0.max(1), self.size.1.max(1));
+        let stencil = self.stencil_view(size);
+        let msaa = self.msaa_view(size);
+        let scene = self.prepare_post(size);
+        let target = self
+            .offscreen
+            .as_ref()
+            .expect("offscreen target not initialized");
+
+        let mut encoder = self
+            .device()
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("offscreen encoder"),
+            });
+        self.encode_frame(
+            &mut encoder,
+            &target.view,
+            scene.as_ref(),
+            msaa.as_ref(),
+            &stencil,
+            size,
+        );
+        self.resolve_gpu_timer(&mut encoder);
+        self.queue().submit(std::iter::once(encoder.finish()));
+
+        let captured = capture.then(|| self.read_image());
+        self.finish_frame_stats();
+        self.clear_frame();
+        self.captured = captured.transpose()?;
+        Ok(())
+    }
+
+    /// Read back a swapchain texture as RGBA8, or `None` (logged) if the surface does
+    /// not allow copies or the readback fails.
+    pub(super) fn read_surface_frame(&self, texture: &wgpu::Texture) -> Option<ImageAsset> {
+        if !self.config().usage.contains(wgpu::TextureUsages::COPY_SRC) {
+            log::warn!("Frame capture is not supported by this surface");
+            return None;
+        }
+        let mut data = match read_texture_rgba8(self.device(), self.queue(), texture) {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!("Failed to capture frame: {}", e);
+                return None;
+            }
+        };
+        if matches!(
+            texture.format(),
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            data.chunks_mut(4).for_each(|pixel| pixel.swap(0, 2));
+        }
+        Some(ImageAsset {
+            width: texture.width(),
+            height: texture.height(),
+            data,
+            sampler: SamplerOptions::default(),
+        })
+    }
+
+    pub(super) fn resize_offscreen(&mut self) {
+        if let Some(device) = self.device.as_ref() {
+            self.offscreen = Some(OffscreenTarget::new(device, self.size));
+        }
+    }
+}
+
+fn request_headless_adapter(
+    instance: &wgpu::Instance,
+    force_fallback_adapter: bool,
+) -> RenderResult<wgpu::Adapter> {
+    let request = |force_fallback_adapter: bool| {
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter,
+            compatible_surface: None,
+        }))
+    };
+
+    match request(force_fallback_adapter) {
+        Ok(adapter) => Ok(adapter),
+        Err(_) if !force_fallback_adapter => request(true)
+            .map_err(|e| RenderError::InitFailed(format!("no headless adapter found: {}", e))),
+        Err(e) => Err(RenderError::InitFailed(format!(
+            "no fallback adapter found: {}",
+            e
+        ))),
+    }
+}
+
+/// Copy a 2D RGBA8 or BGRA8 texture to CPU memory, removing the row padding wgpu
+/// requires. Channels are copied in the texture's order.
+pub(super) fn read_texture_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> RenderResult<Vec<u8>> {
+    if !matches!(
+        texture.format(),
+        wgpu::TextureFormat::Rgba8Unorm
+            | wgpu::TextureFormat::Rgba8UnormSrgb
+            | wgpu::TextureFormat::Bgra8Unorm
+            | wgpu::TextureFormat::Bgra8UnormSrgb
+    ) {
+        return Err(RenderError::InvalidTexture(format!(
+            "cannot read back {:?} as 8-bit RGBA",
+            texture.format()
+        )));
+    }
+    let width = texture.width();
+    let height = texture.height();
+    let unpadded_bytes_per_row = 4 * width;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("readback buffer"),
+        size: padded_bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("readback encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    device
+        .poll(wgpu::PollType::wait_indefinitely())
+        .map_err(|e| RenderError::RenderFailed(format!("readback poll failed: {}", e)))?;
+    rx.recv()
+        .map_err(|e| RenderError::RenderFailed(format!("readback channel closed: {}", e)))?
+        .map_err(|e| RenderError::RenderFailed(format!("readback map failed: {}", e)))?;
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let mapped = slice.get_mapped_range();
+        for row in mapped.chunks(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    Ok(pixels)
+}
+
+/// Headless renderer for tests, or `None` when the machine exposes no adapter at all.
+#[cfg(test)]
+pub(crate) fn headless(width: u32, height: u32) -> Option<WgpuRenderer> {
+    headless_with(&HeadlessConfig::new(width, height))
+}
+
+/// Like `headless`, with a full `config`.
+#[cfg(test)]
+pub(crate) fn headless_with(config: &HeadlessConfig) -> Option<WgpuRenderer> {
+    match WgpuRenderer::new_headless(config) {
+        Ok(renderer) => Some(renderer),
+        Err(e) => {
+            eprintln!("skipping headless test: {e}");
+            None
+        }
+    }
+}
+
+/// RGBA8 texel at `(x, y)` of tightly packed `data` rows, for tests.
+#[cfg(test)]
+pub(crate) fn pixel(data: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+    let i = ((y * width + x) * 4) as usize;
+    [data[i], data[i + 1], data[i + 2], data[i + 3]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{headless, pixel};
+    use crate::core::assets::ImageId;
+    use crate::math::{Color, Vec2};
+    use crate::render::{Drawable, Rectangle, RenderContext, Renderer, SpriteDrawData};
+
+    #[test]
+    fn clear_color_is_read_back() {
+        let Some(mut renderer) = headless(8, 4) else {
+            return;
+        };
+        renderer.set_clear_color(Color::RED.to_linear_rgba());
+        renderer.present().unwrap();
+
+        let image = renderer.read_image().unwrap();
+        assert_eq!((image.width, image.height), (8, 4));
+        assert_eq!(image.data.len(), 8 * 4 * 4);
+        assert!(image.data.chunks(4).all(|p| p == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn rectangle_covers_expected_pixels() {
+        let Some(mut renderer) = headless(16, 16) else {
+            return;
+        };
+        let mut ctx = RenderContext::new((16, 16));
+        ctx.clear(Color::BLACK);
+        Rectangle::new(Vec2::new(4.0, 4.0), Vec2::new(8.0, 8.0), Color::BLUE).draw(&mut ctx);
+
+        renderer.set_clear_color(ctx.clear_color.unwrap().to_linear_rgba());
+        renderer.submit(&ctx.vertices);
+        renderer.present().unwrap();
+
+        let data = renderer.read_pixels().unwrap();
+        assert_eq!(pixel(&data, 16, 8, 8), [0, 0, 255, 255]);
+        assert_eq!(pixel(&data, 16, 1, 1), [0, 0, 0, 255]);
+        assert_eq!(pixel(&data, 16, 14, 14), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn sprite_batches_keep_order_across_textures_and_frames() {
+        let Some(mut renderer) = headless(16, 4) else {
+            return;
+        };
+        let red = ImageId::new();
+        let green = ImageId::new();
+        renderer.upload_image(red, 1, 1, &[255, 0, 0, 255]).unwrap();
+        renderer
+            .upload_image(green, 1, 1, &[0, 255, 0, 255])
+            .unwrap();
+
+        // Small frame first, then a larger one that forces the instance buffer to grow.
+        for count in [2u32, 2000] {
+            let mut ctx = RenderContext::new((16, 4));
+            for i in 0..count {
+                // Columns alternate textures; later sprites overwrite earlier ones.
+                let image = if i % 2 == 0 { red } else { green };
+                let mut sprite = SpriteDrawData::new(image, 4, 4);
+                sprite.origin = Vec2::ZERO;
+                sprite.position = Vec2::new((i % 4 * 4) as f32, 0.0);
+                ctx.draw_sprite(sprite);
+            }
+            renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+            renderer.present().unwrap();
+        }
+
+        let data = renderer.read_pixels().unwrap();
+        assert_eq!(pixel(&data, 16, 1, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&data, 16, 5, 1), [0, 255, 0, 255]);
+        assert_eq!(pixel(&data, 16, 9, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&data, 16, 13, 1), [0, 255, 0, 255]);
+    }
+}
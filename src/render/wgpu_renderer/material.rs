@@ -0,0 +1,371 @@
+use super::blend_state;
+use super::stencil;
+use super::{TextureGpu, WgpuRenderer};
+use crate::core::assets::{ImageId, SamplerOptions};
+use crate::render::blend::BlendMode;
+use crate::render::material::{
+    MAX_MATERIAL_TEXTURES, MaterialDescriptor, MaterialId, MaterialKind, MaterialParams,
+};
+use crate::render::renderer::{RenderError, RenderResult};
+use wgpu::util::DeviceExt;
+
+/// Compiled material: one pipeline per blend mode plus its `@group(1)` resources.
+pub(super) struct MaterialGpu {
+    pub kind: MaterialKind,
+    pipelines: Vec<wgpu::RenderPipeline>,
+    pub bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    /// Textures actually bound; `None` slots use the white fallback.
+    bound_textures: [Option<ImageId>; MAX_MATERIAL_TEXTURES],
+}
+
+/// Layouts and fallbacks shared by all materials. Created with the pipelines.
+pub(super) struct MaterialLayouts {
+    material: wgpu::BindGroupLayout,
+    /// Placeholder for `@group(0)` of shape materials, which have no sprite texture.
+    empty: wgpu::BindGroupLayout,
+    pub empty_bind_group: wgpu::BindGroup,
+    /// Also bound by post passes without an extra texture.
+    pub white: TextureGpu,
+}
+
+impl MaterialGpu {
+    pub fn pipeline(&self, blend: BlendMode) -> &wgpu::RenderPipeline {
+        &self.pipelines[blend.index()]
+    }
+}
+
+impl MaterialLayouts {
+    pub fn new(device: &wgpu::Device, white: TextureGpu) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let mut entries = vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ];
+        entries.extend((0..MAX_MATERIAL_TEXTURES as u32).map(|i| texture_entry(2 + i)));
+
+        let material = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("material bind group layout"),
+            entries: &entries,
+        });
+        let empty = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("empty bind group layout"),
+            entries: &[],
+        });
+        let empty_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("empty bind group"),
+            layout: &empty,
+            entries: &[],
+        });
+        Self {
+            material,
+            empty,
+            empty_bind_group,
+            white,
+        }
+    }
+}
+
+impl WgpuRenderer {
+    /// 1x1 white texture sampled by unset material texture slots.
+    pub(super) fn create_white_texture(&self) -> TextureGpu {
+        let texture = self.device().create_texture_with_data(
+            self.queue(),
+            &wgpu::TextureDescriptor {
+                label: Some("white texture"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &[255, 255, 255, 255],
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.texture_gpu(view, &SamplerOptions::default())
+    }
+
+    pub(super) fn compile_material(
+        &mut self,
+        id: MaterialId,
+        descriptor: &MaterialDescriptor,
+    ) -> RenderResult<()> {
+        let module = descriptor.validate()?;
+        let vertex_entry = descriptor.vertex_entry_point(&module);
+        let layouts = self.material_layouts.as_ref().ok_or_else(|| {
+            RenderError::PipelineSetup("material created before renderer init".to_string())
+        })?;
+        let format = self.color_format.ok_or_else(|| {
+            RenderError::PipelineSetup("material created before renderer init".to_string())
+        })?;
+        let device = self.device();
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&descriptor.label),
+            source: wgpu::ShaderSource::Wgsl(descriptor.module_source().into()),
+        });
+
+        let (group0, vertex_layout) = match descriptor.kind {
+            MaterialKind::Sprite => (
+                self.sprite_bind_group_layout
+                    .as_ref()
+                    .expect("sprite bind group layout not initialized"),
+                &self.sprite_instance_buffer_layout,
+            ),
+            MaterialKind::Shape => (&layouts.empty, &self.vertex_buffer_layout),
+        };
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("material pipeline layout"),
+            bind_group_layouts: &[group0, &layouts.material],
+            push_constant_ranges: &[],
+        });
+        let pipelines = BlendMode::ALL
+            .iter()
+            .map(|&blend| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(&descriptor.label),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: Some(vertex_entry),
+                        buffers: std::slice::from_ref(vertex_layout),
+                        compilation_options: Default::default(),
+                    },
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        cull_mode: None,
+                        ..Default::default()
+                    },
+                    depth_stencil: Some(stencil::content_depth_stencil()),
+                    multisample: self.multisample_state(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: Some("fs_main"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: blend_state(blend),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: Default::default(),
+                    }),
+                    cache: None,
+                    multiview: None,
+                })
+            })
+            .collect();
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(RenderError::ShaderCompilation(format!(
+                "material '{}': {}",
+                descriptor.label, error
+            )));
+        }
+
+        self.unpack_images(&descriptor.params.textures);
+        let uniform_buffer = self
+            .device()
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("material uniforms"),
+                contents: bytemuck::cast_slice(&descriptor.params.uniform_bytes()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let (bind_group, bound_textures) =
+            self.material_bind_group(&uniform_buffer, &descriptor.params.textures);
+
+        self.materials.insert(
+            id,
+            MaterialGpu {
+                kind: descriptor.kind,
+                pipelines,
+                bind_group,
+                uniform_buffer,
+                bound_textures,
+            },
+        );
+        Ok(())
+    }
+
+    pub(super) fn update_material(&mut self, id: MaterialId, params: &MaterialParams) {
+        if !self.materials.contains_key(&id) {
+            return;
+        }
+        self.unpack_images(&params.textures);
+        let material = &self.materials[&id];
+        let uniforms = params.uniform_bytes();
+        self.queue()
+            .write_buffer(&material.uniform_buffer, 0, bytemuck::cast_slice(&uniforms));
+        self.counters.upload(std::mem::size_of_val(&uniforms));
+
+        // Rebuild the bind group when the texture set changes, or when a texture that
+        // was missing at bind time has been uploaded since.
+        let wanted = params
+            .textures
+            .map(|slot| slot.filter(|image| self.textures.contains_key(image)));
+        if wanted != material.bound_textures {
+            let (bind_group, bound_textures) =
+                self.material_bind_group(&material.uniform_buffer, &params.textures);
+            let material = self.materials.get_mut(&id).expect("checked above");
+            material.bind_group = bind_group;
+            material.bound_textures = bound_textures;
+        }
+    }
+
+    fn material_bind_group(
+        &self,
+        uniform_buffer: &wgpu::Buffer,
+        textures: &[Option<ImageId>; MAX_MATERIAL_TEXTURES],
+    ) -> (wgpu::BindGroup, [Option<ImageId>; MAX_MATERIAL_TEXTURES]) {
+        let layouts = self
+            .material_layouts
+            .as_ref()
+            .expect("material layouts not initialized");
+        let bound = textures.map(|slot| slot.filter(|image| self.textures.contains_key(image)));
+        let views: Vec<&wgpu::TextureView> = bound
+            .iter()
+            .map(|slot| match slot {
+                Some(image) => &self.textures[image].view,
+                None => &layouts.white.view,
+            })
+            .collect();
+
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&layouts.white.sampler),
+            },
+        ];
+        entries.extend(
+            views
+                .iter()
+                .enumerate()
+                .map(|(i, view)| wgpu::BindGroupEntry {
+                    binding: 2 + i as u32,
+                    resource: wgpu::BindingResource::TextureView(view),
+                }),
+        );
+
+        let bind_group = self.device().create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("material bind group"),
+            layout: &layouts.material,
+            entries: &entries,
+        });
+        (bind_group, bound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::assets::ImageId;
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{
+        Drawable, MaterialDescriptor, MaterialParams, Materials, Rectangle, RenderContext,
+        Renderer, SpriteDrawData,
+    };
+
+    #[test]
+    fn shape_and_sprite_materials_use_their_params() {
+        let Some(mut renderer) = headless(8, 4) else {
+            return;
+        };
+
+        let mut materials = Materials::new();
+        let solid = materials
+            .create(MaterialDescriptor::shape(
+                "solid",
+                r#"
+                @fragment
+                fn fs_main(in: MaterialVsOut) -> @location(0) vec4<f32> {
+                    return material.colors[0];
+                }
+                "#,
+            ))
+            .unwrap();
+        let masked = materials
+            .create(MaterialDescriptor::sprite(
+                "masked",
+                r#"
+                @fragment
+                fn fs_main(in: MaterialVsOut) -> @location(0) vec4<f32> {
+                    let base = textureSample(sprite_texture, sprite_sampler, in.uv);
+                    let mask = textureSample(material_texture0, material_sampler, in.uv);
+                    return base * mask * vec4<f32>(material.floats[0].x, 1.0, 1.0, 1.0);
+                }
+                "#,
+            ))
+            .unwrap();
+        for (id, descriptor) in materials.iter() {
+            renderer.create_material(id, descriptor).unwrap();
+        }
+
+        let white = ImageId::new();
+        let green = ImageId::new();
+        renderer.upload_image(white, 1, 1, &[255; 4]).unwrap();
+        renderer
+            .upload_image(green, 1, 1, &[0, 255, 0, 255])
+            .unwrap();
+
+        let mut ctx = RenderContext::new((8, 4));
+        ctx.clear(Color::BLACK);
+        ctx.set_material_params(solid, MaterialParams::default().with_color(0, Color::BLUE));
+        ctx.set_material_params(
+            masked,
+            MaterialParams::default()
+                .with_float(0, 1.0)
+                .with_texture(0, green),
+        );
+        ctx.with_material(solid, |ctx| {
+            Rectangle::new(Vec2::ZERO, Vec2::new(4.0, 4.0), Color::RED).draw(ctx);
+        });
+        let mut sprite = SpriteDrawData::new(white, 4, 4);
+        sprite.origin = Vec2::ZERO;
+        sprite.position = Vec2::new(4.0, 0.0);
+        sprite.material = Some(masked);
+        ctx.draw_sprite(sprite);
+
+        for (id, params) in ctx.material_params() {
+            renderer.set_material_params(*id, params);
+        }
+        renderer.set_clear_color(Color::BLACK.to_linear_rgba());
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+
+        let data = renderer.read_pixels().unwrap();
+        assert_eq!(&data[(8 + 1) * 4..(8 + 1) * 4 + 4], [0, 0, 255, 255]);
+        assert_eq!(&data[(8 + 6) * 4..(8 + 6) * 4 + 4], [0, 255, 0, 255]);
+    }
+}
@@ -0,0 +1,217 @@
+use super::{DrawBatch, WgpuRenderer, blend_state, stencil};
+use crate::core::assets::ImageId;
+use crate::math::vec2::Vec2;
+use crate::render::TexturedVertex;
+use crate::render::blend::BlendMode;
+
+/// Mesh vertex: position in NDC, texture coordinates (already mapped into atlas pages)
+/// and linear color.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(super) struct MeshVertexGPU {
+    pos: [f32; 2],
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+impl MeshVertexGPU {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x4,
+    ];
+
+    fn buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MeshVertexGPU>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+impl WgpuRenderer {
+    /// Indexed textured triangle pipelines, one per `BlendMode`. They bind textures
+    /// like sprites, so `sprite_bind_group_layout` must exist.
+    pub(super) fn build_mesh_pipelines(
+        &self,
+        format: wgpu::TextureFormat,
+    ) -> Vec<wgpu::RenderPipeline> {
+        let device = self.device();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("mesh shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                r#"
+                struct MeshVsOut {
+                    @builtin(position) pos: vec4<f32>,
+                    @location(0) uv: vec2<f32>,
+                    @location(1) color: vec4<f32>,
+                };
+
+                @group(0) @binding(0) var mesh_tex: texture_2d<f32>;
+                @group(0) @binding(1) var mesh_sampler: sampler;
+
+                @vertex
+                fn vs(
+                    @location(0) pos: vec2<f32>,
+                    @location(1) uv: vec2<f32>,
+                    @location(2) color: vec4<f32>,
+                ) -> MeshVsOut {
+                    var out: MeshVsOut;
+                    out.pos = vec4<f32>(pos, 0.0, 1.0);
+                    out.uv = uv;
+                    out.color = color;
+                    return out;
+                }
+
+                @fragment
+                fn fs(input: MeshVsOut) -> @location(0) vec4<f32> {
+                    return textureSample(mesh_tex, mesh_sampler, input.uv) * input.color;
+                }
+                "#
+                .into(),
+            ),
+        });
+        let bind_group_layout = self
+            .sprite_bind_group_layout
+            .as_ref()
+            .expect("sprite bind group layout not created");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("mesh pipeline layout"),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let vertex_layout = MeshVertexGPU::buffer_layout();
+
+        BlendMode::ALL
+            .iter()
+            .map(|&blend| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("mesh pipeline"),
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: Some("vs"),
+                        buffers: std::slice::from_ref(&vertex_layout),
+                        compilation_options: Default::default(),
+                    },
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        cull_mode: None,
+                        ..Default::default()
+                    },
+                    depth_stencil: Some(stencil::content_depth_stencil()),
+                    multisample: self.multisample_state(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: Some("fs"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: blend_state(blend),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: Default::default(),
+                    }),
+                    cache: None,
+                    multiview: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Queue a mesh for the frame. Meshes of packed images sample their atlas page;
+    /// their UVs are clamped to the image since pages cannot repeat a single image.
+    pub(super) fn queue_mesh(
+        &mut self,
+        vertices: &[TexturedVertex],
+        indices: &[u32],
+        texture: Option<ImageId>,
+        blend: BlendMode,
+    ) {
+        let region = texture.and_then(|id| self.atlas.regions.get(&id).copied());
+        let texture = match region {
+            Some(region) => Some(self.atlas.pages[region.page].id),
+            None => texture,
+        };
+        if texture.is_some_and(|id| !self.textures.contains_key(&id)) {
+            return;
+        }
+
+        let base = self.mesh_vertices.len() as u32;
+        self.mesh_vertices.extend(vertices.iter().map(|v| {
+            let uv = match region {
+                Some(region) => region
+                    .map(Vec2::new(v.uv[0].clamp(0.0, 1.0), v.uv[1].clamp(0.0, 1.0)))
+                    .to_array(),
+                None => v.uv,
+            };
+            MeshVertexGPU {
+                pos: v.pos,
+                uv,
+                color: v.color,
+            }
+        }));
+        let start = self.mesh_indices.len() as u32;
+        self.mesh_indices.extend(indices.iter().map(|&i| base + i));
+        let end = self.mesh_indices.len() as u32;
+
+        // Consecutive meshes sampling the same texture share one indexed draw.
+        match self.batches.last_mut() {
+            Some(DrawBatch::Meshes {
+                texture_id,
+                blend: batch_blend,
+                indices,
+            }) if *texture_id == texture && *batch_blend == blend && indices.end == start => {
+                indices.end = end
+            }
+            _ if start < end => self.batches.push(DrawBatch::Meshes {
+                texture_id: texture,
+                blend,
+                indices: start..end,
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::assets::ImageId;
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{Drawable, Mesh2d, MeshVertex, RenderContext, Renderer};
+
+    #[test]
+    fn textured_meshes_sample_their_image() {
+        let Some(mut renderer) = headless(4, 4) else {
+            return;
+        };
+        // Left half red, right half blue.
+        let image = ImageId::new();
+        let texels = [[255u8, 0, 0, 255], [0, 0, 255, 255]].concat();
+        renderer.upload_image(image, 2, 1, &texels).unwrap();
+
+        let corner = |x: f32, y: f32| {
+            MeshVertex::new(Vec2::new(x, y), Vec2::new(x / 4.0, y / 4.0), Color::WHITE)
+        };
+        let quad = Mesh2d::new(
+            vec![
+                corner(0.0, 0.0),
+                corner(4.0, 0.0),
+                corner(4.0, 4.0),
+                corner(0.0, 4.0),
+            ],
+            vec![0, 1, 2, 0, 2, 3],
+        );
+        let mut ctx = RenderContext::new((4, 4));
+        quad.with_texture(image).draw(&mut ctx);
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+
+        let pixels = renderer.read_pixels().unwrap();
+        let pixel = |x: usize| &pixels[x * 4..x * 4 + 4];
+        assert_eq!(pixel(0), [255, 0, 0, 255]);
+        assert_eq!(pixel(3), [0, 0, 255, 255]);
+        assert_eq!(renderer.render_stats().draw_calls, 1);
+    }
+}
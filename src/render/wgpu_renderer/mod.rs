@@ -0,0 +1,1259 @@
+use crate::backend::surface_provider::SurfaceProvider;
+use crate::backend::window::WindowConfig;
+use crate::core::assets::{ImageAsset, ImageId, SamplerOptions};
+use crate::math::vec2::Vec2;
+use crate::render::Vertex as CoreVertex;
+use crate::render::atlas::AtlasPageStats;
+use crate::render::blend::BlendMode;
+use crate::render::clip::ClipRect;
+use crate::render::gradient::Gradient;
+use crate::render::material::{MaterialDescriptor, MaterialId, MaterialKind, MaterialParams};
+use crate::render::post::PostEffect;
+use crate::render::renderer::{RenderError, RenderResult, Renderer};
+use crate::render::stats::RenderStats;
+use crate::render::{DrawList, GradientVertex, SdfVertex, SpriteDrawData, TexturedVertex};
+use raw_window_handle::{DisplayHandle, RawDisplayHandle, RawWindowHandle, WindowHandle};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+mod atlas;
+mod buffers;
+mod canvas;
+mod gradient;
+mod headless;
+mod material;
+mod mesh;
+mod msaa;
+mod post;
+mod recovery;
+mod sdf;
+mod stats;
+mod stencil;
+mod texture;
+
+use atlas::Atlas;
+use buffers::GrowableBuffer;
+use canvas::CanvasTarget;
+use gradient::GradientFills;
+pub use headless::HeadlessConfig;
+use headless::OffscreenTarget;
+#[cfg(test)]
+pub(crate) use headless::{headless, headless_with, pixel};
+use material::{MaterialGpu, MaterialLayouts};
+use mesh::MeshVertexGPU;
+use post::PostChain;
+use recovery::InitTarget;
+use sdf::SdfVertexGPU;
+use stats::{FrameCounters, GpuTimer};
+use stencil::StencilPipelines;
+use texture::{ImageTexture, TextureGpu};
+
+pub struct WgpuRenderer {
+    size: (u32, u32),
+    instance: Option<wgpu::Instance>,
+    surface: Option<wgpu::Surface<'static>>,
+    adapter: Option<wgpu::Adapter>,
+    device: Option<wgpu::Device>,
+    queue: Option<wgpu::Queue>,
+    config: Option<wgpu::SurfaceConfiguration>,
+    offscreen: Option<OffscreenTarget>,
+    /// Color format of the main target; canvases are created with the same format.
+    color_format: Option<wgpu::TextureFormat>,
+    canvases: HashMap<ImageId, CanvasTarget>,
+    /// MSAA samples per pixel of every pass drawing batches (see `msaa_samples`).
+    sample_count: u32,
+    /// Multisampled color attachments by target size (see `msaa_view`).
+    msaa_views: HashMap<(u32, u32), wgpu::TextureView>,
+    clear_color: wgpu::Color,
+    /// Shape pipelines, one per `BlendMode` (indexed by `BlendMode::index`).
+    pipelines: Vec<wgpu::RenderPipeline>,
+    vertex_buffer_layout: wgpu::VertexBufferLayout<'static>,
+    pending_vertices: Vec<VertexGPU>,
+    vertex_buffer: GrowableBuffer,
+    /// Sprite pipelines, one per `BlendMode`.
+    sprite_pipelines: Vec<wgpu::RenderPipeline>,
+    sprite_instance_buffer_layout: wgpu::VertexBufferLayout<'static>,
+    sprite_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    textures: HashMap<ImageId, TextureGpu>,
+    /// Textures owned by standalone uploaded images, for in-place updates.
+    image_textures: HashMap<ImageId, ImageTexture>,
+    /// Small images packed into shared pages instead of `textures` (see `pack_image`).
+    atlas: Atlas,
+    sprite_instances: Vec<SpriteInstanceGPU>,
+    sprite_instance_buffer: GrowableBuffer,
+    /// Mesh pipelines, one per `BlendMode`.
+    mesh_pipelines: Vec<wgpu::RenderPipeline>,
+    mesh_vertices: Vec<MeshVertexGPU>,
+    /// Mesh triangles, indexing into `mesh_vertices`.
+    mesh_indices: Vec<u32>,
+    mesh_vertex_buffer: GrowableBuffer,
+    mesh_index_buffer: GrowableBuffer,
+    gradient_fills: GradientFills,
+    /// SDF shape pipelines, one per `BlendMode`.
+    sdf_pipelines: Vec<wgpu::RenderPipeline>,
+    sdf_vertices: Vec<SdfVertexGPU>,
+    sdf_vertex_buffer: GrowableBuffer,
+    batches: Vec<DrawBatch>,
+    materials: HashMap<MaterialId, MaterialGpu>,
+    material_layouts: Option<MaterialLayouts>,
+    stencil_pipelines: Option<StencilPipelines>,
+    /// Stencil attachments by target size (see `stencil_view`).
+    stencil_views: HashMap<(u32, u32), wgpu::TextureView>,
+    post: PostChain,
+    /// Set by `capture_next_frame`; the next `present` reads its frame back.
+    capture_requested: bool,
+    captured: Option<ImageAsset>,
+    counters: FrameCounters,
+    /// Set by `set_gpu_timing`; the timer is created once the device exists.
+    gpu_timing: bool,
+    gpu_timer: Option<GpuTimer>,
+    /// Stats of the last presented frame.
+    last_stats: RenderStats,
+    /// What `init` / `init_headless` set up, to rebuild it in `recover`.
+    init_target: Option<InitTarget>,
+    /// Set from the device-lost callback.
+    device_lost: Arc<AtomicBool>,
+}
+
+impl WgpuRenderer {
+    pub fn new() -> Self {
+        WgpuRenderer {
+            size: (0, 0),
+            instance: None,
+            surface: None,
+            adapter: None,
+            device: None,
+            queue: None,
+            config: None,
+            offscreen: None,
+            color_format: None,
+            canvases: HashMap::new(),
+            sample_count: 1,
+            msaa_views: HashMap::new(),
+            clear_color: wgpu::Color::WHITE,
+            pipelines: Vec::new(),
+            vertex_buffer_layout: VertexGPU::buffer_layout(),
+            pending_vertices: Vec::new(),
+            vertex_buffer: GrowableBuffer::new("immediate vb", wgpu::BufferUsages::VERTEX),
+            sprite_pipelines: Vec::new(),
+            sprite_instance_buffer_layout: SpriteInstanceGPU::buffer_layout(),
+            sprite_bind_group_layout: None,
+            textures: HashMap::new(),
+            image_textures: HashMap::new(),
+            atlas: Atlas::new(),
+            sprite_instances: Vec::new(),
+            sprite_instance_buffer: GrowableBuffer::new(
+                "sprite instances",
+                wgpu::BufferUsages::VERTEX,
+            ),
+            mesh_pipelines: Vec::new(),
+            mesh_vertices: Vec::new(),
+            mesh_indices: Vec::new(),
+            mesh_vertex_buffer: GrowableBuffer::new("mesh vertices", wgpu::BufferUsages::VERTEX),
+            mesh_index_buffer: GrowableBuffer::new("mesh indices", wgpu::BufferUsages::INDEX),
+            gradient_fills: GradientFills::default(),
+            sdf_pipelines: Vec::new(),
+            sdf_vertices: Vec::new(),
+            sdf_vertex_buffer: GrowableBuffer::new("sdf vertices", wgpu::BufferUsages::VERTEX),
+            batches: Vec::new(),
+            materials: HashMap::new(),
+            material_layouts: None,
+            stencil_pipelines: None,
+            stencil_views: HashMap::new(),
+            post: PostChain::default(),
+            capture_requested: false,
+            captured: None,
+            counters: FrameCounters::default(),
+            gpu_timing: false,
+            gpu_timer: None,
+            last_stats: RenderStats::default(),
+            init_target: None,
+            device_lost: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn device(&self) -> &wgpu::Device {
+        self.device.as_ref().expect("wgpu device not initialized")
+    }
+    fn queue(&self) -> &wgpu::Queue {
+        self.queue.as_ref().expect("wgpu queue not initialized")
+    }
+    fn surface(&self) -> &wgpu::Surface<'static> {
+        self.surface.as_ref().expect("wgpu surface not initialized")
+    }
+    fn config(&self) -> &wgpu::SurfaceConfiguration {
+        self.config.as_ref().expect("wgpu config not initialized")
+    }
+
+    /// Create the surface for a window, then the device and pipelines drawing to it.
+    fn init_window(
+        &mut self,
+        window: RawWindowHandle,
+        display: RawDisplayHandle,
+        vsync: bool,
+        msaa_samples: u32,
+    ) -> RenderResult<()> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let unsafe_target = wgpu::SurfaceTargetUnsafe::RawHandle {
+            raw_window_handle: window,
+            raw_display_handle: display,
+        };
+        let surface = unsafe { instance.create_surface_unsafe(unsafe_target) }
+            .map_err(|e| RenderError::SurfaceError(format!("create_surface failed: {}", e)))?;
+
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: Some(&surface),
+        }))
+        .map_err(|e| RenderError::InitFailed(format!("no compatible adapter found: {}", e)))?;
+
+        let (device, queue) = request_device(&adapter)?;
+
+        let caps = surface.get_capabilities(&adapter);
+        let present_mode = if vsync {
+            [
+                wgpu::PresentMode::Fifo,
+                wgpu::PresentMode::FifoRelaxed,
+                wgpu::PresentMode::AutoVsync,
+                wgpu::PresentMode::Mailbox,
+            ]
+            .into_iter()
+            .find(|mode| caps.present_modes.iter().any(|m| m == mode))
+            .unwrap_or(caps.present_modes[0])
+        } else {
+            [
+                wgpu::PresentMode::AutoNoVsync,
+                wgpu::PresentMode::Immediate,
+                wgpu::PresentMode::Mailbox,
+            ]
+            .into_iter()
+            .find(|mode| caps.present_modes.iter().any(|m| m == mode))
+            .unwrap_or_else(|| {
+                [
+                    wgpu::PresentMode::Fifo,
+                    wgpu::PresentMode::FifoRelaxed,
+                    wgpu::PresentMode::AutoVsync,
+                ]
+                .into_iter()
+                .find(|mode| caps.present_modes.iter().any(|m| m == mode))
+                .unwrap_or(caps.present_modes[0])
+            })
+        };
+        let format = caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .unwrap_or(caps.formats[0]);
+        let alpha_mode = caps.alpha_modes[0];
+
+        // Copying out of the swapchain is needed for screenshots; not every platform
+        // allows it.
+        let usage =
+            wgpu::TextureUsages::RENDER_ATTACHMENT | (caps.usages & wgpu::TextureUsages::COPY_SRC);
+        let config = wgpu::SurfaceConfiguration {
+            usage,
+            format,
+            width: self.size.0.max(1),
+            height: self.size.1.max(1),
+            present_mode,
+            alpha_mode,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 0,
+        };
+        surface.configure(&device, &config);
+        self.sample_count = msaa::supported_sample_count(&adapter, &device, format, msaa_samples);
+
+        self.instance = Some(instance);
+        self.surface = Some(surface);
+        self.adapter = Some(adapter);
+        self.device = Some(device);
+        self.queue = Some(queue);
+        self.config = Some(config);
+        self.init_target = Some(InitTarget::Window {
+            window,
+            display,
+            vsync,
+            msaa_samples,
+        });
+        self.watch_device_loss();
+        self.build_pipelines(format);
+
+        Ok(())
+    }
+
+    /// Create the shape, sprite, mesh, gradient and SDF pipelines (one per blend mode) for
+    /// the given color target format, drawing with `sample_count` samples.
+    /// Requires the device to be initialized.
+    fn build_pipelines(&mut self, format: wgpu::TextureFormat) {
+        let device = self.device();
+
+        // Inline WGSL shader to render pre-transformed, colored vertices
+        let shader_src = r#"
+            struct VsOut {
+                @builtin(position) pos: vec4<f32>,
+                @location(0) color: vec4<f32>,
+            };
+
+            @vertex
+            fn vs(@location(0) pos: vec2<f32>, @location(1) color: vec4<f32>) -> VsOut {
+                var out: VsOut;
+                out.pos = vec4<f32>(pos, 0.0, 1.0);
+                out.color = color;
+                return out;
+            }
+
+            @fragment
+            fn fs(@location(0) color: vec4<f32>) -> @location(0) vec4<f32> {
+                return color;
+            }
+        "#;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("immediate shader"),
+            source: wgpu::ShaderSource::Wgsl(shader_src.into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("immediate pipeline layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+        let pipelines = BlendMode::ALL
+            .iter()
+            .map(|&blend| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("immediate pipeline"),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: Some("vs"),
+                        buffers: std::slice::from_ref(&self.vertex_buffer_layout),
+                        compilation_options: Default::default(),
+                    },
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        cull_mode: None,
+                        unclipped_depth: false,
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        conservative: false,
+                    },
+                    depth_stencil: Some(stencil::content_depth_stencil()),
+                    multisample: self.multisample_state(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: Some("fs"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: blend_state(blend),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: Default::default(),
+                    }),
+                    cache: None,
+                    multiview: None,
+                })
+            })
+            .collect();
+
+        // Sprite pipeline (textured quads)
+        let sprite_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("sprite bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        // Sprites are drawn instanced: one instance per sprite, six vertices per instance
+        // expanded from the quad corners (two triangles: tl-tr-br, tl-br-bl).
+        let sprite_shader_src = r#"
+            struct SpriteInstance {
+                @location(0) tl: vec2<f32>,
+                @location(1) tr: vec2<f32>,
+                @location(2) br: vec2<f32>,
+                @location(3) bl: vec2<f32>,
+                @location(4) uv_min: vec2<f32>,
+                @location(5) uv_max: vec2<f32>,
+                @location(6) color: vec4<f32>,
+            };
+
+            struct SpriteVsOut {
+                @builtin(position) pos: vec4<f32>,
+                @location(0) uv: vec2<f32>,
+                @location(1) color: vec4<f32>,
+            };
+
+            @group(0) @binding(0) var sprite_tex: texture_2d<f32>;
+            @group(0) @binding(1) var sprite_sampler: sampler;
+
+            @vertex
+            fn vs_main(@builtin(vertex_index) index: u32, inst: SpriteInstance) -> SpriteVsOut {
+                var pos: vec2<f32>;
+                var uv: vec2<f32>;
+                switch index {
+                    case 0u, 3u: {
+                        pos = inst.tl;
+                        uv = inst.uv_min;
+                    }
+                    case 1u: {
+                        pos = inst.tr;
+                        uv = vec2<f32>(inst.uv_max.x, inst.uv_min.y);
+                    }
+                    case 2u, 4u: {
+                        pos = inst.br;
+                        uv = inst.uv_max;
+                    }
+                    default: {
+                        pos = inst.bl;
+                        uv = vec2<f32>(inst.uv_min.x, inst.uv_max.y);
+                    }
+                }
+
+                var out: SpriteVsOut;
+                out.pos = vec4<f32>(pos, 0.0, 1.0);
+                out.uv = uv;
+                out.color = inst.color;
+                return out;
+            }
+
+            @fragment
+            fn fs_main(input: SpriteVsOut) -> @location(0) vec4<f32> {
+                let tex_color = textureSample(sprite_tex, sprite_sampler, input.uv);
+                return tex_color * input.color;
+            }
+        "#;
+        let sprite_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sprite shader"),
+            source: wgpu::ShaderSource::Wgsl(sprite_shader_src.into()),
+        });
+
+        let sprite_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("sprite pipeline layout"),
+                bind_group_layouts: &[&sprite_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let sprite_pipelines = BlendMode::ALL
+            .iter()
+            .map(|&blend| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("sprite pipeline"),
+                    layout: Some(&sprite_pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &sprite_shader,
+                        entry_point: Some("vs_main"),
+                        buffers: std::slice::from_ref(&self.sprite_instance_buffer_layout),
+                        compilation_options: Default::default(),
+                    },
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        cull_mode: None,
+                        unclipped_depth: false,
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        conservative: false,
+                    },
+                    depth_stencil: Some(stencil::content_depth_stencil()),
+                    multisample: self.multisample_state(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &sprite_shader,
+                        entry_point: Some("fs_main"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: blend_state(blend),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: Default::default(),
+                    }),
+                    cache: None,
+                    multiview: None,
+                })
+            })
+            .collect();
+
+        self.color_format = Some(format);
+        self.pipelines = pipelines;
+        self.sprite_bind_group_layout = Some(sprite_bind_group_layout);
+        self.sprite_pipelines = sprite_pipelines;
+        self.mesh_pipelines = self.build_mesh_pipelines(format);
+        self.build_gradient_pipelines(format);
+        self.sdf_pipelines = self.build_sdf_pipelines(format);
+
+        let white = self.create_white_texture();
+        self.material_layouts = Some(MaterialLayouts::new(self.device(), white));
+        self.stencil_pipelines = Some(self.build_stencil_pipelines(format));
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct VertexGPU {
+    pos: [f32; 2],
+    color: [f32; 4],
+}
+
+impl VertexGPU {
+    fn buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<VertexGPU>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x2,
+                    offset: 0,
+                    shader_location: 0,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 8,
+                    shader_location: 1,
+                },
+            ],
+        }
+    }
+}
+
+/// Per-sprite instance data: quad corners in NDC, UV rectangle and linear tint.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpriteInstanceGPU {
+    tl: [f32; 2],
+    tr: [f32; 2],
+    br: [f32; 2],
+    bl: [f32; 2],
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+    color: [f32; 4],
+}
+
+impl SpriteInstanceGPU {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x2,
+        3 => Float32x2,
+        4 => Float32x2,
+        5 => Float32x2,
+        6 => Float32x4,
+    ];
+
+    fn buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteInstanceGPU>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Run of queued draws of one kind, kept in submission order.
+enum DrawBatch {
+    /// `range` is a range into `pending_vertices`.
+    Shapes {
+        material: Option<MaterialId>,
+        blend: BlendMode,
+        range: Range<u32>,
+    },
+    /// Consecutive sprites sharing a texture, material and blend mode: one instanced
+    /// draw call. `instances` is a range into `sprite_instances`.
+    Sprites {
+        texture_id: ImageId,
+        material: Option<MaterialId>,
+        blend: BlendMode,
+        instances: Range<u32>,
+    },
+    /// Consecutive meshes sharing a texture (`None`: untextured) and blend mode: one
+    /// indexed draw call. `indices` is a range into `mesh_indices`.
+    Meshes {
+        texture_id: Option<ImageId>,
+        blend: BlendMode,
+        indices: Range<u32>,
+    },
+    /// Gradient fills sharing a blend mode. `range` is a range into the vertices of
+    /// `gradient_fills`.
+    Gradients { blend: BlendMode, range: Range<u32> },
+    /// SDF shapes sharing a blend mode. `range` is a range into `sdf_vertices`.
+    SdfShapes { blend: BlendMode, range: Range<u32> },
+    /// Scissor rect for the following batches.
+    Clip(Option<ClipRect>),
+    /// Reset the stencil buffer with the full-screen triangle at `clear`, then write
+    /// each mask (ranges into `pending_vertices`, outermost first).
+    Masks {
+        clear: Range<u32>,
+        masks: Vec<Range<u32>>,
+    },
+}
+
+impl Renderer for WgpuRenderer {
+    fn init(
+        &mut self,
+        surface_provider: &dyn SurfaceProvider,
+        config: Option<&WindowConfig>,
+    ) -> RenderResult<()> {
+        self.size = surface_provider.size();
+
+        let wh: WindowHandle = surface_provider
+            .window_handle()
+            .map_err(|e| RenderError::InitFailed(format!("window_handle failed: {}", e)))?;
+        let dh: DisplayHandle = surface_provider
+            .display_handle()
+            .map_err(|e| RenderError::InitFailed(format!("display_handle failed: {}", e)))?;
+        let vsync = config.and_then(|cfg| cfg.vsync).unwrap_or(false);
+        let msaa_samples = config.and_then(|cfg| cfg.msaa_samples).unwrap_or(1);
+        self.init_window(wh.as_raw(), dh.as_raw(), vsync, msaa_samples)
+    }
+
+    fn resize(&mut self, new_size: (u32, u32)) {
+        self.size = new_size;
+        self.stencil_views.clear();
+        self.msaa_views.clear();
+        if self.offscreen.is_some() {
+            self.resize_offscreen();
+            return;
+        }
+        if let (Some(surface), Some(device), Some(config)) = (
+            self.surface.as_ref(),
+            self.device.as_ref(),
+            self.config.as_mut(),
+        ) {
+            config.width = self.size.0.max(1);
+            config.height = self.size.1.max(1);
+            surface.configure(device, config);
+        }
+    }
+
+    fn present(&mut self) -> RenderResult<()> {
+        // A request only applies to this frame, even if it is not presented.
+        let capture = std::mem::take(&mut self.capture_requested);
+        if self.is_device_lost() {
+            self.skip_frame();
+            return Err(RenderError::DeviceLost);
+        }
+        self.ensure_gpu_timer();
+        self.upload_frame_buffers();
+        if self.offscreen.is_some() {
+            return self.present_offscreen(capture);
+        }
+
+        let size = (self.config().width, self.config().height);
+        let stencil = self.stencil_view(size);
+        let msaa = self.msaa_view(size);
+        let scene = self.prepare_post(size);
+        let surface = self.surface();
+        let device = self.device();
+        let queue = self.queue();
+        let config = self.config();
+
+        let frame = match surface.get_current_texture() {
+            Ok(frame) => Ok(Some(frame)),
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                surface.configure(device, config);
+                surface.get_current_texture().map(Some).map_err(|e| {
+                    RenderError::SurfaceError(format!("get_current_texture after Lost: {}", e))
+                })
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                log::error!("GPU memory exhausted in present");
+                Err(RenderError::OutOfMemory)
+            }
+            // The compositor did not hand out a frame in time: skip this one.
+            Err(wgpu::SurfaceError::Timeout) => Ok(None),
+            Err(e) => Err(RenderError::SurfaceError(e.to_string())),
+        };
+        let frame = match frame {
+            Ok(Some(frame)) => frame,
+            skipped => {
+                self.skip_frame();
+                return skipped.map(|_| ());
+            }
+        };
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("clear encoder"),
+        });
+        self.encode_frame(
+            &mut encoder,
+            &view,
+            scene.as_ref(),
+            msaa.as_ref(),
+            &stencil,
+            size,
+        );
+        self.resolve_gpu_timer(&mut encoder);
+
+        queue.submit(std::iter::once(encoder.finish()));
+        let captured = capture.then(|| self.read_surface_frame(&frame.texture));
+        frame.present();
+        self.captured = captured.flatten();
+        self.finish_frame_stats();
+        self.clear_frame();
+        Ok(())
+    }
+    fn submit(&mut self, vertices: &[CoreVertex]) {
+        self.submit_shapes(vertices, None, BlendMode::Alpha);
+    }
+
+    fn submit_shapes(
+        &mut self,
+        vertices: &[CoreVertex],
+        material: Option<MaterialId>,
+        blend: BlendMode,
+    ) {
+        let start = self.pending_vertices.len() as u32;
+        for v in vertices.iter().copied() {
+            self.pending_vertices.push(VertexGPU {
+                pos: v.pos,
+                color: v.color,
+            });
+        }
+        let end = self.pending_vertices.len() as u32;
+
+        match self.batches.last_mut() {
+            Some(DrawBatch::Shapes {
+                material: batch_material,
+                blend: batch_blend,
+                range,
+            }) if *batch_material == material && *batch_blend == blend && range.end == start => {
+                range.end = end
+            }
+            _ if start < end => self.batches.push(DrawBatch::Shapes {
+                material,
+                blend,
+                range: start..end,
+            }),
+            _ => {}
+        }
+    }
+    fn set_clip_rect(&mut self, rect: Option<ClipRect>) {
+        self.batches.push(DrawBatch::Clip(rect));
+    }
+
+    fn set_stencil_masks(&mut self, masks: &[&[CoreVertex]]) {
+        let mut push = |vertices: &[[f32; 2]]| {
+            let start = self.pending_vertices.len() as u32;
+            self.pending_vertices
+                .extend(vertices.iter().map(|&pos| VertexGPU {
+                    pos,
+                    color: [0.0; 4],
+                }));
+            start..self.pending_vertices.len() as u32
+        };
+        let clear = push(&[[-1.0, -1.0], [3.0, -1.0], [-1.0, 3.0]]);
+        let masks = masks
+            .iter()
+            .map(|mask| push(&mask.iter().map(|v| v.pos).collect::<Vec<_>>()))
+            .collect();
+        self.batches.push(DrawBatch::Masks { clear, masks });
+    }
+
+    fn set_clear_color(&mut self, rgba: [f32; 4]) {
+        self.clear_color = wgpu::Color {
+            r: rgba[0] as f64,
+            g: rgba[1] as f64,
+            b: rgba[2] as f64,
+            a: rgba[3] as f64,
+        };
+    }
+
+    fn upload_image(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> RenderResult<()> {
+        self.upload_texture(id, width, height, data, &SamplerOptions::default())
+    }
+
+    fn upload_image_with_sampler(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        self.upload_texture(id, width, height, data, sampler)
+    }
+
+    fn update_image_region(
+        &mut self,
+        id: ImageId,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> RenderResult<()> {
+        self.update_texture_region(id, x, y, width, height, data)
+    }
+
+    fn unload_image(&mut self, id: ImageId) {
+        self.remove_texture(id);
+    }
+
+    fn create_material(
+        &mut self,
+        id: MaterialId,
+        descriptor: &MaterialDescriptor,
+    ) -> RenderResult<()> {
+        self.compile_material(id, descriptor)
+    }
+
+    fn set_material_params(&mut self, id: MaterialId, params: &MaterialParams) {
+        self.update_material(id, params);
+    }
+
+    fn create_canvas(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        self.create_canvas_target(id, width, height, sampler)
+    }
+
+    fn render_to_canvas(
+        &mut self,
+        id: ImageId,
+        clear_color: Option<[f32; 4]>,
+        list: &DrawList<'_>,
+    ) -> RenderResult<()> {
+        self.render_canvas(id, clear_color, list)
+    }
+
+    fn capture_next_frame(&mut self) {
+        self.capture_requested = true;
+    }
+
+    fn take_captured_frame(&mut self) -> Option<ImageAsset> {
+        self.captured.take()
+    }
+
+    fn set_post_effects(&mut self, effects: &[PostEffect]) -> RenderResult<()> {
+        self.set_post_chain(effects)
+    }
+
+    fn atlas_stats(&self) -> Vec<AtlasPageStats> {
+        self.atlas_page_stats()
+    }
+
+    fn render_stats(&self) -> RenderStats {
+        self.last_stats.clone()
+    }
+
+    fn set_gpu_timing(&mut self, enabled: bool) {
+        self.enable_gpu_timing(enabled);
+    }
+
+    fn recover(&mut self) -> RenderResult<()> {
+        self.recreate()
+    }
+
+    fn draw_mesh(
+        &mut self,
+        vertices: &[TexturedVertex],
+        indices: &[u32],
+        texture: Option<ImageId>,
+        blend: BlendMode,
+    ) {
+        self.queue_mesh(vertices, indices, texture, blend);
+    }
+
+    fn fill_gradient(
+        &mut self,
+        vertices: &[GradientVertex],
+        gradient: &Gradient,
+        blend: BlendMode,
+    ) {
+        self.queue_gradient(vertices, gradient, blend);
+    }
+
+    fn draw_sdf_shapes(&mut self, vertices: &[SdfVertex], blend: BlendMode) {
+        self.queue_sdf_shapes(vertices, blend);
+    }
+
+    fn draw_sprites(&mut self, sprites: &[SpriteDrawData], viewport_size: (u32, u32)) {
+        let (w, h) = (viewport_size.0.max(1) as f32, viewport_size.1.max(1) as f32);
+        let to_ndc = |p: Vec2| -> [f32; 2] { [(p.x / w) * 2.0 - 1.0, 1.0 - (p.y / h) * 2.0] };
+
+        for sprite in sprites {
+            let (texture, uv_min, uv_max) =
+                self.sprite_source(sprite.image_id, sprite.uv_min, sprite.uv_max);
+            if !self.textures.contains_key(&texture) {
+                continue;
+            }
+
+            // Calculate world corners from sprite data
+            let [tl, tr, br, bl] = self.compute_sprite_corners(sprite).map(to_ndc);
+
+            let index = self.sprite_instances.len() as u32;
+            self.sprite_instances.push(SpriteInstanceGPU {
+                tl,
+                tr,
+                br,
+                bl,
+                uv_min: uv_min.to_array(),
+                uv_max: uv_max.to_array(),
+                color: sprite.tint.to_linear_rgba(),
+            });
+
+            // Extend the current batch while the texture stays the same, so runs such
+            // as text glyphs or particles from one atlas become a single draw call.
+            match self.batches.last_mut() {
+                Some(DrawBatch::Sprites {
+                    texture_id,
+                    material,
+                    blend,
+                    instances,
+                }) if *texture_id == texture
+                    && *material == sprite.material
+                    && *blend == sprite.blend
+                    && instances.end == index =>
+                {
+                    instances.end = index + 1;
+                }
+                _ => self.batches.push(DrawBatch::Sprites {
+                    texture_id: texture,
+                    material: sprite.material,
+                    blend: sprite.blend,
+                    instances: index..index + 1,
+                }),
+            }
+        }
+    }
+}
+
+impl WgpuRenderer {
+    /// Record a render pass targeting `view` (`size` pixels), drawing queued batches in
+    /// submission order. With MSAA, batches are drawn into `msaa` and resolved into
+    /// `view`. `stencil` must come from `stencil_view(size)`; `label` names the pass in
+    /// GPU timings.
+    #[allow(clippy::too_many_arguments)]
+    fn encode_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        msaa: Option<&wgpu::TextureView>,
+        stencil: &wgpu::TextureView,
+        size: (u32, u32),
+        load: wgpu::LoadOp<wgpu::Color>,
+        label: &'static str,
+    ) {
+        let (view, resolve_target) = match msaa {
+            Some(msaa) => (msaa, Some(view)),
+            None => (view, None),
+        };
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("main pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: stencil,
+                depth_ops: None,
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+            }),
+            timestamp_writes: self.timestamp_writes(label),
+            occlusion_query_set: None,
+        });
+
+        let full = ClipRect::new(0, 0, size.0.max(1), size.1.max(1));
+        let mut clip = full;
+
+        for batch in &self.batches {
+            match batch {
+                DrawBatch::Shapes {
+                    material,
+                    blend,
+                    range,
+                } => {
+                    let Some(vb) = self.vertex_buffer.buffer() else {
+                        continue;
+                    };
+                    match self.material(*material, MaterialKind::Shape) {
+                        Some(material) => {
+                            let layouts = self.material_layouts.as_ref().unwrap();
+                            rpass.set_pipeline(material.pipeline(*blend));
+                            rpass.set_bind_group(0, &layouts.empty_bind_group, &[]);
+                            rpass.set_bind_group(1, &material.bind_group, &[]);
+                            self.counters.bind_groups(2);
+                        }
+                        None => rpass.set_pipeline(&self.pipelines[blend.index()]),
+                    }
+                    rpass.set_vertex_buffer(0, vb.slice(..));
+                    rpass.draw(range.clone(), 0..1);
+                    self.counters.draw();
+                }
+                DrawBatch::Sprites {
+                    texture_id,
+                    material,
+                    blend,
+                    instances,
+                } => {
+                    let (Some(texture), Some(ib)) = (
+                        self.textures.get(texture_id),
+                        self.sprite_instance_buffer.buffer(),
+                    ) else {
+                        continue;
+                    };
+                    match self.material(*material, MaterialKind::Sprite) {
+                        Some(material) => {
+                            rpass.set_pipeline(material.pipeline(*blend));
+                            rpass.set_bind_group(1, &material.bind_group, &[]);
+                            self.counters.bind_groups(1);
+                        }
+                        None => rpass.set_pipeline(&self.sprite_pipelines[blend.index()]),
+                    }
+                    rpass.set_bind_group(0, &texture.bind_group, &[]);
+                    self.counters.bind_groups(1);
+                    rpass.set_vertex_buffer(0, ib.slice(..));
+                    rpass.draw(0..6, instances.clone());
+                    self.counters.draw();
+                }
+                DrawBatch::Meshes {
+                    texture_id,
+                    blend,
+                    indices,
+                } => {
+                    let bind_group = match texture_id {
+                        Some(id) => self.textures.get(id).map(|texture| &texture.bind_group),
+                        None => self
+                            .material_layouts
+                            .as_ref()
+                            .map(|layouts| &layouts.white.bind_group),
+                    };
+                    let (Some(bind_group), Some(vb), Some(ib)) = (
+                        bind_group,
+                        self.mesh_vertex_buffer.buffer(),
+                        self.mesh_index_buffer.buffer(),
+                    ) else {
+                        continue;
+                    };
+                    rpass.set_pipeline(&self.mesh_pipelines[blend.index()]);
+                    rpass.set_bind_group(0, bind_group, &[]);
+                    self.counters.bind_groups(1);
+                    rpass.set_vertex_buffer(0, vb.slice(..));
+                    rpass.set_index_buffer(ib.slice(..), wgpu::IndexFormat::Uint32);
+                    rpass.draw_indexed(indices.clone(), 0, 0..1);
+                    self.counters.draw();
+                }
+                DrawBatch::Gradients { blend, range } => {
+                    if self.gradient_fills.draw(&mut rpass, *blend, range.clone()) {
+                        self.counters.bind_groups(1);
+                        self.counters.draw();
+                    }
+                }
+                DrawBatch::SdfShapes { blend, range } => {
+                    let Some(vb) = self.sdf_vertex_buffer.buffer() else {
+                        continue;
+                    };
+                    rpass.set_pipeline(&self.sdf_pipelines[blend.index()]);
+                    rpass.set_vertex_buffer(0, vb.slice(..));
+                    rpass.draw(range.clone(), 0..1);
+                    self.counters.draw();
+                }
+                DrawBatch::Clip(rect) => {
+                    clip = rect.map_or(full, |rect| rect.clamp_to(size));
+                    // Empty clips are skipped by `submit_draw_list`; keep the scissor valid.
+                    if !clip.is_empty() {
+                        rpass.set_scissor_rect(clip.x, clip.y, clip.width, clip.height);
+                    }
+                }
+                DrawBatch::Masks { clear, masks } => {
+                    let (Some(vb), Some(stencil)) =
+                        (self.vertex_buffer.buffer(), self.stencil_pipelines.as_ref())
+                    else {
+                        continue;
+                    };
+                    rpass.set_vertex_buffer(0, vb.slice(..));
+                    // Masks are not clipped: reset and write them over the whole target.
+                    rpass.set_scissor_rect(0, 0, full.width, full.height);
+                    rpass.set_pipeline(&stencil.clear);
+                    rpass.draw(clear.clone(), 0..1);
+                    self.counters.draw();
+                    rpass.set_pipeline(&stencil.write);
+                    for (depth, mask) in masks.iter().enumerate() {
+                        rpass.set_stencil_reference(depth as u32);
+                        rpass.draw(mask.clone(), 0..1);
+                        self.counters.draw();
+                    }
+                    rpass.set_stencil_reference(masks.len() as u32);
+                    if !clip.is_empty() {
+                        rpass.set_scissor_rect(clip.x, clip.y, clip.width, clip.height);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record the main pass and post-processing for a frame presented to `view`.
+    /// `scene` is the post chain's input target from `prepare_post`, if any; `msaa`
+    /// comes from `msaa_view(size)`.
+    fn encode_frame(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        scene: Option<&wgpu::TextureView>,
+        msaa: Option<&wgpu::TextureView>,
+        stencil: &wgpu::TextureView,
+        size: (u32, u32),
+    ) {
+        let load = wgpu::LoadOp::Clear(self.clear_color);
+        match scene {
+            Some(scene) => {
+                self.encode_pass(encoder, scene, msaa, stencil, size, load, "scene");
+                self.encode_post(encoder, view);
+            }
+            None => self.encode_pass(encoder, view, msaa, stencil, size, load, "scene"),
+        }
+    }
+
+    /// Copy this frame's shape vertices, sprite instances, meshes, gradient fills and SDF
+    /// shapes into the persistent GPU buffers. Must run before `encode_pass`.
+    fn upload_frame_buffers(&mut self) {
+        let (Some(device), Some(queue)) = (self.device.as_ref(), self.queue.as_ref()) else {
+            return;
+        };
+        let vertex_bytes = std::mem::size_of_val(self.pending_vertices.as_slice());
+        let instance_bytes = std::mem::size_of_val(self.sprite_instances.as_slice());
+        let mesh_bytes = std::mem::size_of_val(self.mesh_vertices.as_slice())
+            + std::mem::size_of_val(self.mesh_indices.as_slice());
+        let sdf_bytes = std::mem::size_of_val(self.sdf_vertices.as_slice());
+        self.counters.geometry(
+            self.pending_vertices.len()
+                + self.mesh_vertices.len()
+                + self.gradient_fills.vertices.len()
+                + self.sdf_vertices.len(),
+            self.sprite_instances.len(),
+        );
+        self.counters
+            .upload(vertex_bytes + instance_bytes + mesh_bytes + sdf_bytes);
+        self.vertex_buffer
+            .write(device, queue, bytemuck::cast_slice(&self.pending_vertices));
+        self.sprite_instance_buffer.write(
+            device,
+            queue,
+            bytemuck::cast_slice(&self.sprite_instances),
+        );
+        self.mesh_vertex_buffer
+            .write(device, queue, bytemuck::cast_slice(&self.mesh_vertices));
+        self.mesh_index_buffer
+            .write(device, queue, bytemuck::cast_slice(&self.mesh_indices));
+        self.sdf_vertex_buffer
+            .write(device, queue, bytemuck::cast_slice(&self.sdf_vertices));
+        let gradient_bytes = self.gradient_fills.upload(device, queue);
+        self.counters.upload(gradient_bytes);
+    }
+
+    /// Compiled material for `id` if it exists and matches `kind`; draws fall back to
+    /// the default pipeline otherwise.
+    fn material(&self, id: Option<MaterialId>, kind: MaterialKind) -> Option<&MaterialGpu> {
+        self.materials
+            .get(&id?)
+            .filter(|material| material.kind == kind)
+    }
+
+    /// Drop everything queued for the frame that was just presented.
+    /// Drop the queued draws and counters of a frame that is not presented, so they do
+    /// not end up in the next one.
+    fn skip_frame(&mut self) {
+        self.discard_frame_stats();
+        self.clear_frame();
+    }
+
+    fn clear_frame(&mut self) {
+        self.pending_vertices.clear();
+        self.sprite_instances.clear();
+        self.mesh_vertices.clear();
+        self.mesh_indices.clear();
+        self.gradient_fills.clear();
+        self.sdf_vertices.clear();
+        self.batches.clear();
+    }
+
+    /// Compute world-space corners of a sprite quad from draw data, in the order the
+    /// UV region's corners are applied (see `SpriteDrawData::textured_corners`).
+    fn compute_sprite_corners(&self, sprite: &SpriteDrawData) -> [Vec2; 4] {
+        sprite.textured_corners()
+    }
+}
+
+/// Fixed-function blend state for `mode`. Blending happens in linear space (sRGB
+/// targets); see `BlendMode` for the equations.
+fn blend_state(mode: BlendMode) -> Option<wgpu::BlendState> {
+    use wgpu::{BlendComponent, BlendFactor, BlendOperation};
+
+    let color = |src_factor, dst_factor| BlendComponent {
+        src_factor,
+        dst_factor,
+        operation: BlendOperation::Add,
+    };
+    let color = match mode {
+        BlendMode::Alpha => color(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+        BlendMode::Additive => color(BlendFactor::SrcAlpha, BlendFactor::One),
+        BlendMode::Multiply => color(BlendFactor::Dst, BlendFactor::OneMinusSrcAlpha),
+        BlendMode::Screen => color(BlendFactor::One, BlendFactor::OneMinusSrc),
+        BlendMode::PremultipliedAlpha => color(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+        BlendMode::Opaque => return None,
+    };
+    Some(wgpu::BlendState {
+        color,
+        alpha: BlendComponent::OVER,
+    })
+}
+
+/// Request a logical device with the engine's default limits, plus timestamp queries
+/// (see `Renderer::set_gpu_timing`) and adapter-specific MSAA sample counts when the
+/// adapter has them.
+fn request_device(adapter: &wgpu::Adapter) -> RenderResult<(wgpu::Device, wgpu::Queue)> {
+    let optional_features =
+        wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+        required_features: adapter.features() & optional_features,
+        required_limits: wgpu::Limits::default(),
+        label: Some("RustyEngine Device"),
+        trace: wgpu::Trace::default(),
+        experimental_features: wgpu::ExperimentalFeatures::default(),
+        memory_hints: wgpu::MemoryHints::default(),
+    }))
+    .map_err(|e| RenderError::InitFailed(format!("request_device failed: {}", e)))
+}
@@ -0,0 +1,133 @@
+use super::WgpuRenderer;
+use super::stencil::STENCIL_FORMAT;
+
+/// Highest sample count up to `requested` usable for color attachments of `format`
+/// and the stencil attachment. Devices without adapter-specific format features only
+/// allow 1 and 4 samples.
+pub(super) fn supported_sample_count(
+    adapter: &wgpu::Adapter,
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    requested: u32,
+) -> u32 {
+    let adapter_specific = device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+    let supported = |count: u32| {
+        if !adapter_specific {
+            return count == 1 || count == 4;
+        }
+        [format, STENCIL_FORMAT].iter().all(|&format| {
+            adapter
+                .get_texture_format_features(format)
+                .flags
+                .sample_count_supported(count)
+        })
+    };
+    let count = [16, 8, 4, 2]
+        .into_iter()
+        .find(|&count| count <= requested && supported(count))
+        .unwrap_or(1);
+    if count != requested.max(1) {
+        log::warn!(
+            "{} MSAA samples are not supported for {:?}; using {}",
+            requested,
+            format,
+            count
+        );
+    }
+    count
+}
+
+impl WgpuRenderer {
+    /// Samples per pixel of the color and stencil attachments every pipeline draws to;
+    /// `1` without MSAA.
+    pub fn msaa_samples(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Multisample state of the pipelines drawing queued batches.
+    pub(super) fn multisample_state(&self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count,
+            ..Default::default()
+        }
+    }
+
+    /// Multisampled color texture of `size` pixels, resolved into the actual target at
+    /// the end of each pass. Targets of the same size share one texture, so only
+    /// passes that clear it may use it. `None` without MSAA.
+    pub(super) fn msaa_view(&mut self, size: (u32, u32)) -> Option<wgpu::TextureView> {
+        if self.sample_count <= 1 {
+            return None;
+        }
+        let size = (size.0.max(1), size.1.max(1));
+        if let Some(view) = self.msaa_views.get(&size) {
+            return Some(view.clone());
+        }
+        let view = self
+            .create_msaa_texture(size, "msaa color")
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.msaa_views.insert(size, view.clone());
+        Some(view)
+    }
+
+    /// Multisampled color texture of `size` pixels in the main color format.
+    pub(super) fn create_msaa_texture(
+        &self,
+        size: (u32, u32),
+        label: &'static str,
+    ) -> wgpu::Texture {
+        self.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size.0.max(1),
+                height: size.1.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.color_format.expect("renderer not initialized"),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::headless_with;
+    use crate::render::{Drawable, HeadlessConfig, RenderContext, Renderer, Triangle};
+
+    #[test]
+    fn msaa_smooths_triangle_edges() {
+        let config = HeadlessConfig::new(8, 8).with_msaa_samples(4);
+        let Some(mut renderer) = headless_with(&config) else {
+            return;
+        };
+        if renderer.msaa_samples() == 1 {
+            eprintln!("skipping MSAA test: not supported by the adapter");
+            return;
+        }
+        renderer.set_clear_color([0.0, 0.0, 0.0, 1.0]);
+        let mut ctx = RenderContext::new((8, 8));
+        Triangle::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(8.0, 0.0),
+            Vec2::new(0.0, 8.0),
+            Color::WHITE,
+        )
+        .draw(&mut ctx);
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+
+        // Pixels on the diagonal are half covered; away from it they are not blended.
+        let pixels = renderer.read_pixels().unwrap();
+        let red = |x: usize, y: usize| pixels[(y * 8 + x) * 4];
+        assert_eq!(red(1, 1), 255);
+        assert_eq!(red(6, 6), 0);
+        assert!((1..255).contains(&red(3, 4)), "edge pixel {}", red(3, 4));
+    }
+}
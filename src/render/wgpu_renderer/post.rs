@@ -0,0 +1,585 @@
+use super::WgpuRenderer;
+use crate::core::assets::ImageId;
+use crate::render::post::{PostEffect, post_module_source};
+use crate::render::renderer::{RenderError, RenderResult};
+use std::collections::HashMap;
+
+/// Intermediate targets needed at most: effect input, effect output, two temporaries.
+const POST_TARGETS: usize = 4;
+
+/// Post-processing state: the enabled effects, their compiled shaders and the
+/// screen-sized targets the chain ping-pongs between.
+#[derive(Default)]
+pub(super) struct PostChain {
+    effects: Vec<PostEffect>,
+    /// Pipelines keyed by shader body (built-in constant or user source).
+    pipelines: HashMap<String, wgpu::RenderPipeline>,
+    layout: Option<wgpu::BindGroupLayout>,
+    sampler: Option<wgpu::Sampler>,
+    targets: Vec<wgpu::TextureView>,
+    target_size: (u32, u32),
+    uniforms: Vec<wgpu::Buffer>,
+    /// Passes of the frame being presented, built by `prepare_post`.
+    passes: Vec<PreparedPass>,
+}
+
+struct PreparedPass {
+    shader: String,
+    bind_group: wgpu::BindGroup,
+    /// Target index, or `None` for the frame's final view.
+    dest: Option<usize>,
+}
+
+/// Where a pass reads from or writes to, relative to its effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Input,
+    Temp(usize),
+    Output,
+}
+
+struct PassSpec<'a> {
+    shader: &'a str,
+    floats: [f32; 16],
+    source: Slot,
+    dest: Slot,
+    texture: Option<ImageId>,
+}
+
+impl<'a> PassSpec<'a> {
+    fn new(shader: &'a str, params: &[f32], source: Slot, dest: Slot) -> Self {
+        let mut floats = [0.0; 16];
+        floats[..params.len()].copy_from_slice(params);
+        Self {
+            shader,
+            floats,
+            source,
+            dest,
+            texture: None,
+        }
+    }
+}
+
+/// Passes implementing `effect`. Every effect reads `Input` and ends in `Output`.
+fn passes(effect: &PostEffect) -> Vec<PassSpec<'_>> {
+    use Slot::{Input, Output, Temp};
+    match effect {
+        PostEffect::Bloom {
+            threshold,
+            intensity,
+            radius,
+        } => vec![
+            PassSpec::new(THRESHOLD, &[*threshold], Input, Temp(0)),
+            PassSpec::new(BLUR, &[1.0, 0.0, *radius], Temp(0), Temp(1)),
+            PassSpec::new(BLUR, &[0.0, 1.0, *radius], Temp(1), Temp(0)),
+            PassSpec::new(BLOOM_COMPOSITE, &[*intensity], Temp(0), Output),
+        ],
+        PostEffect::Vignette {
+            intensity,
+            radius,
+            softness,
+        } => vec![PassSpec::new(
+            VIGNETTE,
+            &[*intensity, *radius, *softness],
+            Input,
+            Output,
+        )],
+        PostEffect::ColorGrading { lut, strength } => vec![PassSpec {
+            texture: Some(*lut),
+            ..PassSpec::new(COLOR_GRADING, &[*strength], Input, Output)
+        }],
+        PostEffect::Crt {
+            scanline_intensity,
+            curvature,
+        } => vec![PassSpec::new(
+            CRT,
+            &[*scanline_intensity, *curvature],
+            Input,
+            Output,
+        )],
+        PostEffect::ChromaticAberration { offset } => vec![PassSpec::new(
+            CHROMATIC_ABERRATION,
+            &[*offset],
+            Input,
+            Output,
+        )],
+        PostEffect::Blur { radius } => vec![
+            PassSpec::new(BLUR, &[1.0, 0.0, *radius], Input, Temp(0)),
+            PassSpec::new(BLUR, &[0.0, 1.0, *radius], Temp(0), Output),
+        ],
+        PostEffect::Custom(shader) => vec![PassSpec {
+            floats: shader.floats,
+            texture: shader.texture,
+            ..PassSpec::new(&shader.source, &[], Input, Output)
+        }],
+    }
+}
+
+impl WgpuRenderer {
+    /// Replace the post-processing chain, compiling shaders not seen before.
+    pub(super) fn set_post_chain(&mut self, effects: &[PostEffect]) -> RenderResult<()> {
+        let format = self.color_format.ok_or_else(|| {
+            RenderError::PipelineSetup("post effects set before renderer init".to_string())
+        })?;
+        if self.post.layout.is_none() {
+            self.create_post_layout();
+        }
+
+        for effect in effects {
+            for spec in passes(effect) {
+                if !self.post.pipelines.contains_key(spec.shader) {
+                    let pipeline = self.compile_post_shader(spec.shader, format)?;
+                    self.post
+                        .pipelines
+                        .insert(spec.shader.to_string(), pipeline);
+                }
+            }
+        }
+        self.post.effects = effects.to_vec();
+        Ok(())
+    }
+
+    fn create_post_layout(&mut self) {
+        let device = self.device();
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("post bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                texture_entry(2),
+                texture_entry(3),
+                texture_entry(4),
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("post sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        self.post.layout = Some(layout);
+        self.post.sampler = Some(sampler);
+    }
+
+    fn compile_post_shader(
+        &self,
+        source: &str,
+        format: wgpu::TextureFormat,
+    ) -> RenderResult<wgpu::RenderPipeline> {
+        let device = self.device();
+        let layout = self
+            .post
+            .layout
+            .as_ref()
+            .expect("post layout created above");
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("post shader"),
+            source: wgpu::ShaderSource::Wgsl(post_module_source(source).into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("post pipeline layout"),
+            bind_group_layouts: &[layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("post pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("post_vs"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            cache: None,
+            multiview: None,
+        });
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(RenderError::ShaderCompilation(format!(
+                "post effect: {}",
+                error
+            )));
+        }
+        Ok(pipeline)
+    }
+
+    /// Build this frame's post passes for a target of `size` pixels. Returns the view
+    /// the main pass must render into instead of the final one, or `None` when no
+    /// effect is enabled.
+    pub(super) fn prepare_post(&mut self, size: (u32, u32)) -> Option<wgpu::TextureView> {
+        self.post.passes.clear();
+        if self.post.effects.is_empty() {
+            return None;
+        }
+        if self.post.target_size != size {
+            self.post.targets.clear();
+            self.post.target_size = size;
+        }
+        while self.post.targets.len() < POST_TARGETS {
+            let view = self.create_post_target(size);
+            self.post.targets.push(view);
+        }
+
+        // Resolve slots to targets: each effect reads the previous effect's output.
+        let effects = std::mem::take(&mut self.post.effects);
+        let mut plans = Vec::new();
+        let mut input = 0;
+        for (i, effect) in effects.iter().enumerate() {
+            let free: Vec<usize> = (0..POST_TARGETS).filter(|&t| t != input).collect();
+            let output = (i + 1 < effects.len()).then_some(free[0]);
+            let resolve = |slot| match slot {
+                Slot::Input => Some(input),
+                Slot::Temp(n) => Some(free[1 + n]),
+                Slot::Output => output,
+            };
+            for spec in passes(effect) {
+                let source = resolve(spec.source).expect("passes never read their output");
+                let dest = resolve(spec.dest);
+                plans.push((spec, source, input, dest));
+            }
+            input = output.unwrap_or(input);
+        }
+
+        let textures: Vec<_> = plans.iter().map(|(spec, ..)| spec.texture).collect();
+        self.unpack_images(&textures);
+        while self.post.uniforms.len() < plans.len() {
+            let buffer = self.device().create_buffer(&wgpu::BufferDescriptor {
+                label: Some("post uniforms"),
+                size: std::mem::size_of::<[f32; 20]>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            self.post.uniforms.push(buffer);
+        }
+
+        let passes = plans
+            .iter()
+            .enumerate()
+            .map(|(i, (spec, source, original, dest))| {
+                let mut uniforms = [0.0f32; 20];
+                uniforms[..16].copy_from_slice(&spec.floats);
+                uniforms[16] = size.0 as f32;
+                uniforms[17] = size.1 as f32;
+                self.queue().write_buffer(
+                    &self.post.uniforms[i],
+                    0,
+                    bytemuck::cast_slice(&uniforms),
+                );
+                self.counters.upload(std::mem::size_of_val(&uniforms));
+                PreparedPass {
+                    shader: spec.shader.to_string(),
+                    bind_group: self.post_bind_group(i, *source, *original, spec.texture),
+                    dest: *dest,
+                }
+            })
+            .collect();
+        drop(plans);
+        self.post.passes = passes;
+        self.post.effects = effects;
+        Some(self.post.targets[0].clone())
+    }
+
+    fn post_bind_group(
+        &self,
+        uniform: usize,
+        source: usize,
+        original: usize,
+        texture: Option<ImageId>,
+    ) -> wgpu::BindGroup {
+        let layouts = self
+            .material_layouts
+            .as_ref()
+            .expect("material layouts not initialized");
+        let texture = texture
+            .and_then(|id| self.textures.get(&id))
+            .map_or(&layouts.white.view, |texture| &texture.view);
+        self.device().create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("post bind group"),
+            layout: self.post.layout.as_ref().expect("post layout not created"),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.post.uniforms[uniform].as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(
+                        self.post
+                            .sampler
+                            .as_ref()
+                            .expect("post sampler not created"),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&self.post.targets[source]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&self.post.targets[original]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(texture),
+                },
+            ],
+        })
+    }
+
+    fn create_post_target(&self, size: (u32, u32)) -> wgpu::TextureView {
+        let texture = self.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("post target"),
+            size: wgpu::Extent3d {
+                width: size.0.max(1),
+                height: size.1.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.color_format.expect("renderer not initialized"),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    /// Record the passes built by `prepare_post`, the last one drawing into `view`.
+    pub(super) fn encode_post(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        for pass in &self.post.passes {
+            let Some(pipeline) = self.post.pipelines.get(&pass.shader) else {
+                continue;
+            };
+            let target = pass.dest.map_or(view, |index| &self.post.targets[index]);
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("post pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: self.timestamp_writes("post"),
+                occlusion_query_set: None,
+            });
+            rpass.set_pipeline(pipeline);
+            rpass.set_bind_group(0, &pass.bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+            self.counters.bind_groups(1);
+            self.counters.draw();
+        }
+    }
+}
+
+/// Keeps pixels brighter than `floats[0].x`, scaled by how much they exceed it.
+const THRESHOLD: &str = r#"
+@fragment
+fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(source_texture, post_sampler, in.uv, 0.0);
+    let luma = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let keep = max(luma - post.floats[0].x, 0.0) / max(luma, 0.0001);
+    return vec4<f32>(color.rgb * keep, 1.0);
+}
+"#;
+
+/// One direction (`floats[0].xy`) of a gaussian blur with radius `floats[0].z` pixels.
+const BLUR: &str = r#"
+@fragment
+fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+    let radius = post.floats[0].z;
+    let step = post.floats[0].xy / post.resolution;
+    let sigma = max(radius / 3.0, 0.0001);
+    let taps = i32(min(ceil(radius), 32.0));
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i = -taps; i <= taps; i++) {
+        let x = f32(i);
+        let weight = exp(-(x * x) / (2.0 * sigma * sigma));
+        sum += textureSampleLevel(source_texture, post_sampler, in.uv + step * x, 0.0) * weight;
+        total += weight;
+    }
+    return sum / total;
+}
+"#;
+
+/// Adds the blurred highlights back onto the effect input.
+const BLOOM_COMPOSITE: &str = r#"
+@fragment
+fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+    let base = textureSampleLevel(original_texture, post_sampler, in.uv, 0.0);
+    let glow = textureSampleLevel(source_texture, post_sampler, in.uv, 0.0);
+    return vec4<f32>(base.rgb + glow.rgb * post.floats[0].x, base.a);
+}
+"#;
+
+const VIGNETTE: &str = r#"
+@fragment
+fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(source_texture, post_sampler, in.uv, 0.0);
+    let p = post.floats[0];
+    let d = length(in.uv - vec2<f32>(0.5)) * 1.41421356;
+    let edge = smoothstep(p.y, p.y + max(p.z, 0.0001), d);
+    return vec4<f32>(color.rgb * (1.0 - p.x * edge), color.a);
+}
+"#;
+
+/// LUT lookup in sRGB space, blending the two nearest blue slices.
+const COLOR_GRADING: &str = r#"
+fn to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(source_texture, post_sampler, in.uv, 0.0);
+    let n = f32(textureDimensions(post_texture).y);
+    let c = clamp(to_srgb(color.rgb), vec3<f32>(0.0), vec3<f32>(1.0)) * (n - 1.0);
+    let slice = floor(c.b);
+    let next = min(slice + 1.0, n - 1.0);
+    let v = (c.g + 0.5) / n;
+    let a = textureSampleLevel(post_texture, post_sampler, vec2<f32>((slice * n + c.r + 0.5) / (n * n), v), 0.0);
+    let b = textureSampleLevel(post_texture, post_sampler, vec2<f32>((next * n + c.r + 0.5) / (n * n), v), 0.0);
+    let graded = mix(a.rgb, b.rgb, c.b - slice);
+    return vec4<f32>(mix(color.rgb, graded, post.floats[0].x), color.a);
+}
+"#;
+
+const CRT: &str = r#"
+@fragment
+fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+    let p = post.floats[0];
+    var c = in.uv * 2.0 - 1.0;
+    c = c * (1.0 + p.y * dot(c, c));
+    let uv = c * 0.5 + 0.5;
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    var color = textureSampleLevel(source_texture, post_sampler, uv, 0.0);
+    let odd_row = fract(floor(in.position.y) * 0.5) * 2.0;
+    color = vec4<f32>(color.rgb * (1.0 - p.x * odd_row), color.a);
+    return select(vec4<f32>(0.0, 0.0, 0.0, 1.0), color, inside);
+}
+"#;
+
+const CHROMATIC_ABERRATION: &str = r#"
+@fragment
+fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+    let shift = (in.uv - vec2<f32>(0.5)) * 2.0 * post.floats[0].x / post.resolution;
+    let center = textureSampleLevel(source_texture, post_sampler, in.uv, 0.0);
+    let r = textureSampleLevel(source_texture, post_sampler, in.uv + shift, 0.0).r;
+    let b = textureSampleLevel(source_texture, post_sampler, in.uv - shift, 0.0).b;
+    return vec4<f32>(r, center.g, b, center.a);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::core::assets::ImageId;
+    use crate::math::Color;
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{PostEffect, PostShader, Renderer};
+
+    #[test]
+    fn effects_run_in_order_on_the_presented_frame() {
+        let Some(mut renderer) = headless(8, 4) else {
+            return;
+        };
+        let swap = PostEffect::custom(
+            "swap red and green",
+            r#"
+            @fragment
+            fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+                let c = textureSampleLevel(source_texture, post_sampler, in.uv, 0.0);
+                return vec4<f32>(c.g, c.r, c.b, c.a);
+            }
+            "#,
+        )
+        .unwrap();
+        let add_blue = PostShader::new(
+            "add blue",
+            r#"
+            @fragment
+            fn fs_main(in: PostVsOut) -> @location(0) vec4<f32> {
+                let c = textureSampleLevel(source_texture, post_sampler, in.uv, 0.0);
+                return c + vec4<f32>(0.0, 0.0, post.floats[0].x, 0.0);
+            }
+            "#,
+        )
+        .unwrap()
+        .with_float(0, 1.0);
+
+        // Blur and bloom leave a uniform frame unchanged (nothing above the threshold).
+        let effects = [
+            swap,
+            PostEffect::blur(3.0),
+            PostEffect::Custom(add_blue),
+            PostEffect::Bloom {
+                threshold: 2.0,
+                intensity: 1.0,
+                radius: 2.0,
+            },
+        ];
+        renderer.set_post_effects(&effects).unwrap();
+        renderer.set_clear_color(Color::RED.to_linear_rgba());
+        renderer.present().unwrap();
+        let data = renderer.read_pixels().unwrap();
+        assert!(data.chunks(4).all(|p| p == [0, 255, 255, 255]));
+
+        renderer.set_post_effects(&[]).unwrap();
+        renderer.present().unwrap();
+        let data = renderer.read_pixels().unwrap();
+        assert!(data.chunks(4).all(|p| p == [255, 0, 0, 255]));
+
+        // The remaining built-ins compile and run (an unknown LUT samples white).
+        let effects = [
+            PostEffect::vignette(),
+            PostEffect::crt(),
+            PostEffect::chromatic_aberration(2.0),
+            PostEffect::color_grading(ImageId::new()),
+        ];
+        renderer.set_post_effects(&effects).unwrap();
+        renderer.present().unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+use super::atlas::Atlas;
+use super::gradient::GradientFills;
+use super::post::PostChain;
+use super::{HeadlessConfig, WgpuRenderer};
+use crate::render::renderer::{RenderError, RenderResult};
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Where the renderer draws, kept so `recover` can rebuild the same setup.
+#[derive(Clone)]
+pub(super) enum InitTarget {
+    Window {
+        window: RawWindowHandle,
+        display: RawDisplayHandle,
+        vsync: bool,
+        msaa_samples: u32,
+    },
+    Headless(HeadlessConfig),
+}
+
+impl WgpuRenderer {
+    /// Flag the device as lost when wgpu reports it, so the next `present` fails with
+    /// `RenderError::DeviceLost`.
+    pub(super) fn watch_device_loss(&mut self) {
+        let lost = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&lost);
+        self.device()
+            .set_device_lost_callback(move |reason, message| {
+                // Dropping the device on purpose (recovery, shutdown) is not a loss.
+                if matches!(reason, wgpu::DeviceLostReason::Destroyed) {
+                    return;
+                }
+                log::error!("GPU device lost ({:?}): {}", reason, message);
+                flag.store(true, Ordering::SeqCst);
+            });
+        self.device_lost = lost;
+    }
+
+    pub(super) fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::SeqCst)
+    }
+
+    /// Drop every GPU resource and initialize again for the same window or headless
+    /// target, at the current size.
+    pub(super) fn recreate(&mut self) -> RenderResult<()> {
+        let target = self.init_target.clone().ok_or_else(|| {
+            RenderError::InitFailed("cannot recover a renderer that was never initialized".into())
+        })?;
+        log::warn!("Re-creating the wgpu device and surface");
+        self.reset_gpu_state();
+        match target {
+            InitTarget::Window {
+                window,
+                display,
+                vsync,
+                msaa_samples,
+            } => self.init_window(window, display, vsync, msaa_samples),
+            InitTarget::Headless(mut config) => {
+                (config.width, config.height) = self.size;
+                self.init_headless(&config)
+            }
+        }
+    }
+
+    /// Forget everything created on the old device. Settings (atlas config, GPU timing,
+    /// clear color) are kept.
+    fn reset_gpu_state(&mut self) {
+        self.clear_frame();
+        self.textures.clear();
+        self.image_textures.clear();
+        let atlas_config = self.atlas.config;
+        self.atlas = Atlas::new();
+        self.atlas.config = atlas_config;
+        self.canvases.clear();
+        self.materials.clear();
+        self.material_layouts = None;
+        self.stencil_pipelines = None;
+        self.stencil_views.clear();
+        self.msaa_views.clear();
+        self.post = PostChain::default();
+        self.vertex_buffer.reset();
+        self.sprite_instance_buffer.reset();
+        self.mesh_vertex_buffer.reset();
+        self.mesh_index_buffer.reset();
+        self.gradient_fills = GradientFills::default();
+        self.sdf_vertex_buffer.reset();
+        self.gpu_timer = None;
+        self.captured = None;
+        self.offscreen = None;
+        self.surface = None;
+        self.config = None;
+        self.queue = None;
+        self.device = None;
+        self.adapter = None;
+        self.instance = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::assets::ImageId;
+    use crate::render::Renderer;
+    use crate::render::renderer::RenderError;
+    use crate::render::wgpu_renderer::headless;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn lost_device_is_reported_and_recovered() {
+        let Some(mut renderer) = headless(4, 4) else {
+            return;
+        };
+        let image = ImageId::new();
+        renderer
+            .upload_image(image, 1, 1, &[255, 0, 0, 255])
+            .unwrap();
+
+        renderer.device_lost.store(true, Ordering::SeqCst);
+        assert!(matches!(renderer.present(), Err(RenderError::DeviceLost)));
+
+        renderer.recover().unwrap();
+        assert!(!renderer.is_device_lost());
+        assert!(renderer.textures.is_empty());
+        renderer
+            .upload_image(image, 1, 1, &[255, 0, 0, 255])
+            .unwrap();
+        renderer.present().unwrap();
+    }
+}
@@ -0,0 +1,224 @@
+use super::{DrawBatch, WgpuRenderer, blend_state, stencil};
+use crate::render::SdfVertex;
+use crate::render::blend::BlendMode;
+
+/// SDF shape vertex: position in NDC, position relative to the shape's center, and the
+/// rounded box (half size, corner radius, outline) with its linear colors.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(super) struct SdfVertexGPU {
+    pos: [f32; 2],
+    local: [f32; 2],
+    half_size: [f32; 2],
+    radius: f32,
+    outline: f32,
+    color: [f32; 4],
+    outline_color: [f32; 4],
+}
+
+impl SdfVertexGPU {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x2,
+        3 => Float32,
+        4 => Float32,
+        5 => Float32x4,
+        6 => Float32x4,
+    ];
+
+    fn buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SdfVertexGPU>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+impl WgpuRenderer {
+    /// SDF shape pipelines, one per `BlendMode`. Coverage comes from the distance to
+    /// the edge and its screen-space derivative, so edges stay one pixel wide at any
+    /// scale.
+    pub(super) fn build_sdf_pipelines(
+        &self,
+        format: wgpu::TextureFormat,
+    ) -> Vec<wgpu::RenderPipeline> {
+        let device = self.device();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sdf shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                r#"
+                struct SdfVsOut {
+                    @builtin(position) pos: vec4<f32>,
+                    @location(0) local: vec2<f32>,
+                    @location(1) half_size: vec2<f32>,
+                    @location(2) radius: f32,
+                    @location(3) outline: f32,
+                    @location(4) color: vec4<f32>,
+                    @location(5) outline_color: vec4<f32>,
+                };
+
+                @vertex
+                fn vs(
+                    @location(0) pos: vec2<f32>,
+                    @location(1) local: vec2<f32>,
+                    @location(2) half_size: vec2<f32>,
+                    @location(3) radius: f32,
+                    @location(4) outline: f32,
+                    @location(5) color: vec4<f32>,
+                    @location(6) outline_color: vec4<f32>,
+                ) -> SdfVsOut {
+                    var out: SdfVsOut;
+                    out.pos = vec4<f32>(pos, 0.0, 1.0);
+                    out.local = local;
+                    out.half_size = half_size;
+                    out.radius = radius;
+                    out.outline = outline;
+                    out.color = color;
+                    out.outline_color = outline_color;
+                    return out;
+                }
+
+                @fragment
+                fn fs(input: SdfVsOut) -> @location(0) vec4<f32> {
+                    // Signed distance to the rounded box, negative inside.
+                    let q = abs(input.local) - input.half_size + vec2<f32>(input.radius);
+                    let d = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0)
+                        - input.radius;
+                    // One pixel, in local units.
+                    let aa = max(length(vec2<f32>(dpdx(d), dpdy(d))), 1e-6);
+                    let coverage = clamp(0.5 - d / aa, 0.0, 1.0);
+                    let fill = clamp(0.5 - (d + input.outline) / aa, 0.0, 1.0);
+                    let color = select(
+                        input.color,
+                        mix(input.outline_color, input.color, fill),
+                        input.outline > 0.0,
+                    );
+                    return vec4<f32>(color.rgb, color.a * coverage);
+                }
+                "#
+                .into(),
+            ),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("sdf pipeline layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+        let vertex_layout = SdfVertexGPU::buffer_layout();
+
+        BlendMode::ALL
+            .iter()
+            .map(|&blend| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("sdf pipeline"),
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: Some("vs"),
+                        buffers: std::slice::from_ref(&vertex_layout),
+                        compilation_options: Default::default(),
+                    },
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        cull_mode: None,
+                        ..Default::default()
+                    },
+                    depth_stencil: Some(stencil::content_depth_stencil()),
+                    multisample: self.multisample_state(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: Some("fs"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: blend_state(blend),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: Default::default(),
+                    }),
+                    cache: None,
+                    multiview: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Queue SDF shapes. Consecutive shapes with the same blend mode share one draw call.
+    pub(super) fn queue_sdf_shapes(&mut self, vertices: &[SdfVertex], blend: BlendMode) {
+        let start = self.sdf_vertices.len() as u32;
+        self.sdf_vertices
+            .extend(vertices.iter().map(|v| SdfVertexGPU {
+                pos: v.pos,
+                local: v.local,
+                half_size: v.half_size,
+                radius: v.radius,
+                outline: v.outline,
+                color: v.color,
+                outline_color: v.outline_color,
+            }));
+        let end = self.sdf_vertices.len() as u32;
+
+        match self.batches.last_mut() {
+            Some(DrawBatch::SdfShapes {
+                blend: batch_blend,
+                range,
+            }) if *batch_blend == blend && range.end == start => range.end = end,
+            _ if start < end => self.batches.push(DrawBatch::SdfShapes {
+                blend,
+                range: start..end,
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{Circle, Drawable, RenderContext, Renderer, RoundedRect, SoftwareRenderer};
+
+    #[test]
+    fn sdf_shapes_match_the_software_renderer() {
+        let (w, h) = (32, 16);
+        let Some(mut gpu) = headless(w, h) else {
+            return;
+        };
+        let mut cpu = SoftwareRenderer::new(w, h);
+        let mut ctx = RenderContext::new((w, h));
+        Circle::new(Vec2::new(8.0, 8.0), 6.5, Color::RED)
+            .with_smooth_edges()
+            .draw(&mut ctx);
+        RoundedRect::new(
+            Vec2::new(17.0, 2.0),
+            Vec2::new(13.0, 12.0),
+            4.0,
+            Color::BLUE,
+        )
+        .with_outline(2.0, Color::WHITE)
+        .draw(&mut ctx);
+
+        for renderer in [&mut gpu as &mut dyn Renderer, &mut cpu] {
+            renderer.set_clear_color([0.0, 0.0, 0.0, 1.0]);
+            renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+            renderer.present().unwrap();
+        }
+
+        let gpu_pixels = gpu.read_pixels().unwrap();
+        let cpu_pixels = cpu.read_pixels();
+        let smoothed = gpu_pixels
+            .chunks(4)
+            .filter(|px| px[0] > 0 && px[0] < 255)
+            .count();
+        assert!(smoothed > 0, "edges are not smoothed");
+        let mismatched = gpu_pixels
+            .chunks(4)
+            .zip(cpu_pixels.chunks(4))
+            .filter(|(a, b)| a.iter().zip(b.iter()).any(|(x, y)| x.abs_diff(*y) > 24))
+            .count();
+        // Derivatives are estimated per 2x2 quad on the GPU: edge pixels may differ.
+        assert!(mismatched <= 8, "{mismatched} pixels differ");
+        assert_eq!(gpu.render_stats().draw_calls, 1);
+    }
+}
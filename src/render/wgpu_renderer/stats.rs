@@ -0,0 +1,283 @@
+use super::WgpuRenderer;
+use crate::render::renderer::{RenderError, RenderResult};
+use crate::render::stats::{PassTiming, RenderStats};
+use std::cell::{Cell, RefCell};
+
+/// Passes timed per frame; later passes are not timed.
+const MAX_TIMED_PASSES: u32 = 32;
+
+/// Counters of the frame being recorded. Cells so `&self` encoding code can count.
+#[derive(Default)]
+pub(super) struct FrameCounters {
+    draw_calls: Cell<u32>,
+    vertices: Cell<u32>,
+    sprites: Cell<u32>,
+    bind_group_switches: Cell<u32>,
+    uploaded_bytes: Cell<u64>,
+}
+
+impl FrameCounters {
+    pub fn draw(&self) {
+        self.draw_calls.set(self.draw_calls.get() + 1);
+    }
+
+    pub fn bind_groups(&self, count: u32) {
+        self.bind_group_switches
+            .set(self.bind_group_switches.get() + count);
+    }
+
+    pub fn upload(&self, bytes: usize) {
+        self.uploaded_bytes
+            .set(self.uploaded_bytes.get() + bytes as u64);
+    }
+
+    pub fn geometry(&self, vertices: usize, sprites: usize) {
+        self.vertices.set(self.vertices.get() + vertices as u32);
+        self.sprites.set(self.sprites.get() + sprites as u32);
+    }
+
+    /// Counted values so far, resetting every counter.
+    fn take(&self) -> RenderStats {
+        RenderStats {
+            draw_calls: self.draw_calls.take(),
+            vertices: self.vertices.take(),
+            sprites: self.sprites.take(),
+            bind_group_switches: self.bind_group_switches.take(),
+            uploaded_bytes: self.uploaded_bytes.take(),
+            ..RenderStats::default()
+        }
+    }
+}
+
+/// Timestamp queries written at the start and end of each render pass, resolved at the
+/// end of the frame and read back by waiting for the GPU.
+pub(super) struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    /// Labels of the passes timed so far this frame, in query order.
+    passes: RefCell<Vec<&'static str>>,
+}
+
+impl GpuTimer {
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let size = (MAX_TIMED_PASSES * 2) as u64 * wgpu::QUERY_SIZE as u64;
+        Self {
+            query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("pass timestamps"),
+                ty: wgpu::QueryType::Timestamp,
+                count: MAX_TIMED_PASSES * 2,
+            }),
+            resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("timestamp resolve buffer"),
+                size,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            readback_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("timestamp readback buffer"),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            period: queue.get_timestamp_period(),
+            passes: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Timestamp writes for the next pass, or `None` once the frame's queries are used up.
+    pub fn pass_writes(&self, label: &'static str) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let mut passes = self.passes.borrow_mut();
+        let index = passes.len() as u32;
+        if index == MAX_TIMED_PASSES {
+            return None;
+        }
+        passes.push(label);
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index * 2),
+            end_of_pass_write_index: Some(index * 2 + 1),
+        })
+    }
+
+    /// Copy the frame's timestamps to the readback buffer. Record it after the last
+    /// timed pass of the frame.
+    fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let count = self.passes.borrow().len() as u32 * 2;
+        if count == 0 {
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        let bytes = count as u64 * wgpu::QUERY_SIZE as u64;
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, bytes);
+    }
+
+    /// Wait for the frame to finish and return its pass timings, starting a new frame.
+    fn read(&self, device: &wgpu::Device) -> RenderResult<Vec<PassTiming>> {
+        let passes = std::mem::take(&mut *self.passes.borrow_mut());
+        if passes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let slice = self
+            .readback_buffer
+            .slice(..passes.len() as u64 * 2 * wgpu::QUERY_SIZE as u64);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|e| RenderError::RenderFailed(format!("timestamp poll failed: {}", e)))?;
+        rx.recv()
+            .map_err(|e| RenderError::RenderFailed(format!("timestamp channel closed: {}", e)))?
+            .map_err(|e| RenderError::RenderFailed(format!("timestamp map failed: {}", e)))?;
+
+        let timings = {
+            let mapped = slice.get_mapped_range();
+            let ticks: Vec<u64> = mapped
+                .chunks_exact(8)
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("8-byte chunk")))
+                .collect();
+            passes
+                .iter()
+                .zip(ticks.chunks_exact(2))
+                .map(|(&label, pair)| PassTiming {
+                    label,
+                    millis: pair[1].saturating_sub(pair[0]) as f32 * self.period / 1_000_000.0,
+                })
+                .collect()
+        };
+        self.readback_buffer.unmap();
+        Ok(timings)
+    }
+}
+
+impl WgpuRenderer {
+    /// Time render passes on the GPU from the next frame on, if the adapter supports
+    /// timestamp queries. Reading the timings back waits for the GPU every frame.
+    pub(super) fn enable_gpu_timing(&mut self, enabled: bool) {
+        self.gpu_timing = enabled;
+        if !enabled {
+            self.gpu_timer = None;
+        }
+    }
+
+    /// Create the GPU timer once timing is requested and the device is ready.
+    pub(super) fn ensure_gpu_timer(&mut self) {
+        if !self.gpu_timing || self.gpu_timer.is_some() {
+            return;
+        }
+        let (Some(device), Some(queue)) = (self.device.as_ref(), self.queue.as_ref()) else {
+            return;
+        };
+        if device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            self.gpu_timer = Some(GpuTimer::new(device, queue));
+        } else {
+            log::warn!("GPU timing requested but timestamp queries are not supported");
+            self.gpu_timing = false;
+        }
+    }
+
+    /// Timestamp writes for a pass labelled `label`, if GPU timing is on.
+    pub(super) fn timestamp_writes(
+        &self,
+        label: &'static str,
+    ) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        self.gpu_timer.as_ref()?.pass_writes(label)
+    }
+
+    /// Record the copy of this frame's timestamps into the frame's last encoder.
+    pub(super) fn resolve_gpu_timer(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(timer) = &self.gpu_timer {
+            timer.resolve(encoder);
+        }
+    }
+
+    /// Drop the counters of a frame that was not presented.
+    pub(super) fn discard_frame_stats(&mut self) {
+        self.counters.take();
+    }
+
+    /// Close the presented frame's counters into `last_stats`.
+    pub(super) fn finish_frame_stats(&mut self) {
+        let mut stats = self.counters.take();
+        stats.texture_memory_bytes = self.texture_memory_bytes();
+        if let Some(timer) = &self.gpu_timer {
+            match timer.read(self.device()) {
+                Ok(passes) => stats.gpu_passes = passes,
+                Err(e) => log::error!("Failed to read GPU timings: {}", e),
+            }
+        }
+        self.last_stats = stats;
+    }
+
+    /// Bytes held by standalone image textures (with their mip chains), atlas pages
+    /// and canvases (with their multisampled textures).
+    fn texture_memory_bytes(&self) -> u64 {
+        let bytes = |texture: &wgpu::Texture| {
+            (0..texture.mip_level_count())
+                .map(|level| {
+                    let width = (texture.width() >> level).max(1) as u64;
+                    let height = (texture.height() >> level).max(1) as u64;
+                    width * height * 4 * texture.sample_count() as u64
+                })
+                .sum::<u64>()
+        };
+        let images = self
+            .image_textures
+            .values()
+            .map(|image| bytes(&image.texture));
+        let pages = self.atlas.pages.iter().map(|page| bytes(&page.texture));
+        let canvases = self
+            .canvases
+            .values()
+            .map(|canvas| bytes(&canvas.texture) + canvas.msaa.as_ref().map_or(0, bytes));
+        images.chain(pages).chain(canvases).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::assets::ImageId;
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{Drawable, Rectangle, RenderContext, Renderer, SpriteDrawData};
+
+    #[test]
+    fn stats_count_the_last_presented_frame() {
+        let Some(mut renderer) = headless(8, 8) else {
+            return;
+        };
+        renderer.set_gpu_timing(true);
+        let texture = ImageId::new();
+        renderer
+            .upload_image(texture, 1, 1, &[255, 255, 255, 255])
+            .unwrap();
+
+        let mut ctx = RenderContext::new((8, 8));
+        Rectangle::new(Vec2::ZERO, Vec2::new(4.0, 4.0), Color::RED).draw(&mut ctx);
+        ctx.draw_sprite(SpriteDrawData::new(texture, 2, 2));
+        ctx.draw_sprite(SpriteDrawData::new(texture, 2, 2));
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+
+        let stats = renderer.render_stats();
+        assert_eq!((stats.draw_calls, stats.vertices, stats.sprites), (2, 6, 2));
+        assert_eq!(stats.bind_group_switches, 1);
+        assert!(stats.uploaded_bytes > 0);
+        assert!(stats.texture_memory_bytes > 0);
+        if !stats.gpu_passes.is_empty() {
+            assert_eq!(stats.gpu_passes[0].label, "scene");
+        }
+
+        renderer.present().unwrap();
+        let stats = renderer.render_stats();
+        assert_eq!(
+            (stats.draw_calls, stats.sprites, stats.uploaded_bytes),
+            (0, 0, 0)
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use super::WgpuRenderer;
+
+/// Format of the stencil attachment shared by every render pass.
+pub(super) const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Stencil8;
+
+/// Stencil state of every color pipeline: draw only where the stencil value equals
+/// the reference (the number of active masks, `0` without masks).
+pub(super) fn content_depth_stencil() -> wgpu::DepthStencilState {
+    depth_stencil(
+        wgpu::CompareFunction::Equal,
+        wgpu::StencilOperation::Keep,
+        0,
+    )
+}
+
+fn depth_stencil(
+    compare: wgpu::CompareFunction,
+    pass_op: wgpu::StencilOperation,
+    write_mask: u32,
+) -> wgpu::DepthStencilState {
+    let face = wgpu::StencilFaceState {
+        compare,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op,
+    };
+    wgpu::DepthStencilState {
+        format: STENCIL_FORMAT,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: 0xff,
+            write_mask,
+        },
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+/// Pipelines that only touch the stencil buffer, drawing shape vertices.
+pub(super) struct StencilPipelines {
+    /// Increments covered pixels whose value equals the reference. Drawing nested
+    /// masks with references `0..n` leaves `n` only where they all overlap.
+    pub write: wgpu::RenderPipeline,
+    /// Resets covered pixels to zero (drawn as a full-screen triangle).
+    pub clear: wgpu::RenderPipeline,
+}
+
+impl WgpuRenderer {
+    pub(super) fn build_stencil_pipelines(&self, format: wgpu::TextureFormat) -> StencilPipelines {
+        let device = self.device();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("stencil shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                r#"
+                @vertex
+                fn vs(@location(0) pos: vec2<f32>) -> @builtin(position) vec4<f32> {
+                    return vec4<f32>(pos, 0.0, 1.0);
+                }
+
+                @fragment
+                fn fs() -> @location(0) vec4<f32> {
+                    return vec4<f32>(0.0);
+                }
+                "#
+                .into(),
+            ),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("stencil pipeline layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label, depth_stencil| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs"),
+                    buffers: std::slice::from_ref(&self.vertex_buffer_layout),
+                    compilation_options: Default::default(),
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                depth_stencil: Some(depth_stencil),
+                multisample: self.multisample_state(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::empty(),
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                cache: None,
+                multiview: None,
+            })
+        };
+
+        StencilPipelines {
+            write: pipeline(
+                "stencil write pipeline",
+                depth_stencil(
+                    wgpu::CompareFunction::Equal,
+                    wgpu::StencilOperation::IncrementClamp,
+                    0xff,
+                ),
+            ),
+            clear: pipeline(
+                "stencil clear pipeline",
+                depth_stencil(
+                    wgpu::CompareFunction::Always,
+                    wgpu::StencilOperation::Zero,
+                    0xff,
+                ),
+            ),
+        }
+    }
+
+    /// Stencil attachment for a target of `size` pixels. Targets of the same size
+    /// share one texture; it is cleared at the start of every pass.
+    pub(super) fn stencil_view(&mut self, size: (u32, u32)) -> wgpu::TextureView {
+        let size = (size.0.max(1), size.1.max(1));
+        if let Some(view) = self.stencil_views.get(&size) {
+            return view.clone();
+        }
+
+        let texture = self.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("stencil buffer"),
+            size: wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: STENCIL_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.stencil_views.insert(size, view.clone());
+        view
+    }
+}
@@ -0,0 +1,368 @@
+use super::WgpuRenderer;
+use crate::core::assets::{AddressMode, FilterMode, ImageId, SamplerOptions, check_rgba8};
+use crate::math::Color;
+use crate::render::renderer::{RenderError, RenderResult, validate_region};
+
+pub(super) struct TextureGpu {
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    /// Built once at upload time and reused by every sprite using this texture.
+    pub bind_group: wgpu::BindGroup,
+}
+
+/// Texture owned by a single uploaded (not packed) image, kept to update it in place.
+pub(super) struct ImageTexture {
+    pub texture: wgpu::Texture,
+    sampler: SamplerOptions,
+}
+
+impl WgpuRenderer {
+    /// Create (or replace) the texture for `id`, with a mip chain if requested. Small
+    /// images go into a shared atlas page instead.
+    pub(super) fn upload_texture(
+        &mut self,
+        id: ImageId,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        sampler: &SamplerOptions,
+    ) -> RenderResult<()> {
+        check_rgba8(width, height, data).map_err(RenderError::InvalidTexture)?;
+
+        if self.pack_image(id, width, height, data, sampler) {
+            return Ok(());
+        }
+
+        let levels = if sampler.mipmaps {
+            mip_chain(width, height, data)
+        } else {
+            vec![(width, height, data.to_vec())]
+        };
+
+        // Same size and sampler: overwrite the existing texture, keeping its bind group.
+        if let Some(existing) = self.image_textures.get(&id)
+            && (existing.texture.width(), existing.texture.height()) == (width, height)
+            && existing.sampler == *sampler
+        {
+            for (level, (width, height, data)) in levels.iter().enumerate() {
+                self.write_rgba8(
+                    &existing.texture,
+                    level as u32,
+                    (0, 0),
+                    (*width, *height),
+                    data,
+                );
+            }
+            return Ok(());
+        }
+
+        let texture = self.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("sprite texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: levels.len() as u32,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        for (level, (width, height, data)) in levels.iter().enumerate() {
+            self.write_rgba8(&texture, level as u32, (0, 0), (*width, *height), data);
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let texture_gpu = self.texture_gpu(view, sampler);
+        self.textures.insert(id, texture_gpu);
+        self.image_textures.insert(
+            id,
+            ImageTexture {
+                texture,
+                sampler: *sampler,
+            },
+        );
+        Ok(())
+    }
+
+    /// Overwrite a rect of an uploaded image's base level.
+    pub(super) fn update_texture_region(
+        &mut self,
+        id: ImageId,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> RenderResult<()> {
+        if self.atlas.regions.contains_key(&id) {
+            return self.update_packed_region(id, x, y, width, height, data);
+        }
+        let existing = self.image_textures.get(&id).ok_or_else(|| {
+            RenderError::InvalidTexture(format!("no image uploaded for {:?}", id))
+        })?;
+        let texture = &existing.texture;
+        validate_region(
+            (texture.width(), texture.height()),
+            (x, y, width, height),
+            data,
+        )?;
+        if texture.mip_level_count() > 1 {
+            return Err(RenderError::InvalidTexture(
+                "partial updates of mipmapped images are not supported".to_string(),
+            ));
+        }
+        self.write_rgba8(texture, 0, (x, y), (width, height), data);
+        Ok(())
+    }
+
+    /// Drop every texture of `id`, freeing its atlas slot.
+    pub(super) fn remove_texture(&mut self, id: ImageId) {
+        self.textures.remove(&id);
+        self.image_textures.remove(&id);
+        self.release_region(id);
+        self.canvases.remove(&id);
+    }
+
+    /// Write tightly packed RGBA8 rows to a rect of one mip level of `texture`.
+    pub(super) fn write_rgba8(
+        &self,
+        texture: &wgpu::Texture,
+        mip_level: u32,
+        origin: (u32, u32),
+        size: (u32, u32),
+        data: &[u8],
+    ) {
+        self.counters.upload(data.len());
+        self.queue().write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level,
+                origin: wgpu::Origin3d {
+                    x: origin.0,
+                    y: origin.1,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * size.0),
+                rows_per_image: Some(size.1),
+            },
+            wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Sampler and cached bind group for a sprite texture view.
+    pub(super) fn texture_gpu(
+        &self,
+        view: wgpu::TextureView,
+        options: &SamplerOptions,
+    ) -> TextureGpu {
+        let device = self.device();
+        let address_mode = match options.address_mode {
+            AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            AddressMode::Repeat => wgpu::AddressMode::Repeat,
+            AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        };
+        let filter = match options.filter {
+            FilterMode::Nearest => wgpu::FilterMode::Nearest,
+            FilterMode::Linear => wgpu::FilterMode::Linear,
+        };
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("sprite sampler"),
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: if options.mipmaps {
+                filter
+            } else {
+                wgpu::FilterMode::Nearest
+            },
+            ..Default::default()
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sprite bind group"),
+            layout: self
+                .sprite_bind_group_layout
+                .as_ref()
+                .expect("sprite bind group layout not initialized"),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        TextureGpu {
+            view,
+            sampler,
+            bind_group,
+        }
+    }
+}
+
+/// Full mip chain of an sRGB RGBA8 image, base level first, down to 1x1.
+///
+/// Each level is a 2x2 box filter of the previous one, averaged in linear space so
+/// minified sprites do not darken.
+fn mip_chain(width: u32, height: u32, data: &[u8]) -> Vec<(u32, u32, Vec<u8>)> {
+    let mut levels = vec![(width, height, data.to_vec())];
+    while let Some((w, h, prev)) = levels.last()
+        && (*w > 1 || *h > 1)
+    {
+        let (w, h) = (*w, *h);
+        let (next_w, next_h) = ((w / 2).max(1), (h / 2).max(1));
+        let texel = |x: u32, y: u32, c: usize| {
+            let index = ((y.min(h - 1) * w + x.min(w - 1)) * 4) as usize + c;
+            let value = prev[index] as f32 / 255.0;
+            if c < 3 {
+                Color::srgb_to_linear(value)
+            } else {
+                value
+            }
+        };
+
+        let mut next = Vec::with_capacity((next_w * next_h * 4) as usize);
+        for y in 0..next_h {
+            for x in 0..next_w {
+                for c in 0..4 {
+                    let (x0, y0) = (x * 2, y * 2);
+                    let sum = texel(x0, y0, c)
+                        + texel(x0 + 1, y0, c)
+                        + texel(x0, y0 + 1, c)
+                        + texel(x0 + 1, y0 + 1, c);
+                    let avg = sum / 4.0;
+                    let encoded = if c < 3 {
+                        Color::linear_to_srgb(avg)
+                    } else {
+                        avg
+                    };
+                    next.push((encoded.clamp(0.0, 1.0) * 255.0).round() as u8);
+                }
+            }
+        }
+        levels.push((next_w, next_h, next));
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mip_chain;
+    use crate::core::assets::{AddressMode, ImageId, SamplerOptions};
+    use crate::math::{Color, Vec2};
+    use crate::render::wgpu_renderer::headless;
+    use crate::render::{RenderContext, Renderer, SoftwareRenderer, SpriteDrawData};
+
+    /// A red/blue 2x1 texture tiled twice across an 8x1 target with nearest filtering.
+    fn render_tiled(renderer: &mut dyn Renderer) {
+        let texture = ImageId::new();
+        let sampler = SamplerOptions::pixel_art().with_address_mode(AddressMode::Repeat);
+        let data = [255, 0, 0, 255, 0, 0, 255, 255];
+        renderer
+            .upload_image_with_sampler(texture, 2, 1, &data, &sampler)
+            .unwrap();
+
+        let mut ctx = RenderContext::new((8, 1));
+        let mut sprite = SpriteDrawData::new(texture, 8, 1);
+        sprite.origin = Vec2::ZERO;
+        sprite.uv_max = Vec2::new(2.0, 1.0);
+        ctx.draw_sprite(sprite);
+
+        renderer.set_clear_color(Color::BLACK.to_linear_rgba());
+        renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+        renderer.present().unwrap();
+    }
+
+    fn assert_tiled(data: &[u8]) {
+        let reds: Vec<u8> = data.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, [255, 255, 0, 0, 255, 255, 0, 0]);
+    }
+
+    #[test]
+    fn nearest_repeat_sampler_tiles_crisply() {
+        let mut cpu = SoftwareRenderer::new(8, 1);
+        render_tiled(&mut cpu);
+        assert_tiled(&cpu.read_pixels());
+
+        if let Some(mut gpu) = headless(8, 1) {
+            render_tiled(&mut gpu);
+            assert_tiled(&gpu.read_pixels().unwrap());
+        }
+    }
+
+    /// Upload a red 2x1 image, paint its right pixel blue, stretch it over a 2x1 target,
+    /// then unload it and draw again. Returns both frames.
+    fn update_then_unload(renderer: &mut dyn Renderer) -> Vec<Vec<u8>> {
+        let texture = ImageId::new();
+        let sampler = SamplerOptions::pixel_art();
+        let red = [255, 0, 0, 255].repeat(2);
+        renderer
+            .upload_image_with_sampler(texture, 2, 1, &red, &sampler)
+            .unwrap();
+        renderer
+            .update_image_region(texture, 1, 0, 1, 1, &[0, 0, 255, 255])
+            .unwrap();
+        assert!(
+            renderer
+                .update_image_region(texture, 1, 0, 2, 1, &[0; 8])
+                .is_err()
+        );
+
+        let mut frames = Vec::new();
+        for _ in 0..2 {
+            let mut ctx = RenderContext::new((2, 1));
+            let mut sprite = SpriteDrawData::new(texture, 2, 1);
+            sprite.origin = Vec2::ZERO;
+            ctx.draw_sprite(sprite);
+            renderer.set_clear_color(Color::BLACK.to_linear_rgba());
+            renderer.submit_draw_list(&ctx.draw_list(), ctx.size);
+            renderer.capture_next_frame();
+            renderer.present().unwrap();
+            frames.push(renderer.take_captured_frame().unwrap().data);
+            renderer.unload_image(texture);
+        }
+        frames
+    }
+
+    #[test]
+    fn region_updates_and_unload_reach_the_texture() {
+        let expected = vec![
+            vec![255, 0, 0, 255, 0, 0, 255, 255],
+            vec![0, 0, 0, 255, 0, 0, 0, 255],
+        ];
+        let mut cpu = SoftwareRenderer::new(2, 1);
+        assert_eq!(update_then_unload(&mut cpu), expected);
+
+        if let Some(mut gpu) = headless(2, 1) {
+            assert_eq!(update_then_unload(&mut gpu), expected);
+        }
+    }
+
+    #[test]
+    fn mip_chain_halves_down_to_one_pixel() {
+        let data = [255u8, 255, 255, 255, 0, 0, 0, 255].repeat(4 * 2);
+        let levels = mip_chain(4, 4, &data);
+        let sizes: Vec<_> = levels.iter().map(|(w, h, _)| (*w, *h)).collect();
+        assert_eq!(sizes, vec![(4, 4), (2, 2), (1, 1)]);
+        // Black and white average to 0.5 in linear space, ~188 in sRGB.
+        assert_eq!(levels[2].2, vec![188, 188, 188, 255]);
+    }
+}